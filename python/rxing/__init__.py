@@ -1,73 +1,643 @@
 from .rxing_lib import (
     decode_luma_pixels as _decode_luma_pixels,
+    decode_luma16_pixels,
+    decode_pil_image,
+    decode_numpy,
+    decode_rgb_pixels,
+    decode_yuv,
+    decode_from_bit_matrix,
+    diagnose_luma_pixels,
+    decode_luma_candidates,
+    binarize_luma_pixels,
+    detect_luma_pixels,
+    Detection,
+    ParsedResult,
+    to_luma,
     decode_image_bytes as _decode_image_bytes,
     decode_from_file_path as _decode_from_file_path,
+    decode_multiple_image_bytes,
+    decode_multiple_luma_pixels,
+    decode_multiple_from_file_path,
+    decode_all_frames,
+    decode_region_grid,
+    scan_and_annotate,
+    decode_timed,
+    DecodeTiming,
+    decode_batch,
+    DecodeFailure,
+    scan_directory,
+    DirectoryScanner,
+    ReaderException,
+    NotFoundException,
+    ChecksumException,
+    FormatException,
+    WriterException,
     encode as _encode,  # Import Rust encode as _encode
+    encode_many,
+    EncodeFailure,
+    encode_gs1,
+    parse_gs1,
+    validate_encodable,
+    capacity,
+    upce_to_upca,
+    encode_to_png,
+    encode_to_svg,
+    encode_sheet,
+    encode_to_file,
+    set_timestamp_override,
+    timestamp_override,
+    supported_read_formats,
+    supported_write_formats,
     RXingResult,
+    FormatAttempt,
+    VideoScanner,
+    BarcodeReader,
+    BarcodeWriter,
+    LuminanceSource,
+    BinaryBitmap,
+    AppendCollector,
     Point,
+    QRPoints,
     BitMatrix as _RustBitMatrix,
-    BarcodeFormat,
+    BARCODE_FORMATS as _BARCODE_FORMATS,
+    METADATA_KEYS as _METADATA_KEYS,
+    DecodeHints,
+    EncodeHints,
 )
+import asyncio
+import base64
+import contextlib
+import enum
+import functools
+import io
+import json
+import os
+
 import PIL.Image
+import PIL.ImageOps
 import numpy as np
 
 
-def decode(source, hints=None):
+class _FormatEnum(str, enum.Enum):
+    # Members are the strings rxing reports (RXingResult.barcode_format, metadata
+    # keys), so they compare, hash and format like those strings and work wherever
+    # one is accepted.
+    def __str__(self):
+        return str.__str__(self)
+
+    def __format__(self, format_spec):
+        return str.__format__(self, format_spec)
+
+    @classmethod
+    def _missing_(cls, value):
+        # BarcodeFormat("QR_CODE") and BarcodeFormat("qr_code") look up by name.
+        if isinstance(value, str):
+            return cls.__members__.get(value.upper())
+        return None
+
+
+BarcodeFormat = _FormatEnum(
+    "BarcodeFormat", _BARCODE_FORMATS, module=__name__, qualname="BarcodeFormat"
+)
+BarcodeFormat.__doc__ = """
+Barcode formats known to rxing, e.g. BarcodeFormat.QR_CODE.
+
+Iterate over the class to list every format. Each member is a str equal to the
+name rxing uses in RXingResult.barcode_format ("qrcode", "code 128", ...), so
+members can be passed to any function that takes a format string and compared
+with the strings earlier releases returned.
+"""
+
+MetadataKey = _FormatEnum(
+    "MetadataKey",
+    [(key, key) for key in _METADATA_KEYS],
+    module=__name__,
+    qualname="MetadataKey",
+)
+MetadataKey.__doc__ = """
+Result metadata keys, e.g. MetadataKey.ORIENTATION.
+
+Each member is a str equal to its name, as used in RXingResult.result_metadata,
+so members work as keys of result_metadata and RXingResult.metadata and as the
+argument of get_metadata() and has_metadata().
+"""
+
+
+def decode(
+    source,
+    hints=None,
+    max_dimension=None,
+    try_scales=None,
+    rotations=None,
+    timeout_ms=None,
+    binarizer="hybrid",
+    padding=0,
+    apply_exif_orientation=True,
+    collect_points=True,
+    roi=None,
+    retry_full_resolution=True,
+):
     """
     Decodes a barcode from various sources.
 
     :param source: The source to decode from. Can be:
-                   - str: Path to an image file.
+                   - str or os.PathLike: Path to an image file.
                    - bytes: Image file content as bytes.
                    - PIL.Image.Image: A Pillow Image object.
                    - numpy.ndarray: A NumPy array representing an image.
-                                    (expects uint8, 2D for grayscale, 3D for RGB/RGBA)
-    :param hints: Optional dictionary of decoding hints.
+                                    (expects uint8, 2D for grayscale, 3D for RGB/RGBA;
+                                    2D uint16 is decoded with decode_luma16_pixels)
+    :param hints: Optional dictionary of decoding hints, or a DecodeHints object.
+    :param max_dimension: Optional bound in pixels for the longest image side. Larger
+                          images are downscaled before binarizing, which is much faster
+                          for big scans. Only used for file paths and image bytes.
+    :param retry_full_resolution: With max_dimension, decode the image again at
+                                  its full resolution when the downscaled copy
+                                  yields nothing, e.g. for dense codes in large
+                                  photos. A scan that fails both ways costs
+                                  both decodes. On by default; pass False to
+                                  give up after the downscaled decode.
+    :param try_scales: Optional list of scale factors in (0, 1], e.g. [1.0, 0.5, 0.25].
+                       Each scale is tried in order and the first success is returned.
+                       Only used for file paths and image bytes.
+                       `result_points` are always reported in full-resolution coordinates.
+    :param rotations: Optional list of clockwise angles in degrees, e.g.
+                      [0, 90, 180, 270]. The image is rotated and decoded for each
+                      angle in order; the angle that worked is reported as
+                      RXingResult.rotation. Multiples of 90 are cheap, other
+                      angles resample the whole image. Every extra angle is a full
+                      decode attempt, so a failing scan costs
+                      len(rotations) * len(try_scales) decodes. Defaults to [0].
+    :param timeout_ms: Optional time budget in milliseconds. The decode runs on a
+                       worker thread and TimeoutError is raised once the budget is
+                       spent. rxing cannot interrupt a decode, so the worker is
+                       abandoned rather than stopped: it keeps using a CPU core
+                       until it finishes and its result is discarded.
+    :param binarizer: "hybrid" (default) thresholds each 8x8 block against its
+                      neighbourhood, which copes with shadows and uneven lighting
+                      but treats blocks with less than 24 levels of contrast as
+                      background. "global_histogram" (or "global") uses one
+                      threshold from the histogram of the whole image, so it can
+                      read faint, low-contrast prints but fails on unevenly lit
+                      photos. "fixed_threshold" makes every pixel darker than 128
+                      black; pick another cutoff with "fixed_threshold:N", N
+                      from 1 to 255. "otsu" picks the single threshold that best
+                      splits the histogram into dark and light, for washed-out
+                      images with even lighting. "adaptive" compares each pixel
+                      with the mean of the window around it, for faint codes
+                      under uneven lighting; "adaptive:W" sets the window to W
+                      pixels (default: 1/8 of the larger image side, at least
+                      15), which must be wider than the largest solid dark area.
+    :param padding: Width in pixels of a white border added around the image
+                    before binarizing, for codes cropped flush to the edge (or to
+                    the symbol itself with PURE_BARCODE) that have no quiet zone.
+                    Must be non-negative. The padded copy is 2 * padding pixels
+                    wider and taller than the source, so keep it to a few modules.
+                    `result_points` stay in source coordinates.
+    :param apply_exif_orientation: Rotate or flip file paths and image bytes as
+                                   their EXIF orientation tag says, so a phone photo
+                                   is decoded the way it is displayed. `result_points`
+                                   are then in the displayed image's coordinates.
+                                   Pass False to decode the pixels as stored.
+    :param collect_points: Pass False to leave `result_points` as None and skip
+                           copying the points into every result, for
+                           high-throughput callers that only need the text.
+    :param roi: Optional (x, y, width, height) region in pixels. Only this part
+                of the image is binarized and searched, which is much faster
+                than scanning a whole high-resolution frame. It is cropped
+                before padding, scaling and rotation, and `result_points` stay
+                in full-image coordinates. A region that does not fit inside
+                the image raises ValueError.
     :return: RXingResult object.
+    :raises NotFoundException: If no barcode is found.
+    :raises ChecksumException: If a barcode is found but fails its checksum.
+    :raises FormatException: If a barcode is found but its content is malformed.
     :raises TypeError: If the source type is not supported.
+    :raises TimeoutError: If timeout_ms is set and the decode takes longer.
     """
     if hints is None:
         hints = {}
 
-    if isinstance(source, str):
-        return _decode_from_file_path(source, hints)
+    if isinstance(source, (str, os.PathLike)):
+        return _decode_from_file_path(
+            source,
+            hints,
+            max_dimension,
+            try_scales,
+            rotations,
+            timeout_ms,
+            binarizer,
+            padding,
+            apply_exif_orientation,
+            collect_points,
+            roi,
+            retry_full_resolution,
+        )
     elif isinstance(source, bytes):
-        return _decode_image_bytes(source, hints)
+        return _decode_image_bytes(
+            source,
+            hints,
+            max_dimension,
+            try_scales,
+            rotations,
+            timeout_ms,
+            binarizer,
+            padding,
+            apply_exif_orientation,
+            collect_points,
+            roi,
+            retry_full_resolution,
+        )
+    elif max_dimension is not None or try_scales is not None:
+        raise TypeError(
+            "max_dimension and try_scales are only supported for file paths and bytes."
+        )
     elif isinstance(source, PIL.Image.Image):
         img = source
         if img.mode not in ("L", "RGB", "RGBA"):
             img = img.convert("L")
-        elif img.mode != "L":
-            img = img.convert("L")
-
-        width, height = img.size
-        luma_data = img.tobytes()
-        return _decode_luma_pixels(luma_data, width, height, hints)
+        return decode_pil_image(
+            img, hints, rotations, timeout_ms, binarizer, padding, collect_points, roi
+        )
     elif isinstance(source, np.ndarray):
+        if source.dtype == np.uint16 and source.ndim == 2:
+            height, width = source.shape
+            return decode_luma16_pixels(
+                np.ascontiguousarray(source),
+                width,
+                height,
+                hints,
+                None,
+                rotations,
+                timeout_ms,
+                binarizer,
+                padding,
+                collect_points,
+                roi=roi,
+            )
         if source.dtype != np.uint8:
-            raise TypeError("NumPy array must be of dtype uint8.")
+            raise TypeError("NumPy array must be of dtype uint8 (or 2D uint16).")
+
+        return decode_numpy(
+            source,
+            hints,
+            "RGB",
+            rotations,
+            timeout_ms,
+            binarizer,
+            padding,
+            collect_points,
+            roi,
+        )
+    else:
+        raise TypeError(
+            "Unsupported source type. Expected str, bytes, PIL.Image.Image, or numpy.ndarray."
+        )
 
-        if source.ndim == 2:  # Grayscale
-            pil_img = PIL.Image.fromarray(source, mode="L")
-        elif source.ndim == 3 and source.shape[2] in (3, 4):  # RGB or RGBA
-            pil_img = PIL.Image.fromarray(
-                source, mode="RGB" if source.shape[2] == 3 else "RGBA"
-            )
-            pil_img = pil_img.convert("L")
-        else:
-            raise TypeError("NumPy array must be 2D (grayscale) or 3D (RGB/RGBA).")
 
-        width, height = pil_img.size
-        luma_data = pil_img.tobytes()
-        return _decode_luma_pixels(luma_data, width, height, hints)
+def decode_pure(source, hints=None, timeout_ms=None, roi=None):
+    """
+    Decodes a clean, synthetic barcode image with the PURE_BARCODE hint set.
+
+    PURE_BARCODE skips symbol detection and samples the modules straight from
+    the image, which is faster and more reliable for renders, screenshots of
+    generated codes, and encode() output. It only works when the image holds
+    a single unrotated, unskewed barcode and nothing else besides its quiet
+    zone. Use decode() for camera captures and scans, where the hint makes
+    decoding fail with NotFoundException.
+
+    :param source: A BitMatrix (decoded with decode_from_bit_matrix()) or any
+                   source accepted by decode().
+    :param hints: Optional dictionary of further decoding hints, or a DecodeHints
+                  object; PURE_BARCODE is always set.
+    :param timeout_ms: Optional time budget in milliseconds, see decode().
+    :param roi: Optional (x, y, width, height) region holding the barcode, see
+                decode(). Not supported for BitMatrix sources.
+    :return: RXingResult object.
+    :raises TypeError: If hints is neither a dict nor a DecodeHints object, the
+                       source type is not supported, or roi is given with a
+                       BitMatrix.
+    """
+    if isinstance(hints, DecodeHints):
+        hints = hints._with_pure_barcode()
+    elif hints is None or isinstance(hints, dict):
+        hints = dict(hints or {}, PURE_BARCODE=True)
     else:
+        raise TypeError(
+            "decode_pure() takes a hints dict or a DecodeHints object, got "
+            f"{type(hints).__name__}."
+        )
+    if isinstance(source, BitMatrix):
+        if roi is not None:
+            raise TypeError("roi is not supported for BitMatrix sources.")
+        return decode_from_bit_matrix(source, hints, timeout_ms)
+    return decode(source, hints, timeout_ms=timeout_ms, roi=roi)
+
+
+def decode_multiple(
+    source,
+    hints=None,
+    sort="none",
+    row_tolerance=10.0,
+    apply_exif_orientation=True,
+    collect_points=True,
+    roi=None,
+):
+    """
+    Decodes every barcode found in an image.
+
+    :param source: Same source types as decode().
+    :param hints: Optional dictionary of decoding hints, or a DecodeHints object.
+    :param sort: "none" keeps the detector's order. "reading_order" sorts the
+                 results top-to-bottom, then left-to-right, by the centroid of
+                 each result's `result_points`. Results without points go last.
+    :param row_tolerance: Used with sort="reading_order". Codes whose centroids are
+                          at most this many pixels below the first code of a row
+                          are treated as the same row. Raise it for codes that are
+                          not perfectly aligned; 0 sorts purely by height.
+    :param apply_exif_orientation: Same as for decode().
+    :param collect_points: Same as for decode(). Sorting still uses the points.
+    :param roi: Same as for decode(): only codes inside the region are found,
+                and their `result_points` are in full-image coordinates.
+    :return: List of RXingResult objects, empty if no barcode is found.
+    :raises ValueError: For an unknown `sort` or a negative `row_tolerance`.
+    """
+    if hints is None:
+        hints = {}
+
+    if isinstance(source, (str, os.PathLike)):
+        return decode_multiple_from_file_path(
+            source,
+            hints,
+            sort,
+            row_tolerance,
+            apply_exif_orientation,
+            collect_points,
+            roi,
+        )
+    if isinstance(source, bytes):
+        return decode_multiple_image_bytes(
+            source,
+            hints,
+            sort,
+            row_tolerance,
+            apply_exif_orientation,
+            collect_points,
+            roi,
+        )
+    if isinstance(source, np.ndarray):
+        source = PIL.Image.fromarray(source)
+    if not isinstance(source, PIL.Image.Image):
         raise TypeError(
             "Unsupported source type. Expected str, bytes, PIL.Image.Image, or numpy.ndarray."
         )
+    img = source
+    if img.mode not in ("L", "RGB", "RGBA"):
+        # RGBA keeps transparency, which to_luma turns into white like the decoder does
+        img = img.convert("RGBA")
+    width, height = img.size
+    luma_data = to_luma(img.tobytes(), width, height, len(img.getbands()))
+    return decode_multiple_luma_pixels(
+        luma_data, width, height, hints, sort, row_tolerance, collect_points, roi=roi
+    )
+
+
+def decode_with_all_formats_tried(source, hints=None, verbose=False):
+    """
+    Diagnostic decode for barcodes that will not scan.
+
+    With verbose=False this is the same as decode(). With verbose=True every
+    readable format (or only those in the POSSIBLE_FORMATS hint) is tried on its
+    own and a list of FormatAttempt objects is returned, one per format, with
+    `format`, `success`, `error_kind` (e.g. "NotFoundException",
+    "ChecksumException"), `error_message` and `result` (RXingResult or None).
+    This decodes the image once per format, so keep it for triage.
+
+    :param source: Same source types as decode().
+    :param hints: Optional dictionary of decoding hints, or a DecodeHints object.
+    :param verbose: Return the per-format report instead of the first result.
+    :return: RXingResult, or a list of FormatAttempt when verbose is True.
+    """
+    if not verbose:
+        return decode(source, hints)
+    if hints is None:
+        hints = {}
+    luma_data, width, height = _to_luma(source)
+    return diagnose_luma_pixels(luma_data, width, height, hints)
+
+
+def decode_candidates(source, hints=None, binarizer="hybrid", roi=None):
+    """
+    Returns every distinct reading of the 1D barcodes in an image, best first.
+
+    Each scan line (and its reverse, for upside-down codes) is decoded with every
+    1D format on its own, or only those in POSSIBLE_FORMATS. Readings with the
+    same text are merged, and the list is ranked by how many scan lines agree,
+    which RXingResult.scan_lines reports; ties keep the reading closest
+    to the middle row first. rxing has no per-read confidence, so agreement
+    between scan lines is the only ranking signal. Use this for blurry captures
+    to present choices or to check the readings against a set of valid values.
+
+    :param source: Same source types as decode().
+    :param hints: Optional dictionary of decoding hints, or a DecodeHints object.
+    :param binarizer: "hybrid" (default), "global_histogram",
+                      "fixed_threshold[:N]", "otsu" or "adaptive[:W]", see
+                      decode().
+    :param roi: Optional (x, y, width, height) region to scan, see decode().
+                Only the rows of the region are tried.
+    :return: List of RXingResult, empty when nothing could be read.
+    """
+    if hints is None:
+        hints = {}
+    luma_data, width, height = _to_luma(source)
+    return decode_luma_candidates(luma_data, width, height, hints, binarizer, roi=roi)
+
+
+def binarize(source, binarizer="hybrid", hints=None):
+    """
+    Returns the black-and-white bitmap the readers search for a barcode.
+
+    Use it when a code does not scan, to tell a binarization problem (the
+    modules are missing or merged in the bitmap) from a detection problem (the
+    bitmap looks right but nothing is found). Set bits are black.
+
+    :param source: Same source types as decode().
+    :param binarizer: Same values as for decode(), default "hybrid".
+    :param hints: Optional dictionary of decoding hints, or a DecodeHints
+                  object. The binarizers do not use any hint; this is accepted
+                  so the arguments of a failing decode() call can be reused.
+    :return: BitMatrix the size of the image.
+    :raises NotFoundException: "global_histogram" found no dark and light peak
+                               in the image's histogram.
+    """
+    luma_data, width, height = _to_luma(source)
+    return binarize_luma_pixels(luma_data, width, height, binarizer, hints)
+
+
+def detect(source, formats=None, binarizer="hybrid"):
+    """
+    Finds 2D symbols without decoding them.
+
+    Only the detectors run, so this is cheaper than decode() and suits live
+    viewfinder overlays or counting the codes in a photo. The format is a guess
+    from the detector that fired, and a detection may fail to decode.
+
+    :param source: Same source types as decode().
+    :param formats: Optional list of format names to look for, from "QR_CODE",
+                    "DATA_MATRIX", "AZTEC" and "PDF_417" (default: all four).
+                    1D formats have no detector and raise ValueError.
+    :param binarizer: Same values as for decode(), default "hybrid".
+    :return: List of Detection, each with `format` and `corners`; empty when
+             nothing is found.
+    """
+    luma_data, width, height = _to_luma(source)
+    return detect_luma_pixels(luma_data, width, height, formats, binarizer)
+
+
+def _luminance_source_from_image(source):
+    """
+    Builds a LuminanceSource from any decode() source: a path, image file bytes,
+    a PIL image or a NumPy array. Color images use decode()'s grayscale weights.
+    """
+    luma_data, width, height = _to_luma(source)
+    return LuminanceSource(luma_data, width, height)
+
+
+LuminanceSource.from_image = staticmethod(_luminance_source_from_image)
+
+
+def _to_luma(source):
+    """Loads any decode() source as 8-bit grayscale: (bytes, width, height)."""
+    if isinstance(source, (str, os.PathLike)):
+        img = PIL.ImageOps.exif_transpose(PIL.Image.open(source))
+    elif isinstance(source, bytes):
+        img = PIL.ImageOps.exif_transpose(PIL.Image.open(io.BytesIO(source)))
+    elif isinstance(source, PIL.Image.Image):
+        img = source
+    elif isinstance(source, np.ndarray):
+        img = PIL.Image.fromarray(source)
+    else:
+        raise TypeError(
+            "Unsupported source type. Expected str, bytes, PIL.Image.Image, or numpy.ndarray."
+        )
+    width, height = img.size
+    # Match decode_pil_image() rather than PIL's own RGB -> L weights
+    if img.mode in ("RGB", "RGBA"):
+        return to_luma(img.tobytes(), width, height, len(img.mode)), width, height
+    return img.convert("L").tobytes(), width, height
+
+
+def decode_stream(
+    frames, width, height, pixel_format="L", hints=None, binarizer="hybrid", roi=None
+):
+    """
+    Decodes a sequence of video frames, yielding one result per frame.
+
+    A single VideoScanner is created for the whole stream, so the hints are
+    parsed and the readers are set up once instead of per frame. Each frame is
+    decoded with the GIL released.
+
+    :param frames: Iterable of frames, each `width * height` pixels in
+                   `pixel_format` ("L", "RGB" or "RGBA") as bytes, bytearray,
+                   memoryview or a uint8 numpy.ndarray.
+    :param hints: Optional dictionary of decoding hints, or a DecodeHints object.
+    :param binarizer: "hybrid", "global_histogram", "fixed_threshold[:N]",
+                      "otsu" or "adaptive[:W]", see decode().
+    :param roi: Optional (x, y, width, height) region searched in every frame,
+                see decode().
+    :return: Generator of RXingResult, or None for frames without a barcode.
+    """
+    if hints is None:
+        hints = {}
+    scanner = VideoScanner(width, height, pixel_format, hints, binarizer, roi)
+    for frame in frames:
+        if isinstance(frame, np.ndarray):
+            frame = np.ascontiguousarray(frame, dtype=np.uint8)
+        yield scanner.push_frame(frame)
+
+
+async def decode_async(
+    source,
+    hints=None,
+    max_dimension=None,
+    try_scales=None,
+    rotations=None,
+    executor=None,
+    roi=None,
+):
+    """
+    Awaitable version of decode() for asyncio code.
+
+    The decode runs in `executor` (the event loop's default thread pool when None)
+    and the native decoder releases the GIL while it works, so other tasks keep
+    running. Cancelling the awaiting task stops waiting for the result; a decode
+    that has already started still finishes in its worker thread and its result
+    is discarded.
+
+    :param roi: Optional (x, y, width, height) region to search, see decode().
+    :return: RXingResult object.
+    :raises ValueError: If no barcode is found, as with decode().
+    """
+    loop = asyncio.get_running_loop()
+    return await loop.run_in_executor(
+        executor,
+        functools.partial(
+            decode, source, hints, max_dimension, try_scales, rotations, roi=roi
+        ),
+    )
+
+
+async def decode_image_bytes_async(image_bytes, hints=None, executor=None):
+    """Awaitable decode of encoded image file bytes; see decode_async()."""
+    return await decode_async(image_bytes, hints, executor=executor)
+
+
+async def decode_multiple_async(source, hints=None, sort="none", executor=None):
+    """Awaitable version of decode_multiple(); see decode_async()."""
+    loop = asyncio.get_running_loop()
+    return await loop.run_in_executor(
+        executor, functools.partial(decode_multiple, source, hints, sort)
+    )
+
+
+async def decode_batch_async(items, hints=None, threads=None, executor=None):
+    """
+    Awaitable version of decode_batch(); see decode_async().
+
+    The batch still fans out over the rayon pool selected by `threads`;
+    `executor` only hosts the call that waits for it.
+    """
+    loop = asyncio.get_running_loop()
+    return await loop.run_in_executor(
+        executor, functools.partial(decode_batch, items, hints, threads)
+    )
+
+
+@contextlib.contextmanager
+def fixed_timestamp(timestamp: int = 0):
+    """
+    Context manager that makes every result decoded inside it report `timestamp`
+    (Unix epoch milliseconds) instead of the wall-clock decode time, e.g. for
+    snapshot tests of `to_dict()`. The previous override is restored on exit. The
+    override is process-wide, so decodes on other threads see it as well.
+    """
+    previous = timestamp_override()
+    set_timestamp_override(timestamp)
+    try:
+        yield
+    finally:
+        set_timestamp_override(previous)
 
 
 def encode(
-    data: str, format: str, width: int = 29, height: int = 29, hints_dict: dict = None
+    data: str,
+    format: str,
+    width: int = 29,
+    height: int = 29,
+    hints_dict: "dict | EncodeHints" = None,
+    *,
+    extended=None,
+    qr_mask=None,
 ):
     """
     Encodes data into a barcode/QR code.
@@ -89,40 +659,53 @@ def encode(
     :param height: Hint for the desired output height in pixels for a rendered image. Defaults to 5.
                    Similar to width, this is a hint and the BitMatrix module count will
                    be determined by the standard.
-    :param hints_dict: Optional dictionary of encoding hints. Defaults to None.
+    :param hints_dict: Optional dictionary of encoding hints, or an EncodeHints object.
+                       Defaults to None.
+    :param extended: CODE_39 and CODE_93 only. True encodes Code 39 in full ASCII
+                     mode, so lowercase letters and punctuation become two-character
+                     sequences; decode it with the CODE_39_EXTENDED_MODE hint. False
+                     allows only the 43 basic characters. None (default) lets the
+                     encoder switch to full ASCII when the data needs it.
+    :param qr_mask: QR_CODE only. Forces mask pattern 0-7 instead of the one the
+                    encoder scores best, so the same data always gives the same
+                    matrix. None (default) keeps the automatic choice.
     :return: BitMatrix object representing the encoded barcode. Its dimensions are in modules.
     :raises ValueError: If encoding fails (e.g., invalid format, data too large for format).
     """
     if hints_dict is None:
         hints_dict = {}
-    return _encode(data, format, width, height, hints_dict)
+    return _encode(
+        data, format, width, height, hints_dict, extended=extended, qr_mask=qr_mask
+    )
 
 
 # --- Methods to add to BitMatrix ---
+# to_bytes() gives 1 for dark modules; images want dark as 0.
+_MODULE_TO_LUMA = bytes([255, 0]) + bytes(254)
+
+
 def _bitmatrix_to_pil_image(self) -> PIL.Image.Image:
     """Converts the BitMatrix to a Pillow Image object (mode '1')."""
     if self.width == 0 or self.height == 0:
         return PIL.Image.new("1", (0, 0))
 
-    img_l = PIL.Image.new("L", (self.width, self.height))
-    pixels_l = img_l.load()
-    matrix_data = self.data
-    for y in range(self.height):
-        for x in range(self.width):
-            pixels_l[x, y] = 0 if matrix_data[y][x] else 255
-
+    luma = self.to_bytes().translate(_MODULE_TO_LUMA)
+    img_l = PIL.Image.frombytes("L", (self.width, self.height), luma)
     return img_l.convert("1")
 
 
-def _bitmatrix_to_numpy_array(self) -> np.ndarray:
-    """Converts the BitMatrix to a NumPy array (dtype=bool)."""
-    return np.array(self.data, dtype=bool)
+def _bitmatrix_to_numpy(self, dtype=bool) -> np.ndarray:
+    """Converts the BitMatrix to a 2-D (height, width) NumPy array.
 
+    Dark modules are True (or 1 for integer dtypes such as numpy.uint8).
+    """
+    modules = np.frombuffer(self.to_bytes(), dtype=np.uint8)
+    return modules.reshape(self.height, self.width).astype(dtype)
 
-def _bitmatrix_save(self, file_path: str, image_format: str = "PNG"):
-    """Saves the BitMatrix as an image file."""
-    pil_img = self.to_pil_image()
-    pil_img.save(file_path, format=image_format.upper())
+
+def _bitmatrix_to_numpy_array(self) -> np.ndarray:
+    """Converts the BitMatrix to a NumPy array (dtype=bool)."""
+    return self.to_numpy()
 
 
 def _bitmatrix_str(self) -> str:
@@ -141,17 +724,165 @@ def _bitmatrix_str(self) -> str:
 
 
 _RustBitMatrix.to_pil_image = _bitmatrix_to_pil_image
+_RustBitMatrix.to_numpy = _bitmatrix_to_numpy
 _RustBitMatrix.to_numpy_array = _bitmatrix_to_numpy_array
-_RustBitMatrix.save = _bitmatrix_save
 _RustBitMatrix.__str__ = _bitmatrix_str
 
 BitMatrix = _RustBitMatrix
 
+
+# --- Methods to add to RXingResult ---
+_RESULT_FIELDS = (
+    "text",
+    "raw_bytes",
+    "text_bytes",
+    "num_bits",
+    "result_points",
+    "barcode_format",
+    "result_metadata",
+    "timestamp",
+    "error_correction_level",
+    "symbology_identifier",
+    "eci",
+    "character_set",
+    "symbol_version",
+    "symbol_size",
+    "upc_ean_extension",
+    "issue_number",
+    "suggested_price",
+    "maxicode_mode",
+    "postal_code",
+    "country_code",
+    "service_class",
+    "structured_append_index",
+    "structured_append_total",
+    "structured_append_parity",
+    "was_inverted",
+    "orientation_degrees",
+    "rotation",
+    "scan_lines",
+    "code_39_extended",
+    "upc_e_text",
+)
+
+_BYTES_ENCODERS = {
+    "base64": lambda data: base64.b64encode(data).decode("ascii"),
+    "hex": bytes.hex,
+}
+
+
+def _result_to_dict(self, bytes_encoding: str = "base64") -> dict:
+    """
+    Returns the result as a JSON-serializable dict with one key per attribute.
+
+    `raw_bytes` and `text_bytes` become strings in `bytes_encoding` ("base64",
+    standard alphabet with padding, or lowercase "hex"), or stay None. The encoding
+    is recorded under "bytes_encoding", so consumers can decode them back with
+    `base64.b64decode()` or `bytes.fromhex()`. `result_points` become
+    `[{"x": ..., "y": ...}]`, `symbol_size` a `[width, height]` list and
+    `result_metadata` is sorted by key.
+    """
+    try:
+        encode_bytes = _BYTES_ENCODERS[bytes_encoding]
+    except KeyError:
+        raise ValueError(
+            f"Unknown bytes_encoding {bytes_encoding!r}; expected 'base64' or 'hex'."
+        ) from None
+
+    result = {field: getattr(self, field) for field in _RESULT_FIELDS}
+    for field in ("raw_bytes", "text_bytes"):
+        if result[field] is not None:
+            result[field] = encode_bytes(bytes(result[field]))
+    if result["result_points"] is not None:
+        result["result_points"] = [
+            {"x": point.x, "y": point.y} for point in result["result_points"]
+        ]
+    if result["symbol_size"] is not None:
+        result["symbol_size"] = list(result["symbol_size"])
+    if result["result_metadata"] is not None:
+        # Sorted so that equal results serialize to the same JSON.
+        result["result_metadata"] = dict(sorted(result["result_metadata"].items()))
+    result["bytes_encoding"] = bytes_encoding
+    return result
+
+
+def _result_to_json(self, bytes_encoding: str = "base64", **kwargs) -> str:
+    """Returns `to_dict(bytes_encoding)` as a JSON string; kwargs go to json.dumps."""
+    return json.dumps(self.to_dict(bytes_encoding), **kwargs)
+
+
+RXingResult.to_dict = _result_to_dict
+RXingResult.to_json = _result_to_json
+
 __all__ = [
     "decode",
+    "decode_async",
+    "decode_pure",
+    "decode_multiple",
+    "decode_multiple_image_bytes",
+    "decode_multiple_luma_pixels",
+    "decode_multiple_from_file_path",
+    "decode_all_frames",
+    "decode_region_grid",
+    "scan_and_annotate",
+    "decode_with_all_formats_tried",
+    "decode_candidates",
+    "binarize",
+    "LuminanceSource",
+    "detect",
+    "Detection",
+    "BinaryBitmap",
+    "ParsedResult",
+    "decode_stream",
+    "decode_image_bytes_async",
+    "decode_multiple_async",
+    "decode_batch_async",
+    "decode_luma16_pixels",
+    "decode_pil_image",
+    "decode_numpy",
+    "decode_rgb_pixels",
+    "decode_yuv",
+    "decode_from_bit_matrix",
+    "to_luma",
+    "decode_timed",
+    "DecodeTiming",
+    "decode_batch",
+    "DecodeFailure",
+    "scan_directory",
+    "DirectoryScanner",
+    "ReaderException",
+    "NotFoundException",
+    "ChecksumException",
+    "FormatException",
+    "WriterException",
     "encode",  # Expose the new Python wrapper for encode
+    "encode_many",
+    "EncodeFailure",
+    "encode_gs1",
+    "parse_gs1",
+    "validate_encodable",
+    "capacity",
+    "upce_to_upca",
+    "encode_to_png",
+    "encode_to_svg",
+    "encode_sheet",
+    "encode_to_file",
     "RXingResult",
+    "FormatAttempt",
+    "VideoScanner",
+    "BarcodeReader",
+    "BarcodeWriter",
+    "AppendCollector",
     "Point",
+    "QRPoints",
     "BitMatrix",
     "BarcodeFormat",
+    "MetadataKey",
+    "DecodeHints",
+    "EncodeHints",
+    "set_timestamp_override",
+    "timestamp_override",
+    "fixed_timestamp",
+    "supported_read_formats",
+    "supported_write_formats",
 ]
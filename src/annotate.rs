@@ -0,0 +1,226 @@
+use image::{Rgba, RgbaImage};
+use imageproc::drawing::draw_line_segment_mut;
+use rxing::{BarcodeFormat, Point};
+
+// 可打印 ASCII (0x20-0x7E) 的 5x8 点阵字体, 每字节一列, 最低位在上. 其它字符画成 '?'
+const FONT: [[u8; 5]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00],
+    [0x00, 0x00, 0x5F, 0x00, 0x00],
+    [0x00, 0x07, 0x00, 0x07, 0x00],
+    [0x14, 0x7F, 0x14, 0x7F, 0x14],
+    [0x24, 0x2A, 0x7F, 0x2A, 0x12],
+    [0x23, 0x13, 0x08, 0x64, 0x62],
+    [0x36, 0x49, 0x56, 0x20, 0x50],
+    [0x00, 0x08, 0x07, 0x03, 0x00],
+    [0x00, 0x1C, 0x22, 0x41, 0x00],
+    [0x00, 0x41, 0x22, 0x1C, 0x00],
+    [0x2A, 0x1C, 0x7F, 0x1C, 0x2A],
+    [0x08, 0x08, 0x3E, 0x08, 0x08],
+    [0x00, 0x80, 0x70, 0x30, 0x00],
+    [0x08, 0x08, 0x08, 0x08, 0x08],
+    [0x00, 0x00, 0x60, 0x60, 0x00],
+    [0x20, 0x10, 0x08, 0x04, 0x02],
+    [0x3E, 0x51, 0x49, 0x45, 0x3E],
+    [0x00, 0x42, 0x7F, 0x40, 0x00],
+    [0x72, 0x49, 0x49, 0x49, 0x46],
+    [0x21, 0x41, 0x49, 0x4D, 0x33],
+    [0x18, 0x14, 0x12, 0x7F, 0x10],
+    [0x27, 0x45, 0x45, 0x45, 0x39],
+    [0x3C, 0x4A, 0x49, 0x49, 0x31],
+    [0x41, 0x21, 0x11, 0x09, 0x07],
+    [0x36, 0x49, 0x49, 0x49, 0x36],
+    [0x46, 0x49, 0x49, 0x29, 0x1E],
+    [0x00, 0x00, 0x14, 0x00, 0x00],
+    [0x00, 0x40, 0x34, 0x00, 0x00],
+    [0x00, 0x08, 0x14, 0x22, 0x41],
+    [0x14, 0x14, 0x14, 0x14, 0x14],
+    [0x00, 0x41, 0x22, 0x14, 0x08],
+    [0x02, 0x01, 0x59, 0x09, 0x06],
+    [0x3E, 0x41, 0x5D, 0x59, 0x4E],
+    [0x7C, 0x12, 0x11, 0x12, 0x7C],
+    [0x7F, 0x49, 0x49, 0x49, 0x36],
+    [0x3E, 0x41, 0x41, 0x41, 0x22],
+    [0x7F, 0x41, 0x41, 0x41, 0x3E],
+    [0x7F, 0x49, 0x49, 0x49, 0x41],
+    [0x7F, 0x09, 0x09, 0x09, 0x01],
+    [0x3E, 0x41, 0x41, 0x51, 0x73],
+    [0x7F, 0x08, 0x08, 0x08, 0x7F],
+    [0x00, 0x41, 0x7F, 0x41, 0x00],
+    [0x20, 0x40, 0x41, 0x3F, 0x01],
+    [0x7F, 0x08, 0x14, 0x22, 0x41],
+    [0x7F, 0x40, 0x40, 0x40, 0x40],
+    [0x7F, 0x02, 0x1C, 0x02, 0x7F],
+    [0x7F, 0x04, 0x08, 0x10, 0x7F],
+    [0x3E, 0x41, 0x41, 0x41, 0x3E],
+    [0x7F, 0x09, 0x09, 0x09, 0x06],
+    [0x3E, 0x41, 0x51, 0x21, 0x5E],
+    [0x7F, 0x09, 0x19, 0x29, 0x46],
+    [0x26, 0x49, 0x49, 0x49, 0x32],
+    [0x03, 0x01, 0x7F, 0x01, 0x03],
+    [0x3F, 0x40, 0x40, 0x40, 0x3F],
+    [0x1F, 0x20, 0x40, 0x20, 0x1F],
+    [0x3F, 0x40, 0x38, 0x40, 0x3F],
+    [0x63, 0x14, 0x08, 0x14, 0x63],
+    [0x03, 0x04, 0x78, 0x04, 0x03],
+    [0x61, 0x59, 0x49, 0x4D, 0x43],
+    [0x00, 0x7F, 0x41, 0x41, 0x41],
+    [0x02, 0x04, 0x08, 0x10, 0x20],
+    [0x00, 0x41, 0x41, 0x41, 0x7F],
+    [0x04, 0x02, 0x01, 0x02, 0x04],
+    [0x40, 0x40, 0x40, 0x40, 0x40],
+    [0x00, 0x03, 0x07, 0x08, 0x00],
+    [0x20, 0x54, 0x54, 0x78, 0x40],
+    [0x7F, 0x28, 0x44, 0x44, 0x38],
+    [0x38, 0x44, 0x44, 0x44, 0x28],
+    [0x38, 0x44, 0x44, 0x28, 0x7F],
+    [0x38, 0x54, 0x54, 0x54, 0x18],
+    [0x00, 0x08, 0x7E, 0x09, 0x02],
+    [0x18, 0xA4, 0xA4, 0x9C, 0x78],
+    [0x7F, 0x08, 0x04, 0x04, 0x78],
+    [0x00, 0x44, 0x7D, 0x40, 0x00],
+    [0x20, 0x40, 0x40, 0x3D, 0x00],
+    [0x7F, 0x10, 0x28, 0x44, 0x00],
+    [0x00, 0x41, 0x7F, 0x40, 0x00],
+    [0x7C, 0x04, 0x78, 0x04, 0x78],
+    [0x7C, 0x08, 0x04, 0x04, 0x78],
+    [0x38, 0x44, 0x44, 0x44, 0x38],
+    [0xFC, 0x18, 0x24, 0x24, 0x18],
+    [0x18, 0x24, 0x24, 0x18, 0xFC],
+    [0x7C, 0x08, 0x04, 0x04, 0x08],
+    [0x48, 0x54, 0x54, 0x54, 0x24],
+    [0x04, 0x04, 0x3F, 0x44, 0x24],
+    [0x3C, 0x40, 0x40, 0x20, 0x7C],
+    [0x1C, 0x20, 0x40, 0x20, 0x1C],
+    [0x3C, 0x40, 0x30, 0x40, 0x3C],
+    [0x44, 0x28, 0x10, 0x28, 0x44],
+    [0x4C, 0x90, 0x90, 0x90, 0x7C],
+    [0x44, 0x64, 0x54, 0x4C, 0x44],
+    [0x00, 0x08, 0x36, 0x41, 0x00],
+    [0x00, 0x00, 0x77, 0x00, 0x00],
+    [0x00, 0x41, 0x36, 0x08, 0x00],
+    [0x02, 0x01, 0x02, 0x04, 0x02],
+];
+
+// 按格式在 SUPPORTED_READ_FORMATS 中的位置取色, 同一格式始终同色
+const PALETTE: [[u8; 3]; 10] = [
+    [31, 119, 180],
+    [255, 127, 14],
+    [44, 160, 44],
+    [214, 39, 40],
+    [148, 103, 189],
+    [140, 86, 75],
+    [227, 119, 194],
+    [0, 0, 128],
+    [188, 189, 34],
+    [23, 190, 207],
+];
+
+pub(crate) fn format_color(format: &BarcodeFormat) -> Rgba<u8> {
+    let index = crate::SUPPORTED_READ_FORMATS
+        .iter()
+        .position(|f| f == format)
+        .unwrap_or(0);
+    let [r, g, b] = PALETTE[index % PALETTE.len()];
+    Rgba([r, g, b, u8::MAX])
+}
+
+// 在 image 上标出一个结果: 沿 points 画出多边形 (一维码为扫描线), 并在其上方写出 text.
+// 线宽与字号随图像尺寸增大
+pub(crate) fn annotate(
+    image: &mut RgbaImage,
+    format: &BarcodeFormat,
+    text: &str,
+    points: &[Point],
+) {
+    if points.is_empty() {
+        return;
+    }
+    let color = format_color(format);
+    let scale = (image.width().min(image.height()) / 400).max(1);
+    let outline = order_around_centroid(points);
+    let closed = outline.len() > 2;
+    for (i, start) in outline.iter().enumerate() {
+        let end = match outline.get(i + 1) {
+            Some(end) => end,
+            None if closed => &outline[0],
+            None => break,
+        };
+        draw_thick_line(image, *start, *end, scale + 1, color);
+    }
+    if outline.len() == 1 {
+        draw_thick_line(image, outline[0], outline[0], scale * 3, color);
+    }
+
+    let left = points.iter().map(|p| p.x).fold(f32::INFINITY, f32::min);
+    let top = points.iter().map(|p| p.y).fold(f32::INFINITY, f32::min);
+    let label_height = 10 * scale;
+    let x = left.max(0.0) as u32;
+    let y = (top as i64 - i64::from(label_height) - i64::from(scale * 2)).max(0) as u32;
+    draw_label(image, x, y, text, scale, color);
+}
+
+// 3 个以上的点按绕中心的角度排序, 得到不自交的多边形
+fn order_around_centroid(points: &[Point]) -> Vec<Point> {
+    let mut ordered = points.to_vec();
+    if ordered.len() > 2 {
+        let count = ordered.len() as f32;
+        let cx = ordered.iter().map(|p| p.x).sum::<f32>() / count;
+        let cy = ordered.iter().map(|p| p.y).sum::<f32>() / count;
+        ordered.sort_by(|a, b| {
+            let angle = |p: &Point| (p.y - cy).atan2(p.x - cx);
+            angle(a).total_cmp(&angle(b))
+        });
+    }
+    ordered
+}
+
+fn draw_thick_line(image: &mut RgbaImage, start: Point, end: Point, width: u32, color: Rgba<u8>) {
+    let radius = (width / 2) as i32;
+    for dy in -radius..=radius {
+        for dx in -radius..=radius {
+            let (dx, dy) = (dx as f32, dy as f32);
+            draw_line_segment_mut(
+                image,
+                (start.x + dx, start.y + dy),
+                (end.x + dx, end.y + dy),
+                color,
+            );
+        }
+    }
+}
+
+// 白字写在格式颜色的底色上, 超出图像的部分裁掉
+fn draw_label(image: &mut RgbaImage, x: u32, y: u32, text: &str, scale: u32, color: Rgba<u8>) {
+    let padding = scale;
+    let width = (text.chars().count() as u32 * 6 * scale + padding * 2).min(image.width());
+    let height = 8 * scale + padding * 2;
+    let x = x.min(image.width().saturating_sub(width));
+    fill_rect(image, (x, y), (width, height), color);
+    let white = Rgba([u8::MAX; 4]);
+    for (i, c) in (0u32..).zip(text.chars()) {
+        let glyph = match c {
+            ' '..='~' => &FONT[c as usize - 0x20],
+            _ => &FONT[usize::from(b'?' - 0x20)],
+        };
+        let glyph_left = x + padding + i * 6 * scale;
+        if glyph_left >= image.width() {
+            break;
+        }
+        for (column, bits) in (0u32..).zip(glyph) {
+            for row in 0..8 {
+                if bits & (1 << row) != 0 {
+                    let top_left = (glyph_left + column * scale, y + padding + row * scale);
+                    fill_rect(image, top_left, (scale, scale), white);
+                }
+            }
+        }
+    }
+}
+
+fn fill_rect(image: &mut RgbaImage, (left, top): (u32, u32), (w, h): (u32, u32), color: Rgba<u8>) {
+    for py in top..top.saturating_add(h).min(image.height()) {
+        for px in left..left.saturating_add(w).min(image.width()) {
+            image.put_pixel(px, py, color);
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use rxing::common::{BitArray, BitMatrix, LineOrientation, Result};
+use rxing::{Binarizer, Luma8LuminanceSource, LuminanceSource};
+use std::borrow::Cow;
+
+// 已二值化的位图直接作为黑白矩阵使用, 不再经过阈值计算.
+// 一维读码器会读取亮度行, TRY_HARDER 时还会旋转亮度源后重建二值化器,
+// 因此同时保留一份 0/255 的亮度图; 旋转后的亮度仍只有这两个值, 以 128 为阈值即可无损还原
+pub(crate) struct BitMatrixBinarizer {
+    source: Luma8LuminanceSource,
+    matrix: BitMatrix,
+}
+
+impl BitMatrixBinarizer {
+    pub fn new(matrix: BitMatrix) -> Self {
+        let (width, height) = (matrix.getWidth(), matrix.getHeight());
+        let pixels = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| if matrix.get(x, y) { 0 } else { u8::MAX })
+            .collect();
+        BitMatrixBinarizer {
+            source: Luma8LuminanceSource::new(pixels, width, height),
+            matrix,
+        }
+    }
+
+    // 由本模块计算阈值的二值化
+    pub fn with_threshold(
+        luma: &[u8],
+        width: usize,
+        height: usize,
+        method: ThresholdMethod,
+    ) -> Self {
+        let black: Vec<bool> = match method {
+            ThresholdMethod::Fixed(cutoff) => luma.iter().map(|&v| v < cutoff).collect(),
+            ThresholdMethod::Otsu => match otsu_threshold(luma) {
+                Some(cutoff) => luma.iter().map(|&v| v <= cutoff).collect(),
+                None => vec![false; luma.len()],
+            },
+            ThresholdMethod::Adaptive(window) => adaptive_threshold(luma, width, height, window),
+        };
+        let thresholded = black
+            .into_iter()
+            .map(|black| if black { 0 } else { u8::MAX })
+            .collect();
+        let source = Luma8LuminanceSource::new(thresholded, width as u32, height as u32);
+        BitMatrixBinarizer::from_source(source)
+    }
+
+    fn from_source(source: Luma8LuminanceSource) -> Self {
+        let (width, height) = (source.get_width(), source.get_height());
+        let mut matrix = BitMatrix::new(width as u32, height as u32)
+            .expect("luminance source dimensions are valid");
+        for (i, luminance) in source.get_matrix().iter().enumerate() {
+            if *luminance < 128 {
+                matrix.set((i % width) as u32, (i / width) as u32);
+            }
+        }
+        BitMatrixBinarizer { source, matrix }
+    }
+}
+
+// Fixed: 亮度低于给定值的像素为黑.
+// Otsu: 由整幅图像的直方图取类间方差最大的阈值, 适合整体偏亮或偏暗、对比度低但光照均匀的图像.
+// Adaptive: 每个像素与以它为中心的窗口 (边长为给定像素数, 省略时按图像大小取) 内的平均亮度比较,
+// 比平均值暗 ADAPTIVE_OFFSET 以上为黑, 适合光照不均且对比度低的图像.
+// 窗口须大于条码中最大的纯色区域, 否则大块深色区域的中间会变白
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum ThresholdMethod {
+    Fixed(u8),
+    Otsu,
+    Adaptive(Option<u32>),
+}
+
+const ADAPTIVE_OFFSET: u64 = 2;
+const MIN_ADAPTIVE_WINDOW: usize = 15;
+
+// 返回 t, 亮度不超过 t 的像素为黑; 只有一种亮度时返回 None (全部为白)
+fn otsu_threshold(luma: &[u8]) -> Option<u8> {
+    let mut histogram = [0u64; 256];
+    for &v in luma {
+        histogram[v as usize] += 1;
+    }
+    let total = luma.len() as f64;
+    let sum_all: f64 = histogram
+        .iter()
+        .enumerate()
+        .map(|(v, &count)| v as f64 * count as f64)
+        .sum();
+    let (mut weight_dark, mut sum_dark) = (0.0, 0.0);
+    let mut best: Option<(u8, f64)> = None;
+    for (t, &count) in histogram.iter().enumerate() {
+        weight_dark += count as f64;
+        sum_dark += t as f64 * count as f64;
+        let weight_light = total - weight_dark;
+        if weight_dark == 0.0 {
+            continue;
+        }
+        if weight_light == 0.0 {
+            break;
+        }
+        let mean_dark = sum_dark / weight_dark;
+        let mean_light = (sum_all - sum_dark) / weight_light;
+        let variance = weight_dark * weight_light * (mean_dark - mean_light).powi(2);
+        if best.is_none_or(|(_, best_variance)| variance > best_variance) {
+            best = Some((t as u8, variance));
+        }
+    }
+    best.map(|(t, _)| t)
+}
+
+// 用积分图求窗口内的亮度和, 图像边缘处窗口只取图像内的部分
+fn adaptive_threshold(luma: &[u8], width: usize, height: usize, window: Option<u32>) -> Vec<bool> {
+    let window = window
+        .map(|window| window as usize)
+        .unwrap_or_else(|| (width.max(height) / 8).max(MIN_ADAPTIVE_WINDOW));
+    let radius = window / 2;
+    let stride = width + 1;
+    let mut integral = vec![0u64; stride * (height + 1)];
+    for y in 0..height {
+        let mut row_sum = 0;
+        for x in 0..width {
+            row_sum += u64::from(luma[y * width + x]);
+            integral[(y + 1) * stride + x + 1] = integral[y * stride + x + 1] + row_sum;
+        }
+    }
+    let mut black = Vec::with_capacity(luma.len());
+    for y in 0..height {
+        let (top, bottom) = (y.saturating_sub(radius), (y + radius + 1).min(height));
+        for x in 0..width {
+            let (left, right) = (x.saturating_sub(radius), (x + radius + 1).min(width));
+            let sum = integral[bottom * stride + right] + integral[top * stride + left]
+                - integral[top * stride + right]
+                - integral[bottom * stride + left];
+            let count = ((right - left) * (bottom - top)) as u64;
+            black.push((u64::from(luma[y * width + x]) + ADAPTIVE_OFFSET) * count < sum);
+        }
+    }
+    black
+}
+
+impl Binarizer for BitMatrixBinarizer {
+    type Source = Luma8LuminanceSource;
+
+    fn get_luminance_source(&self) -> &Self::Source {
+        &self.source
+    }
+
+    fn get_black_row(&self, y: usize) -> Result<Cow<'_, BitArray>> {
+        Ok(Cow::Owned(self.matrix.getRow(y as u32)))
+    }
+
+    fn get_black_row_from_matrix(&self, y: usize) -> Result<Cow<'_, BitArray>> {
+        self.get_black_row(y)
+    }
+
+    fn get_black_matrix(&self) -> Result<&BitMatrix> {
+        Ok(&self.matrix)
+    }
+
+    fn get_black_line(&self, l: usize, lt: LineOrientation) -> Result<Cow<'_, BitArray>> {
+        match lt {
+            LineOrientation::Row => self.get_black_row(l),
+            LineOrientation::Column => Ok(Cow::Owned(self.matrix.getCol(l as u32))),
+        }
+    }
+
+    fn create_binarizer(&self, source: Self::Source) -> Self {
+        BitMatrixBinarizer::from_source(source)
+    }
+
+    fn get_width(&self) -> usize {
+        self.matrix.getWidth() as usize
+    }
+
+    fn get_height(&self) -> usize {
+        self.matrix.getHeight() as usize
+    }
+}
@@ -0,0 +1,89 @@
+use rxing::oned::Code39Reader;
+use rxing::BarcodeFormat;
+use std::borrow::Cow;
+
+// Code 39 的全 ASCII (extended) 模式用 $ % / + 加一个字母表示基本字符集以外的 ASCII 字符,
+// 映射与 rxing 的 Code39Writer 相同. 基本字符集中的 $ % / + 在此模式下也要转义
+fn full_ascii(c: u8) -> Option<[u8; 2]> {
+    match c {
+        0 => Some(*b"%U"),
+        1..=26 => Some([b'$', b'A' + c - 1]),
+        27..=31 => Some([b'%', b'A' + c - 27]),
+        b' ' | b'-' | b'.' | b'0'..=b'9' | b'A'..=b'Z' => None,
+        b'!'..=b',' | b'/' => Some([b'/', b'A' + c - b'!']),
+        b':' => Some(*b"/Z"),
+        b';'..=b'?' => Some([b'%', b'F' + c - b';']),
+        b'@' => Some(*b"%V"),
+        b'['..=b'_' => Some([b'%', b'K' + c - b'[']),
+        b'`' => Some(*b"%W"),
+        b'a'..=b'z' => Some([b'+', c - 32]),
+        _ => Some([b'%', b'P' + c - b'{']),
+    }
+}
+
+// extended 为 None 时交给编码器 (Code 39 只在出现基本字符集以外的字符时才切换到全 ASCII);
+// False 时只接受基本字符集; True 时 Code 39 总按全 ASCII 编码, 读码器开启全 ASCII 后才能还原.
+// Code 93 的移位字符与数据字符分开, 编码器始终支持全 ASCII
+pub(crate) fn contents<'a>(
+    data: &'a str,
+    format: &BarcodeFormat,
+    extended: Option<bool>,
+) -> Result<Cow<'a, str>, String> {
+    let Some(extended) = extended else {
+        return Ok(Cow::Borrowed(data));
+    };
+    if !matches!(format, BarcodeFormat::CODE_39 | BarcodeFormat::CODE_93) {
+        return Err(format!(
+            "extended only applies to CODE_39 and CODE_93, got {:?}",
+            format
+        ));
+    }
+    let (allowed, is_allowed): (&str, fn(char) -> bool) = if extended {
+        ("only ASCII characters", |c| c.is_ascii())
+    } else {
+        (
+            "only 0-9, A-Z, space and - . $ / + % without extended",
+            |c| Code39Reader::ALPHABET_STRING.contains(c),
+        )
+    };
+    if let Some((index, c)) = data.chars().enumerate().find(|&(_, c)| !is_allowed(c)) {
+        return Err(format!(
+            "{:?} cannot encode {:?} at index {}; it supports {}",
+            format, c, index, allowed
+        ));
+    }
+    if !extended || *format == BarcodeFormat::CODE_93 {
+        return Ok(Cow::Borrowed(data));
+    }
+    let mut encoded = Vec::with_capacity(data.len() * 2);
+    for c in data.bytes() {
+        match full_ascii(c) {
+            Some(pair) => encoded.extend_from_slice(&pair),
+            None => encoded.push(c),
+        }
+    }
+    Ok(Cow::Owned(
+        String::from_utf8(encoded).expect("full ASCII encoding is ASCII"),
+    ))
+}
+
+// 按全 ASCII 模式解释读出的基本字符; 移位字符后不是合法的字母时返回 None
+pub(crate) fn decode_full_ascii(text: &str) -> Option<String> {
+    let mut decoded = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if !matches!(c, '$' | '%' | '/' | '+') {
+            decoded.push(c);
+            continue;
+        }
+        let next = chars.next().filter(char::is_ascii_uppercase)? as u8;
+        // %X, %Y, %Z 与 %T 同为 DEL
+        let pair = match [c as u8, next] {
+            [b'%', b'X'..=b'Z'] => *b"%T",
+            pair => pair,
+        };
+        let ascii = (0..=127u8).find(|&ascii| full_ascii(ascii) == Some(pair))?;
+        decoded.push(ascii as char);
+    }
+    Some(decoded)
+}
@@ -0,0 +1,155 @@
+use rxing::common::{BitMatrix, DetectorRXingResult};
+use rxing::{BarcodeFormat, DecodeHints, Point};
+
+// 有独立定位器的格式. 一维码没有定位阶段, 只能通过逐行解码找到
+pub(crate) const DETECTABLE_FORMATS: [BarcodeFormat; 4] = [
+    BarcodeFormat::QR_CODE,
+    BarcodeFormat::DATA_MATRIX,
+    BarcodeFormat::AZTEC,
+    BarcodeFormat::PDF_417,
+];
+
+// 只运行各格式的定位器, 不采样解码. 返回每个候选符号的格式与四个角点 (顺序未整理).
+// QR 码, Data Matrix 与 PDF417 可以找到多个; Aztec 的定位器每幅图像最多返回一个
+pub(crate) fn detect_symbols(
+    matrix: &BitMatrix,
+    formats: &[BarcodeFormat],
+) -> Vec<(BarcodeFormat, [(f32, f32); 4])> {
+    let mut found = Vec::new();
+    for &format in formats {
+        match format {
+            BarcodeFormat::QR_CODE => {
+                let detected = rxing::multi::qrcode::detector::MultiDetector::new(matrix)
+                    .detectMulti(&DecodeHints::default())
+                    .unwrap_or_default();
+                for result in detected.iter().filter(|r| has_timing_patterns(r.getBits())) {
+                    if let Some(corners) = qr_corners(result.getPoints(), result.getBits()) {
+                        found.push((format, corners));
+                    }
+                }
+            }
+            // 与 rxing 的 Data Matrix 读码器相同, 先用移植自 zxing-cpp 的定位器 (可找到多个),
+            // 没有结果时再用 ZXing 的定位器
+            BarcodeFormat::DATA_MATRIX => {
+                let detected =
+                    rxing::datamatrix::detector::zxing_cpp_detector::detect(matrix, false, true)
+                        .ok()
+                        .filter(|detected| !detected.is_empty())
+                        .or_else(|| {
+                            rxing::datamatrix::detector::Detector::new(matrix)
+                                .and_then(|detector| detector.detect())
+                                .ok()
+                                .map(|result| vec![result])
+                        })
+                        .unwrap_or_default();
+                for result in detected {
+                    if let Some(corners) = four_points(result.getPoints()) {
+                        found.push((format, corners));
+                    }
+                }
+            }
+            BarcodeFormat::AZTEC => {
+                if let Ok(result) = rxing::aztec::detector::Detector::new(matrix).detect(false) {
+                    if let Some(corners) = four_points(result.getPoints()) {
+                        found.push((format, corners));
+                    }
+                }
+            }
+            BarcodeFormat::PDF_417 => {
+                for corners in pdf417_corners(matrix) {
+                    found.push((format, corners));
+                }
+            }
+            _ => {}
+        }
+    }
+    found
+}
+
+// 多个 QR 码同在一幅图像中时, 定位器会把不同符号的位置探测图形组合成不存在的候选.
+// 真正的符号在第 6 行与第 6 列的两个位置探测图形之间有黑白交替的定位图形, 允许少量模块出错
+fn has_timing_patterns(bits: &BitMatrix) -> bool {
+    let dimension = bits.getWidth();
+    if dimension < 21 || bits.getHeight() != dimension {
+        return false;
+    }
+    let cells = 8..dimension - 8;
+    let wrong = cells
+        .clone()
+        .filter(|&i| bits.get(i, 6) != (i % 2 == 0))
+        .count()
+        + cells.filter(|&i| bits.get(6, i) != (i % 2 == 0)).count();
+    wrong * 5 <= 2 * (dimension as usize - 16)
+}
+
+fn four_points(points: &[Point]) -> Option<[(f32, f32); 4]> {
+    match points {
+        [a, b, c, d, ..] => Some([(a.x, a.y), (b.x, b.y), (c.x, c.y), (d.x, d.y)]),
+        _ => None,
+    }
+}
+
+// QR 码的定位器返回左下, 左上, 右上三个位置探测图形的中心. 中心离符号边缘 3.5 个模块,
+// 按模块数 (采样网格的边长) 向外推出四个角 (仿射近似, 不考虑透视)
+fn qr_corners(points: &[Point], bits: &BitMatrix) -> Option<[(f32, f32); 4]> {
+    let [bottom_left, top_left, top_right, ..] = points else {
+        return None;
+    };
+    let span = bits.getWidth() as f32 - 7.0;
+    if span <= 0.0 {
+        return None;
+    }
+    let u = (
+        (top_right.x - top_left.x) / span,
+        (top_right.y - top_left.y) / span,
+    );
+    let v = (
+        (bottom_left.x - top_left.x) / span,
+        (bottom_left.y - top_left.y) / span,
+    );
+    let at = |p: &Point, du: f32, dv: f32| (p.x + du * u.0 + dv * v.0, p.y + du * u.1 + dv * v.1);
+    let bottom_right = Point {
+        x: top_right.x + bottom_left.x - top_left.x,
+        y: top_right.y + bottom_left.y - top_left.y,
+    };
+    Some([
+        at(top_left, -3.5, -3.5),
+        at(top_right, 3.5, -3.5),
+        at(&bottom_right, 3.5, 3.5),
+        at(bottom_left, -3.5, 3.5),
+    ])
+}
+
+// 与 rxing 的 PDF417 读码器相同, 依次在旋转 0 / 180 / 270 / 90 度 (逆时针) 的矩阵上查找,
+// 第一个找到符号的角度为准; 角点换算回原矩阵的坐标
+fn pdf417_corners(matrix: &BitMatrix) -> Vec<[(f32, f32); 4]> {
+    let (width, height) = (matrix.getWidth() as f32, matrix.getHeight() as f32);
+    for rotation in [0, 180, 270, 90] {
+        let mut rotated = matrix.clone();
+        if rotated.rotate(rotation).is_err() {
+            continue;
+        }
+        let unrotate = |p: Point| match rotation {
+            180 => (width - 1.0 - p.x, height - 1.0 - p.y),
+            270 => (p.y, height - 1.0 - p.x),
+            90 => (width - 1.0 - p.y, p.x),
+            _ => (p.x, p.y),
+        };
+        let symbols: Vec<[(f32, f32); 4]> =
+            rxing::pdf417::detector::pdf_417_detector::detect(true, &rotated)
+                .unwrap_or_default()
+                .into_iter()
+                // 顶点依次为左上, 左下, 右上, 右下
+                .filter_map(|vertices| match vertices {
+                    [Some(tl), Some(bl), Some(tr), Some(br), ..] => {
+                        Some([unrotate(tl), unrotate(tr), unrotate(br), unrotate(bl)])
+                    }
+                    _ => None,
+                })
+                .collect();
+        if !symbols.is_empty() {
+            return symbols;
+        }
+    }
+    Vec::new()
+}
@@ -0,0 +1,217 @@
+use rxing::{BarcodeFormat, EncodeHints};
+use std::borrow::Cow;
+
+// 编码前检查数据是否符合格式的字符集, 长度与校验位要求, 出错时指出具体的字符或长度.
+// 长度上限与二维码容量由调用方再交给编码器本身校验
+pub(crate) fn check(data: &str, format: &BarcodeFormat) -> Result<(), String> {
+    match format {
+        BarcodeFormat::EAN_13 => check_upc_ean(data, format, 12),
+        BarcodeFormat::EAN_8 => check_upc_ean(data, format, 7),
+        BarcodeFormat::UPC_A => check_upc_ean(data, format, 11),
+        BarcodeFormat::UPC_E => {
+            check_upc_ean(data, format, 7)?;
+            match data.as_bytes()[0] {
+                b'0' | b'1' => Ok(()),
+                other => Err(format!(
+                    "UPC_E number system (first digit) must be 0 or 1, got '{}'",
+                    other as char
+                )),
+            }
+        }
+        BarcodeFormat::ITF => {
+            check_digits(data, format)?;
+            if !data.len().is_multiple_of(2) {
+                return Err(format!(
+                    "ITF encodes digit pairs and needs an even number of digits, got {}",
+                    data.len()
+                ));
+            }
+            Ok(())
+        }
+        BarcodeFormat::CODE_39 | BarcodeFormat::CODE_93 | BarcodeFormat::TELEPEN => {
+            check_chars(data, format, "only ASCII characters", |c| c.is_ascii())
+        }
+        // U+00F1-U+00F4 是 FNC1-FNC4 的转义字符
+        BarcodeFormat::CODE_128 => check_chars(
+            data,
+            format,
+            "only ASCII characters and the FNC escapes U+00F1-U+00F4",
+            |c| c.is_ascii() || ('\u{00f1}'..='\u{00f4}').contains(&c),
+        ),
+        BarcodeFormat::CODABAR => check_codabar(data),
+        _ => Ok(()),
+    }
+}
+
+const ESCAPE_FNC_1: char = '\u{00f1}';
+
+// Code 128 的 FORCE_CODE_SET 与 GS1_FORMAT: 先确认数据都能用强制的码集表示,
+// 再为 GS1 数据补上起始的 FNC1 (已有则不重复)
+pub(crate) fn code_128_contents<'a>(
+    data: &'a str,
+    hints: &EncodeHints,
+) -> Result<Cow<'a, str>, String> {
+    if let Some(code_set) = &hints.ForceCodeSet {
+        check_code_set(data, code_set)?;
+    }
+    if hints.Gs1Format == Some(true) && !data.starts_with(ESCAPE_FNC_1) {
+        return Ok(Cow::Owned(format!("{}{}", ESCAPE_FNC_1, data)));
+    }
+    Ok(Cow::Borrowed(data))
+}
+
+fn check_code_set(data: &str, code_set: &str) -> Result<(), String> {
+    let is_fnc = |c: char| ('\u{00f1}'..='\u{00f4}').contains(&c);
+    let (allowed, is_allowed): (&str, fn(char) -> bool) = match code_set {
+        "A" => ("ASCII 0-95 (no lowercase letters)", |c| {
+            c.is_ascii() && c as u32 <= 95
+        }),
+        // rxing 强制码集 B 时不接受空格
+        "B" => ("ASCII 33-127", |c| c.is_ascii() && c as u32 > 32),
+        _ => ("only digit pairs and FNC1", |c| {
+            c.is_ascii_digit() || c == ESCAPE_FNC_1
+        }),
+    };
+    let bad = data
+        .chars()
+        .enumerate()
+        .find(|&(_, c)| !(is_allowed(c) || (code_set != "C" && is_fnc(c))));
+    if let Some((index, c)) = bad {
+        return Err(format!(
+            "CODE_128 code set {} cannot encode {:?} at index {}; it supports {}",
+            code_set, c, index, allowed
+        ));
+    }
+    if code_set == "C" {
+        // 码集 C 每个符号字符编码两位数字, FNC1 之间的数字个数必须为偶数
+        if let Some(run) = data
+            .split(ESCAPE_FNC_1)
+            .find(|run| !run.len().is_multiple_of(2))
+        {
+            return Err(format!(
+                "CODE_128 code set C encodes digit pairs, but {:?} has an odd number of digits",
+                run
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn check_chars(
+    data: &str,
+    format: &BarcodeFormat,
+    allowed: &str,
+    is_allowed: impl Fn(char) -> bool,
+) -> Result<(), String> {
+    match data.chars().enumerate().find(|(_, c)| !is_allowed(*c)) {
+        Some((index, c)) => Err(format!(
+            "{:?} cannot encode {:?} at index {}; it supports {}",
+            format, c, index, allowed
+        )),
+        None => Ok(()),
+    }
+}
+
+fn check_digits(data: &str, format: &BarcodeFormat) -> Result<(), String> {
+    check_chars(data, format, "only the digits 0-9", |c| c.is_ascii_digit())
+}
+
+// data_digits 位数据, 可再带 1 位校验位; 带校验位时必须正确
+fn check_upc_ean(data: &str, format: &BarcodeFormat, data_digits: usize) -> Result<(), String> {
+    check_digits(data, format)?;
+    let length = data.len();
+    if length != data_digits && length != data_digits + 1 {
+        return Err(format!(
+            "{:?} needs {} digits, or {} with the check digit, got {}",
+            format,
+            data_digits,
+            data_digits + 1,
+            length
+        ));
+    }
+    if length == data_digits {
+        return Ok(());
+    }
+    let (body, given) = data.split_at(data_digits);
+    let expected = if *format == BarcodeFormat::UPC_E {
+        check_digit(&upc_e_to_upc_a_body(body))
+    } else {
+        check_digit(body)
+    };
+    if given.as_bytes()[0] != expected {
+        return Err(format!(
+            "{:?} check digit is '{}' but should be '{}' for {}",
+            format, given, expected as char, body
+        ));
+    }
+    Ok(())
+}
+
+// 自右向左权重为 3, 1, 3, ...
+pub(crate) fn check_digit(body: &str) -> u8 {
+    let sum: u32 = body
+        .bytes()
+        .rev()
+        .enumerate()
+        .map(|(i, d)| u32::from(d - b'0') * if i % 2 == 0 { 3 } else { 1 })
+        .sum();
+    b'0' + ((10 - sum % 10) % 10) as u8
+}
+
+// 7 位或带校验位的 8 位 UPC-E 展开为 12 位 UPC-A; 检查数制与给出的校验位
+pub(crate) fn upc_e_to_upc_a(upc_e: &str) -> Result<String, String> {
+    check(upc_e, &BarcodeFormat::UPC_E)?;
+    let mut upc_a = upc_e_to_upc_a_body(&upc_e[..7]);
+    upc_a.push(check_digit(&upc_a) as char);
+    Ok(upc_a)
+}
+
+// UPC-E 的 7 位 (数制 + 6 位) 展开为 UPC-A 的前 11 位, 校验位按展开后的数计算
+fn upc_e_to_upc_a_body(upc_e: &str) -> String {
+    // 数据均为 ASCII 数字, 可以按字节切片
+    let (system, d) = upc_e.split_at(1);
+    let last = &d[5..];
+    match last {
+        "0" | "1" | "2" => format!("{}{}{}0000{}", system, &d[..2], last, &d[2..5]),
+        "3" => format!("{}{}00000{}", system, &d[..3], &d[3..5]),
+        "4" => format!("{}{}00000{}", system, &d[..4], &d[4..5]),
+        _ => format!("{}{}0000{}", system, &d[..5], last),
+    }
+}
+
+// 起止符可省略 (编码器补 A); 给出时两端必须同为 A-D 或同为 T/N/*/E
+fn check_codabar(data: &str) -> Result<(), String> {
+    let chars: Vec<char> = data.chars().collect();
+    let guard_set = |c: char| match c.to_ascii_uppercase() {
+        'A'..='D' => Some(0),
+        'T' | 'N' | '*' | 'E' => Some(1),
+        _ => None,
+    };
+    let mut body_start = 0;
+    if let [first, .., last] = chars[..] {
+        match (guard_set(first), guard_set(last)) {
+            (None, None) => {}
+            (Some(a), Some(b)) if a == b => body_start = 1,
+            _ => {
+                return Err(format!(
+                    "CODABAR start and stop guards must both be A-D or both be T, N, * or E, \
+                     got {:?} and {:?}",
+                    first, last
+                ))
+            }
+        }
+    }
+    let body = &chars[body_start..chars.len() - body_start];
+    match body
+        .iter()
+        .position(|&c| !(c.is_ascii_digit() || "-$:/.+".contains(c)))
+    {
+        Some(index) => Err(format!(
+            "CODABAR cannot encode {:?} at index {}; between the guards it supports only \
+             0-9 and - $ : / . +",
+            body[index],
+            index + body_start
+        )),
+        None => Ok(()),
+    }
+}
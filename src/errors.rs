@@ -0,0 +1,97 @@
+use pyo3::create_exception;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rxing::Exceptions;
+
+// 与 ZXing 相同的异常层次. 都继承 ValueError, 以前捕获 ValueError 的代码不受影响
+create_exception!(
+    rxing_lib,
+    ReaderException,
+    PyValueError,
+    "Decoding failed; base class of NotFoundException, ChecksumException and FormatException."
+);
+create_exception!(
+    rxing_lib,
+    NotFoundException,
+    ReaderException,
+    "No barcode was found in the image."
+);
+create_exception!(
+    rxing_lib,
+    ChecksumException,
+    ReaderException,
+    "A barcode was found but its checksum or error correction failed."
+);
+create_exception!(
+    rxing_lib,
+    FormatException,
+    ReaderException,
+    "A barcode was found but its content does not follow the symbology's rules."
+);
+create_exception!(rxing_lib, WriterException, PyValueError, "Encoding failed.");
+
+// rxing 的解码错误转换为对应的异常. 实例带 kind (rxing 的错误名, 如 "NotFoundException"
+// 或 "ReedSolomonException") 与 detail (rxing 附带的说明, 没有时为 None)
+pub(crate) fn reader_error(py: Python, message: String, e: &Exceptions) -> PyErr {
+    let err = match e {
+        Exceptions::NotFoundException(_) => NotFoundException::new_err(message),
+        Exceptions::ChecksumException(_) | Exceptions::ReedSolomonException(_) => {
+            ChecksumException::new_err(message)
+        }
+        Exceptions::FormatException(_) => FormatException::new_err(message),
+        _ => ReaderException::new_err(message),
+    };
+    let (kind, detail) = error_parts(e);
+    let value = err.value(py);
+    match value
+        .setattr("kind", kind)
+        .and_then(|_| value.setattr("detail", detail))
+    {
+        Ok(()) => err,
+        Err(e) => e,
+    }
+}
+
+// rxing 的错误名, 如 "NotFoundException"
+pub(crate) fn error_kind(e: &Exceptions) -> &'static str {
+    match e {
+        Exceptions::IllegalArgumentException(_) => "IllegalArgumentException",
+        Exceptions::UnsupportedOperationException(_) => "UnsupportedOperationException",
+        Exceptions::IllegalStateException(_) => "IllegalStateException",
+        Exceptions::ArithmeticException(_) => "ArithmeticException",
+        Exceptions::NotFoundException(_) => "NotFoundException",
+        Exceptions::FormatException(_) => "FormatException",
+        Exceptions::ChecksumException(_) => "ChecksumException",
+        Exceptions::ReaderException(_) => "ReaderException",
+        Exceptions::WriterException(_) => "WriterException",
+        Exceptions::ReedSolomonException(_) => "ReedSolomonException",
+        Exceptions::IndexOutOfBoundsException(_) => "IndexOutOfBoundsException",
+        Exceptions::RuntimeException(_) => "RuntimeException",
+        Exceptions::ParseException(_) => "ParseException",
+        Exceptions::ReaderDecodeException() => "ReaderDecodeException",
+    }
+}
+
+// rxing 的错误名与附带的说明, 说明为空时为 None
+fn error_parts(e: &Exceptions) -> (String, Option<String>) {
+    let detail = match e {
+        Exceptions::IllegalArgumentException(detail)
+        | Exceptions::UnsupportedOperationException(detail)
+        | Exceptions::IllegalStateException(detail)
+        | Exceptions::ArithmeticException(detail)
+        | Exceptions::NotFoundException(detail)
+        | Exceptions::FormatException(detail)
+        | Exceptions::ChecksumException(detail)
+        | Exceptions::ReaderException(detail)
+        | Exceptions::WriterException(detail)
+        | Exceptions::ReedSolomonException(detail)
+        | Exceptions::IndexOutOfBoundsException(detail)
+        | Exceptions::RuntimeException(detail)
+        | Exceptions::ParseException(detail) => Some(detail.clone()),
+        Exceptions::ReaderDecodeException() => None,
+    };
+    (
+        error_kind(e).to_string(),
+        detail.filter(|detail| !detail.is_empty()),
+    )
+}
@@ -0,0 +1,140 @@
+use image::codecs::gif::GifDecoder;
+use image::codecs::png::PngDecoder;
+use image::codecs::webp::WebPDecoder;
+use image::error::{DecodingError, ImageFormatHint, UnsupportedError, UnsupportedErrorKind};
+use image::{
+    AnimationDecoder, DynamicImage, ImageBuffer, ImageError, ImageFormat, ImageResult, Luma, LumaA,
+    Rgb, Rgba,
+};
+use std::io::Cursor;
+use tiff::decoder::{Decoder as TiffDecoder, DecodingResult};
+use tiff::ColorType as TiffColorType;
+
+// 依次把每一帧交给 on_frame: GIF / APNG / 动画 WebP 的每一帧 (image 已按处置方式合成到完整画布),
+// 多页 TIFF 的每一页. 其它图像只有一帧, 与 decode_image_bytes 相同地按 EXIF 方向旋转.
+// 帧逐个解码, 不会同时把所有帧放在内存中
+pub(crate) fn for_each_frame<F>(bytes: &[u8], mut on_frame: F) -> ImageResult<()>
+where
+    F: FnMut(DynamicImage),
+{
+    match image::guess_format(bytes) {
+        Ok(ImageFormat::Gif) => {
+            for_each_rgba_frame(GifDecoder::new(Cursor::new(bytes))?.into_frames(), on_frame)
+        }
+        Ok(ImageFormat::Png) => {
+            let decoder = PngDecoder::new(Cursor::new(bytes))?;
+            if decoder.is_apng()? {
+                return for_each_rgba_frame(decoder.apng()?.into_frames(), on_frame);
+            }
+            on_frame(crate::load_image_bytes(bytes, true)?);
+            Ok(())
+        }
+        Ok(ImageFormat::WebP) => {
+            let decoder = WebPDecoder::new(Cursor::new(bytes))?;
+            if decoder.has_animation() {
+                return for_each_rgba_frame(decoder.into_frames(), on_frame);
+            }
+            on_frame(crate::load_image_bytes(bytes, true)?);
+            Ok(())
+        }
+        Ok(ImageFormat::Tiff) => for_each_tiff_page(bytes, on_frame),
+        _ => {
+            on_frame(crate::load_image_bytes(bytes, true)?);
+            Ok(())
+        }
+    }
+}
+
+fn for_each_rgba_frame<F>(frames: image::Frames, mut on_frame: F) -> ImageResult<()>
+where
+    F: FnMut(DynamicImage),
+{
+    for frame in frames {
+        on_frame(DynamicImage::ImageRgba8(frame?.into_buffer()));
+    }
+    Ok(())
+}
+
+// image 的 TiffDecoder 只读第一页, 这里直接用 tiff 逐页读取; 支持的颜色类型与 image 相同
+fn for_each_tiff_page<F>(bytes: &[u8], mut on_frame: F) -> ImageResult<()>
+where
+    F: FnMut(DynamicImage),
+{
+    let mut decoder = TiffDecoder::new(Cursor::new(bytes)).map_err(tiff_error)?;
+    loop {
+        let (width, height) = decoder.dimensions().map_err(tiff_error)?;
+        let color_type = decoder.colortype().map_err(tiff_error)?;
+        let pixels = decoder.read_image().map_err(tiff_error)?;
+        on_frame(tiff_page(width, height, color_type, pixels)?);
+        if !decoder.more_images() {
+            return Ok(());
+        }
+        decoder.next_image().map_err(tiff_error)?;
+    }
+}
+
+fn tiff_page(
+    width: u32,
+    height: u32,
+    color_type: TiffColorType,
+    pixels: DecodingResult,
+) -> ImageResult<DynamicImage> {
+    let page = match (color_type, pixels) {
+        (TiffColorType::Gray(8), DecodingResult::U8(v)) => {
+            ImageBuffer::<Luma<u8>, _>::from_raw(width, height, v).map(DynamicImage::from)
+        }
+        (TiffColorType::Gray(16), DecodingResult::U16(v)) => {
+            ImageBuffer::<Luma<u16>, _>::from_raw(width, height, v).map(DynamicImage::from)
+        }
+        (TiffColorType::GrayA(8), DecodingResult::U8(v)) => {
+            ImageBuffer::<LumaA<u8>, _>::from_raw(width, height, v).map(DynamicImage::from)
+        }
+        (TiffColorType::GrayA(16), DecodingResult::U16(v)) => {
+            ImageBuffer::<LumaA<u16>, _>::from_raw(width, height, v).map(DynamicImage::from)
+        }
+        (TiffColorType::RGB(8), DecodingResult::U8(v)) => {
+            ImageBuffer::<Rgb<u8>, _>::from_raw(width, height, v).map(DynamicImage::from)
+        }
+        (TiffColorType::RGB(16), DecodingResult::U16(v)) => {
+            ImageBuffer::<Rgb<u16>, _>::from_raw(width, height, v).map(DynamicImage::from)
+        }
+        (TiffColorType::RGBA(8), DecodingResult::U8(v)) => {
+            ImageBuffer::<Rgba<u8>, _>::from_raw(width, height, v).map(DynamicImage::from)
+        }
+        (TiffColorType::RGBA(16), DecodingResult::U16(v)) => {
+            ImageBuffer::<Rgba<u16>, _>::from_raw(width, height, v).map(DynamicImage::from)
+        }
+        // 与 image 相同地把 CMYK 换算为 RGB
+        (TiffColorType::CMYK(8), DecodingResult::U8(v)) => {
+            let rgb = v
+                .chunks_exact(4)
+                .flat_map(|cmyk| {
+                    let k = 255 - u16::from(cmyk[3]);
+                    cmyk[..3]
+                        .iter()
+                        .map(move |&c| ((255 - u16::from(c)) * k / 255) as u8)
+                })
+                .collect();
+            ImageBuffer::<Rgb<u8>, _>::from_raw(width, height, rgb).map(DynamicImage::from)
+        }
+        (color_type, _) => {
+            return Err(ImageError::Unsupported(
+                UnsupportedError::from_format_and_kind(
+                    ImageFormatHint::Exact(ImageFormat::Tiff),
+                    UnsupportedErrorKind::GenericFeature(format!(
+                        "TIFF color type {:?}",
+                        color_type
+                    )),
+                ),
+            ))
+        }
+    };
+    page.ok_or_else(|| tiff_error("page data is shorter than its dimensions"))
+}
+
+fn tiff_error(e: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> ImageError {
+    ImageError::Decoding(DecodingError::new(
+        ImageFormatHint::Exact(ImageFormat::Tiff),
+        e,
+    ))
+}
@@ -0,0 +1,531 @@
+use pyo3::prelude::*;
+use rxing::common::BitMatrix;
+use rxing::datamatrix::encoder::{
+    error_correction, DefaultPlacement, SymbolInfo, SymbolInfoLookup, SymbolShapeHint,
+};
+use rxing::{EncodeHints, Exceptions};
+
+const FNC1_CODEWORD: u8 = 232;
+const PAD_CODEWORD: u8 = 129;
+const GROUP_SEPARATOR: char = '\u{001d}';
+// rxing 编码器输入中表示 FNC1 的字符 (ESCAPE_FNC_1)
+const ESCAPED_FNC1: char = '\u{00f1}';
+
+// 表示内容为 GS1 数据的符号标识符: Code 128, GS1 DataBar, Data Matrix, QR 码, DotCode
+pub(crate) const SYMBOLOGY_IDENTIFIERS: [&str; 5] = ["]C1", "]e0", "]d2", "]Q3", "]J1"];
+
+// AI 数据字段的字符集: 纯数字, 或 GS1 规范的 82 个可编码字符 (CSET 82)
+#[derive(Clone, Copy, PartialEq)]
+enum Charset {
+    Numeric,
+    Alphanumeric,
+}
+
+// 一条 AI 规则: prefix 匹配长度为 ai_length 的 AI (如 "310" 匹配 3100-3109),
+// 数据长度在 min..=max 之间; check_digit 表示最后一位是 GS1 模 10 校验位
+struct AiSpec {
+    prefix: &'static str,
+    ai_length: usize,
+    charset: Charset,
+    min: usize,
+    max: usize,
+    check_digit: bool,
+}
+
+const fn numeric(prefix: &'static str, ai_length: usize, min: usize, max: usize) -> AiSpec {
+    AiSpec {
+        prefix,
+        ai_length,
+        charset: Charset::Numeric,
+        min,
+        max,
+        check_digit: false,
+    }
+}
+
+const fn alphanumeric(prefix: &'static str, ai_length: usize, min: usize, max: usize) -> AiSpec {
+    AiSpec {
+        prefix,
+        ai_length,
+        charset: Charset::Alphanumeric,
+        min,
+        max,
+        check_digit: false,
+    }
+}
+
+const fn with_check_digit(prefix: &'static str, ai_length: usize, length: usize) -> AiSpec {
+    AiSpec {
+        prefix,
+        ai_length,
+        charset: Charset::Numeric,
+        min: length,
+        max: length,
+        check_digit: true,
+    }
+}
+
+// GS1 通用规范中常用的 AI
+const AI_TABLE: [AiSpec; 110] = [
+    with_check_digit("00", 2, 18),
+    with_check_digit("01", 2, 14),
+    with_check_digit("02", 2, 14),
+    alphanumeric("10", 2, 1, 20),
+    numeric("11", 2, 6, 6),
+    numeric("12", 2, 6, 6),
+    numeric("13", 2, 6, 6),
+    numeric("15", 2, 6, 6),
+    numeric("16", 2, 6, 6),
+    numeric("17", 2, 6, 6),
+    numeric("20", 2, 2, 2),
+    alphanumeric("21", 2, 1, 20),
+    alphanumeric("22", 2, 1, 20),
+    alphanumeric("235", 3, 1, 28),
+    alphanumeric("240", 3, 1, 30),
+    alphanumeric("241", 3, 1, 30),
+    numeric("242", 3, 1, 6),
+    alphanumeric("243", 3, 1, 20),
+    alphanumeric("250", 3, 1, 30),
+    alphanumeric("251", 3, 1, 30),
+    alphanumeric("253", 3, 13, 30),
+    alphanumeric("254", 3, 1, 20),
+    numeric("255", 3, 13, 25),
+    numeric("30", 2, 1, 8),
+    numeric("310", 4, 6, 6),
+    numeric("311", 4, 6, 6),
+    numeric("312", 4, 6, 6),
+    numeric("313", 4, 6, 6),
+    numeric("314", 4, 6, 6),
+    numeric("315", 4, 6, 6),
+    numeric("316", 4, 6, 6),
+    numeric("32", 4, 6, 6),
+    numeric("330", 4, 6, 6),
+    numeric("331", 4, 6, 6),
+    numeric("332", 4, 6, 6),
+    numeric("333", 4, 6, 6),
+    numeric("334", 4, 6, 6),
+    numeric("335", 4, 6, 6),
+    numeric("336", 4, 6, 6),
+    numeric("337", 4, 6, 6),
+    numeric("34", 4, 6, 6),
+    numeric("350", 4, 6, 6),
+    numeric("351", 4, 6, 6),
+    numeric("352", 4, 6, 6),
+    numeric("353", 4, 6, 6),
+    numeric("354", 4, 6, 6),
+    numeric("355", 4, 6, 6),
+    numeric("356", 4, 6, 6),
+    numeric("357", 4, 6, 6),
+    numeric("36", 4, 6, 6),
+    numeric("37", 2, 1, 8),
+    numeric("390", 4, 1, 15),
+    numeric("391", 4, 4, 18),
+    numeric("392", 4, 1, 15),
+    numeric("393", 4, 4, 18),
+    numeric("394", 4, 4, 4),
+    numeric("395", 4, 6, 6),
+    alphanumeric("400", 3, 1, 30),
+    alphanumeric("401", 3, 1, 30),
+    with_check_digit("402", 3, 17),
+    alphanumeric("403", 3, 1, 30),
+    with_check_digit("410", 3, 13),
+    with_check_digit("411", 3, 13),
+    with_check_digit("412", 3, 13),
+    with_check_digit("413", 3, 13),
+    with_check_digit("414", 3, 13),
+    with_check_digit("415", 3, 13),
+    with_check_digit("416", 3, 13),
+    with_check_digit("417", 3, 13),
+    alphanumeric("420", 3, 1, 20),
+    alphanumeric("421", 3, 4, 12),
+    numeric("422", 3, 3, 3),
+    numeric("423", 3, 3, 15),
+    numeric("424", 3, 3, 3),
+    numeric("425", 3, 3, 15),
+    numeric("426", 3, 3, 3),
+    alphanumeric("427", 3, 1, 3),
+    numeric("7001", 4, 13, 13),
+    alphanumeric("7002", 4, 1, 30),
+    numeric("7003", 4, 10, 10),
+    numeric("7004", 4, 1, 4),
+    alphanumeric("7005", 4, 1, 12),
+    numeric("7006", 4, 6, 6),
+    numeric("7007", 4, 6, 12),
+    alphanumeric("7008", 4, 1, 3),
+    alphanumeric("7009", 4, 1, 10),
+    alphanumeric("7010", 4, 1, 2),
+    alphanumeric("7020", 4, 1, 20),
+    alphanumeric("7021", 4, 1, 20),
+    alphanumeric("7022", 4, 1, 20),
+    alphanumeric("7023", 4, 1, 30),
+    alphanumeric("703", 4, 4, 30),
+    alphanumeric("710", 3, 1, 20),
+    alphanumeric("711", 3, 1, 20),
+    alphanumeric("712", 3, 1, 20),
+    alphanumeric("713", 3, 1, 20),
+    alphanumeric("714", 3, 1, 20),
+    alphanumeric("715", 3, 1, 20),
+    alphanumeric("716", 3, 1, 20),
+    numeric("8001", 4, 14, 14),
+    alphanumeric("8002", 4, 1, 20),
+    alphanumeric("8003", 4, 14, 30),
+    alphanumeric("8004", 4, 1, 30),
+    numeric("8005", 4, 6, 6),
+    numeric("8006", 4, 18, 18),
+    alphanumeric("8007", 4, 1, 34),
+    numeric("8008", 4, 8, 12),
+    with_check_digit("8017", 4, 18),
+    with_check_digit("8018", 4, 18),
+    alphanumeric("9", 2, 1, 90),
+];
+
+// 数据长度预先定义的 AI 前两位; 其它 AI 后面 (最后一个除外) 需要 FNC1 分隔
+const PREDEFINED_LENGTH_PREFIXES: [&str; 23] = [
+    "00", "01", "02", "03", "04", "11", "12", "13", "14", "15", "16", "17", "18", "19", "20", "23",
+    "31", "32", "33", "34", "35", "36", "41",
+];
+
+// 前两位在预定义长度表中且 AI 表里长度固定时, 数据后不需要 FNC1 分隔. 只看前两位不够:
+// 如 "23" 在表中, 但 AI 235 是变长的
+fn is_predefined_length(ai: &str, spec: &AiSpec) -> bool {
+    spec.min == spec.max && PREDEFINED_LENGTH_PREFIXES.contains(&&ai[..2])
+}
+
+fn is_cset82(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "!\"%&'()*+,-./:;<=>?_".contains(c)
+}
+
+// GS1 模 10 校验位: 从右往左 (不含校验位) 权重依次为 3, 1
+fn gs1_check_digit(digits: &str) -> u32 {
+    let sum: u32 = digits
+        .chars()
+        .rev()
+        .filter_map(|c| c.to_digit(10))
+        .enumerate()
+        .map(|(i, d)| if i % 2 == 0 { d * 3 } else { d })
+        .sum();
+    (10 - sum % 10) % 10
+}
+
+fn lookup(ai: &str) -> Option<&'static AiSpec> {
+    AI_TABLE
+        .iter()
+        .find(|spec| ai.len() == spec.ai_length && ai.starts_with(spec.prefix))
+}
+
+fn validate(ai: &str, value: &str) -> PyResult<()> {
+    let error = |message: String| PyErr::new::<pyo3::exceptions::PyValueError, _>(message);
+    let spec = lookup(ai)
+        .filter(|_| ai.chars().all(|c| c.is_ascii_digit()))
+        .ok_or_else(|| error(format!("Unknown GS1 application identifier {:?}.", ai)))?;
+
+    let length = value.chars().count();
+    if length < spec.min || length > spec.max {
+        return Err(error(if spec.min == spec.max {
+            format!(
+                "AI ({}) value must be {} characters long, got {}.",
+                ai, spec.min, length
+            )
+        } else {
+            format!(
+                "AI ({}) value must be {} to {} characters long, got {}.",
+                ai, spec.min, spec.max, length
+            )
+        }));
+    }
+    match spec.charset {
+        Charset::Numeric if !value.chars().all(|c| c.is_ascii_digit()) => {
+            return Err(error(format!(
+                "AI ({}) value must be numeric, got {:?}.",
+                ai, value
+            )))
+        }
+        Charset::Alphanumeric => {
+            if let Some(c) = value.chars().find(|c| !is_cset82(*c)) {
+                return Err(error(format!(
+                    "AI ({}) value contains {:?}, which is not in the GS1 character set.",
+                    ai, c
+                )));
+            }
+        }
+        _ => {}
+    }
+    if spec.check_digit {
+        let (digits, check) = value.split_at(value.len() - 1);
+        let expected = gs1_check_digit(digits);
+        if check.parse::<u32>().ok() != Some(expected) {
+            return Err(error(format!(
+                "AI ({}) check digit is {}, expected {}.",
+                ai, check, expected
+            )));
+        }
+    }
+    Ok(())
+}
+
+// 按给定顺序拼接 AI 与数据; 非预定义长度的 AI 后插入 separator (最后一个元素除外)
+pub(crate) fn element_string(elements: &[(String, String)], separator: char) -> PyResult<String> {
+    if elements.is_empty() {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "ai_map must contain at least one application identifier.",
+        ));
+    }
+    let mut contents = String::new();
+    for (index, (ai, value)) in elements.iter().enumerate() {
+        validate(ai, value)?;
+        contents.push_str(ai);
+        contents.push_str(value);
+        let is_last = index + 1 == elements.len();
+        let predefined = lookup(ai).is_some_and(|spec| is_predefined_length(ai, spec));
+        if !is_last && !predefined {
+            contents.push(separator);
+        }
+    }
+    Ok(contents)
+}
+
+// 把 GS1 元素串拆分为 (AI, 数据) 并按 AI 表校验. 接受解码器输出的形式 (可带符号标识符,
+// 以 GS 或 FNC1 分隔) 和人可读的 "(01)...(10)..." 形式. 相同 AI 出现多次时数据必须一致
+pub(crate) fn parse_element_string(text: &str) -> PyResult<Vec<(String, String)>> {
+    let error = |message: String| PyErr::new::<pyo3::exceptions::PyValueError, _>(message);
+    let unprefixed = SYMBOLOGY_IDENTIFIERS
+        .iter()
+        .find_map(|id| text.strip_prefix(id))
+        .unwrap_or(text);
+    let chars: Vec<char> = unprefixed
+        .trim_start_matches([GROUP_SEPARATOR, ESCAPED_FNC1])
+        .chars()
+        .collect();
+    let parsed = if chars.first() == Some(&'(') {
+        split_bracketed(&chars)?
+    } else {
+        split_element_string(&chars)?
+    };
+    if parsed.is_empty() {
+        return Err(error(format!("{:?} contains no GS1 elements.", text)));
+    }
+
+    let mut elements: Vec<(String, String)> = Vec::new();
+    for (ai, value) in parsed {
+        validate(&ai, &value)?;
+        match elements.iter().find(|(seen, _)| *seen == ai) {
+            Some((_, seen_value)) if *seen_value != value => {
+                return Err(error(format!(
+                    "AI ({}) appears twice with different values {:?} and {:?}.",
+                    ai, seen_value, value
+                )))
+            }
+            Some(_) => {}
+            None => elements.push((ai, value)),
+        }
+    }
+    Ok(elements)
+}
+
+// 预定义长度的 AI 按固定长度截取, 其它 AI 的数据到下一个分隔符为止
+fn split_element_string(chars: &[char]) -> PyResult<Vec<(String, String)>> {
+    let is_separator = |c: &char| *c == GROUP_SEPARATOR || *c == ESCAPED_FNC1;
+    let mut elements = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if is_separator(&chars[i]) {
+            i += 1;
+            continue;
+        }
+        let (ai, spec) = (2..=4)
+            .find_map(|length| {
+                let ai: String = chars.get(i..i + length)?.iter().collect();
+                let spec = lookup(&ai).filter(|_| ai.chars().all(|c| c.is_ascii_digit()))?;
+                Some((ai, spec))
+            })
+            .ok_or_else(|| {
+                let rest: String = chars[i..].iter().take(4).collect();
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "No GS1 application identifier at {:?} (position {}).",
+                    rest, i
+                ))
+            })?;
+        i += ai.len();
+        let end = if is_predefined_length(&ai, spec) {
+            (i + spec.max).min(chars.len())
+        } else {
+            chars[i..]
+                .iter()
+                .position(is_separator)
+                .map_or(chars.len(), |offset| i + offset)
+        };
+        elements.push((ai, chars[i..end].iter().collect()));
+        i = end;
+    }
+    Ok(elements)
+}
+
+// 数据可以包含括号, 因此只有括号内是表中的 AI 时才作为下一个元素的开始
+fn split_bracketed(chars: &[char]) -> PyResult<Vec<(String, String)>> {
+    let bracketed_ai = |start: usize| -> Option<(String, usize)> {
+        if chars.get(start) != Some(&'(') {
+            return None;
+        }
+        let close = start + chars[start..].iter().take(6).position(|&c| c == ')')?;
+        let ai: String = chars[start + 1..close].iter().collect();
+        let known = ai.chars().all(|c| c.is_ascii_digit()) && lookup(&ai).is_some();
+        known.then_some((ai, close + 1))
+    };
+    let mut elements = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let (ai, start) = bracketed_ai(i).ok_or_else(|| {
+            let rest: String = chars[i..].iter().take(6).collect();
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "No GS1 application identifier at {:?} (position {}).",
+                rest, i
+            ))
+        })?;
+        let end = (start..chars.len())
+            .find(|&j| bracketed_ai(j).is_some())
+            .unwrap_or(chars.len());
+        elements.push((ai, chars[start..end].iter().collect()));
+        i = end;
+    }
+    Ok(elements)
+}
+
+// rxing 的 Data Matrix 最小化编码器把输入中的 FNC1 当作 ECI 处理, 无法编码分隔符,
+// 因此这里直接按 ASCII 编码方式生成码字: 起始与分隔的 FNC1 为 232, 两位数字合为
+// 130 + nn, 其它字符为 ASCII 值 + 1; 纠错与模块排布仍使用 rxing 的实现
+pub(crate) fn write_data_matrix(
+    contents: &str,
+    width: u32,
+    height: u32,
+    hints: &EncodeHints,
+) -> Result<BitMatrix, Exceptions> {
+    let mut codewords = vec![FNC1_CODEWORD];
+    let chars: Vec<char> = contents.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == GROUP_SEPARATOR {
+            codewords.push(FNC1_CODEWORD);
+        } else if c.is_ascii_digit() && chars.get(i + 1).is_some_and(|d| d.is_ascii_digit()) {
+            let pair = (c as u8 - b'0') * 10 + (chars[i + 1] as u8 - b'0');
+            codewords.push(130 + pair);
+            i += 1;
+        } else {
+            codewords.push(c as u8 + 1);
+        }
+        i += 1;
+    }
+
+    let lookup = SymbolInfoLookup::new();
+    let symbol_info = lookup
+        .lookup_with_codewords_shape_size_fail(
+            codewords.len() as u32,
+            SymbolShapeHint::FORCE_NONE,
+            &None,
+            &None,
+            true,
+        )?
+        .ok_or_else(|| Exceptions::illegal_argument_with("no Data Matrix symbol fits"))?;
+    // 填充码字: 第一个为 129, 其后按 253 状态伪随机算法生成
+    let capacity = symbol_info.getDataCapacity() as usize;
+    if codewords.len() < capacity {
+        codewords.push(PAD_CODEWORD);
+    }
+    while codewords.len() < capacity {
+        let position = codewords.len() as u32 + 1;
+        let pad = PAD_CODEWORD as u32 + (149 * position) % 253 + 1;
+        codewords.push(if pad <= 254 { pad } else { pad - 254 } as u8);
+    }
+
+    let data: String = codewords.iter().map(|&codeword| codeword as char).collect();
+    let with_ecc = error_correction::encodeECC200(&data, symbol_info)?;
+    let mut placement = DefaultPlacement::new(
+        with_ecc,
+        symbol_info.getSymbolDataWidth()? as usize,
+        symbol_info.getSymbolDataHeight()? as usize,
+    );
+    placement.place()?;
+
+    let margin = match &hints.Margin {
+        Some(margin) => margin
+            .parse::<u32>()
+            .map_err(|e| Exceptions::parse_with(format!("could not parse {margin}: {e}")))?,
+        None => 0,
+    };
+    render_data_matrix(&placement, symbol_info, width, height, margin)
+}
+
+// 与 DataMatrixWriter 相同: 加上定位图形, 再按请求尺寸整数倍放大并居中
+fn render_data_matrix(
+    placement: &DefaultPlacement,
+    symbol_info: &SymbolInfo,
+    width: u32,
+    height: u32,
+    margin: u32,
+) -> Result<BitMatrix, Exceptions> {
+    let data_width = symbol_info.getSymbolDataWidth()?;
+    let data_height = symbol_info.getSymbolDataHeight()?;
+    let symbol_width = symbol_info.getSymbolWidth()?;
+    let symbol_height = symbol_info.getSymbolHeight()?;
+    // 每个数据区四周各有一圈定位图形
+    let region_width = data_width / ((symbol_width - data_width) / 2);
+    let region_height = data_height / ((symbol_height - data_height) / 2);
+
+    let mut modules = BitMatrix::new(symbol_width, symbol_height)?;
+    let mut matrix_y = 0;
+    for y in 0..data_height {
+        if y % region_height == 0 {
+            for x in (0..symbol_width).step_by(2) {
+                modules.set(x, matrix_y);
+            }
+            matrix_y += 1;
+        }
+        let mut matrix_x = 0;
+        for x in 0..data_width {
+            if x % region_width == 0 {
+                modules.set(matrix_x, matrix_y);
+                matrix_x += 1;
+            }
+            if placement.getBit(x as usize, y as usize) {
+                modules.set(matrix_x, matrix_y);
+            }
+            matrix_x += 1;
+            if x % region_width == region_width - 1 {
+                if y % 2 == 0 {
+                    modules.set(matrix_x, matrix_y);
+                }
+                matrix_x += 1;
+            }
+        }
+        matrix_y += 1;
+        if y % region_height == region_height - 1 {
+            for x in 0..symbol_width {
+                modules.set(x, matrix_y);
+            }
+            matrix_y += 1;
+        }
+    }
+
+    let padded_width = symbol_width + margin * 2;
+    let padded_height = symbol_height + margin * 2;
+    let output_width = width.max(padded_width);
+    let output_height = height.max(padded_height);
+    let multiple = (output_width / padded_width).min(output_height / padded_height);
+    let (mut output, left, top) = if height < symbol_height || width < symbol_width {
+        (BitMatrix::new(symbol_width, symbol_height)?, 0, 0)
+    } else {
+        (
+            BitMatrix::new(width, height)?,
+            (output_width - symbol_width * multiple) / 2,
+            (output_height - symbol_height * multiple) / 2,
+        )
+    };
+    for y in 0..symbol_height {
+        for x in 0..symbol_width {
+            if modules.get(x, y) {
+                output.setRegion(left + x * multiple, top + y * multiple, multiple, multiple)?;
+            }
+        }
+    }
+    Ok(output)
+}
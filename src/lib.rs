@@ -1,12 +1,114 @@
+use pyo3::buffer::PyBuffer;
 use pyo3::prelude::*;
-use pyo3::types::{PyDict, PyList};
+use pyo3::types::{
+    timezone_utc, PyBool, PyBytes, PyDateTime, PyDict, PyInt, PyList, PyString, PyType,
+};
+use rayon::prelude::*;
 use rxing::{
-    common::HybridBinarizer, BarcodeFormat, BinaryBitmap, BufferedImageLuminanceSource,
-    DecodeHints as RxingDecodeHints, EncodeHints as RxingEncodeHints, Luma8LuminanceSource,
-    MultiFormatReader, MultiFormatWriter, RXingResult as InnerRXingResult, Reader, Writer,
+    common::{string_utils, CharacterSet, Eci, GlobalHistogramBinarizer, HybridBinarizer},
+    datamatrix::encoder::SymbolShapeHint,
+    multi::{GenericMultipleBarcodeReader, MultipleBarcodeReader},
+    oned::{MultiFormatOneDReader, OneDReader},
+    pdf417::encoder::Dimensions,
+    BarcodeFormat, Binarizer, BinaryBitmap, BufferedImageLuminanceSource,
+    DecodeHints as RxingDecodeHints, Dimension, EncodeHints as RxingEncodeHints,
+    Luma8LuminanceSource, LuminanceSource, MultiFormatReader, MultiFormatWriter,
+    MultiUseMultiFormatReader, RXingResult as InnerRXingResult, RXingResultMetadataType,
+    RXingResultMetadataValue, Reader, Writer,
 };
 use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[cfg(feature = "image")]
+mod annotate;
+mod bit_binarizer;
+mod code_39;
+mod detect;
+mod encodable;
+mod errors;
+#[cfg(feature = "image")]
+mod frames;
+mod gs1;
+mod luma_source;
+mod maxicode;
+mod metadata;
+mod parsed;
+#[cfg(feature = "image")]
+mod render;
+mod svg;
+mod symbol;
+mod symbol_capacity;
+mod symbology;
+#[cfg(feature = "image")]
+mod walk;
+
+// rxing 定义的全部格式 (DXFilmEdge 没有读码器, 不列出)
+const BARCODE_FORMATS: [BarcodeFormat; 20] = [
+    BarcodeFormat::AZTEC,
+    BarcodeFormat::CODABAR,
+    BarcodeFormat::CODE_39,
+    BarcodeFormat::CODE_93,
+    BarcodeFormat::CODE_128,
+    BarcodeFormat::DATA_MATRIX,
+    BarcodeFormat::EAN_8,
+    BarcodeFormat::EAN_13,
+    BarcodeFormat::ITF,
+    BarcodeFormat::MAXICODE,
+    BarcodeFormat::MICRO_QR_CODE,
+    BarcodeFormat::PDF_417,
+    BarcodeFormat::QR_CODE,
+    BarcodeFormat::RECTANGULAR_MICRO_QR_CODE,
+    BarcodeFormat::RSS_14,
+    BarcodeFormat::RSS_EXPANDED,
+    BarcodeFormat::TELEPEN,
+    BarcodeFormat::UPC_A,
+    BarcodeFormat::UPC_E,
+    BarcodeFormat::UPC_EAN_EXTENSION,
+];
+
+// MultiFormatReader 实际会尝试的格式 (UPC_EAN_EXTENSION 只作为 EAN/UPC 的附加码元数据出现)
+const SUPPORTED_READ_FORMATS: [BarcodeFormat; 19] = [
+    BarcodeFormat::AZTEC,
+    BarcodeFormat::CODABAR,
+    BarcodeFormat::CODE_39,
+    BarcodeFormat::CODE_93,
+    BarcodeFormat::CODE_128,
+    BarcodeFormat::DATA_MATRIX,
+    BarcodeFormat::EAN_8,
+    BarcodeFormat::EAN_13,
+    BarcodeFormat::ITF,
+    BarcodeFormat::MAXICODE,
+    BarcodeFormat::MICRO_QR_CODE,
+    BarcodeFormat::PDF_417,
+    BarcodeFormat::QR_CODE,
+    BarcodeFormat::RECTANGULAR_MICRO_QR_CODE,
+    BarcodeFormat::RSS_14,
+    BarcodeFormat::RSS_EXPANDED,
+    BarcodeFormat::TELEPEN,
+    BarcodeFormat::UPC_A,
+    BarcodeFormat::UPC_E,
+];
+
+// MultiFormatWriter 有编码器的格式
+const SUPPORTED_WRITE_FORMATS: [BarcodeFormat; 14] = [
+    BarcodeFormat::AZTEC,
+    BarcodeFormat::CODABAR,
+    BarcodeFormat::CODE_39,
+    BarcodeFormat::CODE_93,
+    BarcodeFormat::CODE_128,
+    BarcodeFormat::DATA_MATRIX,
+    BarcodeFormat::EAN_8,
+    BarcodeFormat::EAN_13,
+    BarcodeFormat::ITF,
+    BarcodeFormat::PDF_417,
+    BarcodeFormat::QR_CODE,
+    BarcodeFormat::TELEPEN,
+    BarcodeFormat::UPC_A,
+    BarcodeFormat::UPC_E,
+];
 
 #[pyclass(name = "RXingResult")]
 #[derive(Clone)]
@@ -15,6 +117,9 @@ struct PyRXingResult {
     text: String,
     #[pyo3(get)]
     raw_bytes: Option<Vec<u8>>,
+    // text 解码前的原始字节; 无法准确还原时为 None
+    #[pyo3(get)]
+    text_bytes: Option<Vec<u8>>,
     #[pyo3(get)]
     num_bits: usize,
     #[pyo3(get)]
@@ -23,32 +128,746 @@ struct PyRXingResult {
     barcode_format: String,
     #[pyo3(get)]
     result_metadata: Option<HashMap<String, String>>,
+    // 解码时间, Unix 纪元毫秒 (UTC)
+    #[pyo3(get)]
+    timestamp: u64,
+    #[pyo3(get)]
+    error_correction_level: Option<String>,
+    #[pyo3(get)]
+    symbology_identifier: Option<String>,
+    #[pyo3(get)]
+    eci: Option<u32>,
+    #[pyo3(get)]
+    character_set: Option<String>,
+    // EAN/UPC 的 2 位或 5 位附加码, 以及 rxing 对其含义的解析 (期刊期号 / 建议零售价)
+    #[pyo3(get)]
+    upc_ean_extension: Option<String>,
     #[pyo3(get)]
-    timestamp: u128,
+    issue_number: Option<i32>,
+    #[pyo3(get)]
+    suggested_price: Option<String>,
+    // MaxiCode 的模式 (2-6); 模式 2 / 3 的结构化载波报文另有邮政编码, 国家代码, 服务类别
+    #[pyo3(get)]
+    maxicode_mode: Option<u8>,
+    #[pyo3(get)]
+    postal_code: Option<String>,
+    #[pyo3(get)]
+    country_code: Option<u32>,
+    #[pyo3(get)]
+    service_class: Option<u32>,
+    // 符号尺寸, 版本与结构化追加信息的来源, 由对应的 getter 在第一次读取时计算
+    symbol: Option<symbol::LazySymbol>,
+    // ALSO_INVERTED 时由反色图像的重试解出 (浅色条码印在深色底上)
+    #[pyo3(get)]
+    was_inverted: bool,
+    // 阅读方向相对图像 x 轴顺时针转过的角度 [0, 360); 正放的码为 0
+    #[pyo3(get)]
+    orientation_degrees: Option<f32>,
+    // 给定 rotations 时解出条码的角度 (顺时针, 度); 其它情况为 None
+    #[pyo3(get)]
+    rotation: Option<f32>,
+    // decode_candidates 的排序依据: 读出相同 text 的扫描线数
+    #[pyo3(get)]
+    scan_lines: Option<usize>,
+    // CODE_39_EXTENDED_MODE 下 text 是否按全 ASCII 还原
+    #[pyo3(get)]
+    code_39_extended: Option<bool>,
+    // NORMALIZE_UPC_E_TO_UPC_A 下实际读出的 8 位 UPC-E
+    #[pyo3(get)]
+    upc_e_text: Option<String>,
+    // 按类型保存的 rxing 元数据, 键与 result_metadata 相同; 由 get_metadata 读取
+    metadata: HashMap<String, RXingResultMetadataValue>,
+}
+
+// 单次解码的结果, 附带读取符号尺寸, 结构化追加信息与字节段所需的二值化矩阵
+struct Decoded {
+    result: InnerRXingResult,
+    symbol: Option<symbol::LazySymbol>,
+}
+
+impl Decoded {
+    fn new(result: InnerRXingResult, matrix: &mut symbol::MatrixCopy) -> Self {
+        Decoded {
+            symbol: symbol::LazySymbol::new(&result, matrix),
+            result,
+        }
+    }
+
+    // collect_points 为 false 时不复制 result_points, 省去每个结果的一次分配
+    fn into_py_result(self, collect_points: bool) -> PyRXingResult {
+        let mut py_result = PyRXingResult::from_result(self.result, collect_points);
+        py_result.symbol = self.symbol;
+        py_result
+    }
+}
+
+impl From<Decoded> for PyRXingResult {
+    fn from(decoded: Decoded) -> Self {
+        decoded.into_py_result(true)
+    }
+}
+
+// 识别 ECI 字符集时依次尝试的候选 (多字节编码优先, ISO-8859-1 可解码任意字节放在后面)
+const ECI_CHARSET_CANDIDATES: [CharacterSet; 25] = [
+    CharacterSet::UTF8,
+    CharacterSet::Shift_JIS,
+    CharacterSet::GB18030,
+    CharacterSet::Big5,
+    CharacterSet::EUC_KR,
+    CharacterSet::UTF16BE,
+    CharacterSet::UTF16LE,
+    CharacterSet::ISO8859_1,
+    CharacterSet::ISO8859_2,
+    CharacterSet::ISO8859_3,
+    CharacterSet::ISO8859_4,
+    CharacterSet::ISO8859_5,
+    CharacterSet::ISO8859_6,
+    CharacterSet::ISO8859_7,
+    CharacterSet::ISO8859_8,
+    CharacterSet::ISO8859_9,
+    CharacterSet::ISO8859_10,
+    CharacterSet::ISO8859_11,
+    CharacterSet::ISO8859_13,
+    CharacterSet::ISO8859_15,
+    CharacterSet::ISO8859_16,
+    CharacterSet::Cp1250,
+    CharacterSet::Cp1251,
+    CharacterSet::Cp1252,
+    CharacterSet::Cp1256,
+];
+
+// QR 系列结果的 ECI 指示符与解码字符集.
+// rxing 不保留 ECI 段信息, raw_bytes 是去掉段头后的原始字节, 因此用
+// 符号标识符判断是否声明了 ECI, 再找出能把 raw_bytes 还原成 text 的字符集
+fn qr_character_set_info(
+    res: &InnerRXingResult,
+    symbology_identifier: Option<&str>,
+) -> (Option<u32>, Option<String>) {
+    if !matches!(
+        res.getBarcodeFormat(),
+        BarcodeFormat::QR_CODE
+            | BarcodeFormat::MICRO_QR_CODE
+            | BarcodeFormat::RECTANGULAR_MICRO_QR_CODE
+    ) {
+        return (None, None);
+    }
+    let raw_bytes = res.getRawBytes();
+    if raw_bytes.is_empty() {
+        return (None, None);
+    }
+    let decodes_to_text = |charset: &CharacterSet| {
+        charset
+            .decode(raw_bytes)
+            .is_ok_and(|text| text == res.getText())
+    };
+    // 纯 ASCII 内容在所有候选字符集下结果相同, 无法确定声明的 ECI
+    if raw_bytes.is_ascii() {
+        return if raw_bytes == res.getText().as_bytes() {
+            (
+                None,
+                Some(CharacterSet::ASCII.get_charset_name().to_string()),
+            )
+        } else {
+            (None, None)
+        };
+    }
+    let has_eci = symbology_identifier
+        .and_then(|id| id.strip_prefix("]Q"))
+        .is_some_and(|modifier| matches!(modifier, "2" | "4" | "6"));
+    let guessed = if has_eci {
+        None
+    } else {
+        string_utils::guessCharset(raw_bytes, &RxingDecodeHints::default())
+            .filter(|charset| decodes_to_text(charset))
+    };
+    match guessed.or_else(|| {
+        ECI_CHARSET_CANDIDATES
+            .into_iter()
+            .find(|c| decodes_to_text(c))
+    }) {
+        Some(charset) => (
+            has_eci.then(|| Eci::from(charset) as i32 as u32),
+            Some(charset.get_charset_name().to_string()),
+        ),
+        None => (None, None),
+    }
+}
+
+// text 解码前的内容字节. 一维码与未声明 ECI 的 Data Matrix (]d1-]d3) 按 ISO-8859-1
+// 逐字节生成 text; QR 系列确定了字符集时 raw_bytes 即为内容字节. 其它情况
+// (如 rxing 的 Java 移植版 QR 读码器, raw_bytes 是码字) 无法准确还原
+fn decoded_text_bytes(
+    res: &InnerRXingResult,
+    qr_character_set_known: bool,
+    symbology_identifier: Option<&str>,
+) -> Option<Vec<u8>> {
+    let format = res.getBarcodeFormat();
+    if matches!(
+        format,
+        BarcodeFormat::QR_CODE
+            | BarcodeFormat::MICRO_QR_CODE
+            | BarcodeFormat::RECTANGULAR_MICRO_QR_CODE
+    ) {
+        return qr_character_set_known.then(|| res.getRawBytes().to_vec());
+    }
+    let byte_per_char = ONE_D_FORMATS.contains(format)
+        || (*format == BarcodeFormat::DATA_MATRIX
+            && matches!(symbology_identifier, Some("]d1" | "]d2" | "]d3")));
+    if !byte_per_char {
+        return None;
+    }
+    res.getText()
+        .chars()
+        .map(|c| u8::try_from(c as u32).ok())
+        .collect()
+}
+
+// 设置后所有结果的 timestamp 都取这个值 (Unix 纪元毫秒), 使测试中的结果可以逐字节复现
+static TIMESTAMP_OVERRIDE: Mutex<Option<u64>> = Mutex::new(None);
+
+fn current_timestamp_override() -> Option<u64> {
+    *TIMESTAMP_OVERRIDE
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[pyfunction]
+#[pyo3(signature = (timestamp))]
+fn set_timestamp_override(timestamp: Option<u64>) {
+    *TIMESTAMP_OVERRIDE
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = timestamp;
+}
+
+#[pyfunction]
+fn timestamp_override() -> Option<u64> {
+    current_timestamp_override()
 }
 
 impl From<InnerRXingResult> for PyRXingResult {
     fn from(res: InnerRXingResult) -> Self {
-        PyRXingResult {
+        PyRXingResult::from_result(res, true)
+    }
+}
+
+impl PyRXingResult {
+    fn from_result(res: InnerRXingResult, collect_points: bool) -> Self {
+        let metadata = res.getRXingResultMetadata();
+        let error_correction_level = match metadata
+            .get(&RXingResultMetadataType::ERROR_CORRECTION_LEVEL)
+        {
+            Some(RXingResultMetadataValue::ErrorCorrectionLevel(level)) if !level.is_empty() => {
+                Some(level.clone())
+            }
+            _ => None,
+        };
+        let symbology_identifier =
+            match metadata.get(&RXingResultMetadataType::SYMBOLOGY_IDENTIFIER) {
+                Some(RXingResultMetadataValue::SymbologyIdentifier(id)) if !id.is_empty() => {
+                    Some(id.clone())
+                }
+                _ => None,
+            };
+        let (eci, character_set) = qr_character_set_info(&res, symbology_identifier.as_deref());
+        let text_bytes = decoded_text_bytes(
+            &res,
+            character_set.is_some(),
+            symbology_identifier.as_deref(),
+        );
+        let upc_ean_extension = match metadata.get(&RXingResultMetadataType::UPC_EAN_EXTENSION) {
+            Some(RXingResultMetadataValue::UpcEanExtension(extension)) => Some(extension.clone()),
+            _ => None,
+        };
+        let issue_number = match metadata.get(&RXingResultMetadataType::ISSUE_NUMBER) {
+            Some(RXingResultMetadataValue::IssueNumber(number)) => Some(*number),
+            _ => None,
+        };
+        let suggested_price = match metadata.get(&RXingResultMetadataType::SUGGESTED_PRICE) {
+            Some(RXingResultMetadataValue::SuggestedPrice(price)) => Some(price.clone()),
+            _ => None,
+        };
+        let was_inverted = matches!(
+            metadata.get(&RXingResultMetadataType::IS_INVERTED),
+            Some(RXingResultMetadataValue::IsInverted(true))
+        );
+        let orientation_degrees = orientation_degrees(&res);
+        let metadata: HashMap<_, _> = metadata
+            .iter()
+            .map(|(k, v)| (format!("{:?}", k), v.clone()))
+            .collect();
+        let maxicode = (*res.getBarcodeFormat() == BarcodeFormat::MAXICODE)
+            .then(|| maxicode::fields(res.getRawBytes()))
+            .flatten();
+        let aztec_modifier = (*res.getBarcodeFormat() == BarcodeFormat::AZTEC)
+            .then(|| symbology::aztec_modifier(res.getRawBytes(), res.getNumBits()))
+            .flatten();
+        let result = PyRXingResult {
             text: res.getText().to_string(),
-            raw_bytes: Some(res.getRawBytes().to_vec()),
+            raw_bytes: (!res.getRawBytes().is_empty()).then(|| res.getRawBytes().to_vec()),
+            text_bytes,
             num_bits: res.getNumBits(),
-            result_points: Some(
+            result_points: (collect_points && !res.getPoints().is_empty()).then(|| {
                 res.getPoints()
                     .iter()
                     .map(|p| PyPoint { x: p.x, y: p.y })
-                    .collect(),
-            ),
+                    .collect()
+            }),
             barcode_format: res.getBarcodeFormat().to_string(),
             result_metadata: Some(
-                res.getRXingResultMetadata()
+                metadata
                     .iter()
-                    .map(|(k, v)| (format!("{:?}", k), format!("{:?}", v)))
+                    .map(|(key, value)| (key.clone(), format!("{:?}", value)))
                     .collect(),
             ),
-            timestamp: res.getTimestamp(),
+            timestamp: current_timestamp_override()
+                .unwrap_or_else(|| u64::try_from(res.getTimestamp()).unwrap_or(u64::MAX)),
+            error_correction_level,
+            symbology_identifier,
+            eci,
+            character_set,
+            upc_ean_extension,
+            issue_number,
+            suggested_price,
+            maxicode_mode: maxicode.as_ref().map(|fields| fields.mode),
+            postal_code: maxicode
+                .as_ref()
+                .and_then(|fields| fields.postal_code.clone()),
+            country_code: maxicode.as_ref().and_then(|fields| fields.country_code),
+            service_class: maxicode.and_then(|fields| fields.service_class),
+            symbol: None,
+            was_inverted,
+            orientation_degrees,
+            rotation: None,
+            scan_lines: None,
+            code_39_extended: None,
+            upc_e_text: None,
+            metadata,
+        };
+        match aztec_modifier {
+            Some(modifier) => result.with_symbology_modifier(modifier),
+            None => result,
+        }
+    }
+
+    // 按类型的元数据; rxing 没有给出的 QR 字节段在第一次读取 BYTE_SEGMENTS 时才恢复
+    fn metadata_value(&self, key: &str) -> Option<std::borrow::Cow<'_, RXingResultMetadataValue>> {
+        match self.metadata.get(key) {
+            Some(value) => Some(std::borrow::Cow::Borrowed(value)),
+            None if key == "BYTE_SEGMENTS" => {
+                let segments = self.symbol.as_ref()?.byte_segments()?;
+                Some(std::borrow::Cow::Owned(
+                    RXingResultMetadataValue::ByteSegments(segments.clone()),
+                ))
+            }
+            None => None,
+        }
+    }
+
+    // 绑定自己添加的元数据同时写入 result_metadata 与按类型保存的 metadata
+    fn insert_metadata(&mut self, key: &str, value: RXingResultMetadataValue) {
+        if let Some(result_metadata) = self.result_metadata.as_mut() {
+            result_metadata.insert(key.to_string(), format!("{:?}", value));
+        }
+        self.metadata.insert(key.to_string(), value);
+    }
+}
+
+// 一维码取起止两个端点的连线, QR / Data Matrix 取从符号左上角到右上角的边 (rxing 按符号的
+// 左上, 右上, 右下, 左下给出角点); 其它格式的结果点不随方向变化, 退回 ORIENTATION 元数据
+// (PDF417 / MaxiCode 为 0 / 90 / 180 / 270)
+fn orientation_degrees(res: &InnerRXingResult) -> Option<f32> {
+    let points = res.getPoints();
+    let format = res.getBarcodeFormat();
+    let edge = match points {
+        [start, .., end] if ONE_D_FORMATS.contains(format) => Some((start, end)),
+        [top_left, top_right, _, _]
+            if matches!(
+                format,
+                BarcodeFormat::QR_CODE
+                    | BarcodeFormat::MICRO_QR_CODE
+                    | BarcodeFormat::RECTANGULAR_MICRO_QR_CODE
+                    | BarcodeFormat::DATA_MATRIX
+            ) =>
+        {
+            Some((top_left, top_right))
+        }
+        _ => None,
+    };
+    if let Some((from, to)) = edge.filter(|(from, to)| from != to) {
+        return Some(
+            (to.y - from.y)
+                .atan2(to.x - from.x)
+                .to_degrees()
+                .rem_euclid(360.0),
+        );
+    }
+    match res
+        .getRXingResultMetadata()
+        .get(&RXingResultMetadataType::ORIENTATION)
+    {
+        Some(RXingResultMetadataValue::Orientation(degrees)) => {
+            Some(degrees.rem_euclid(360) as f32)
+        }
+        _ => None,
+    }
+}
+
+// 一维码解码器按 ISO-8859-1 逐字节生成 text (扩展字符为 U+0080..U+00FF)
+const ONE_D_FORMATS: [BarcodeFormat; 12] = [
+    BarcodeFormat::CODABAR,
+    BarcodeFormat::CODE_39,
+    BarcodeFormat::CODE_93,
+    BarcodeFormat::CODE_128,
+    BarcodeFormat::EAN_8,
+    BarcodeFormat::EAN_13,
+    BarcodeFormat::ITF,
+    BarcodeFormat::RSS_14,
+    BarcodeFormat::RSS_EXPANDED,
+    BarcodeFormat::TELEPEN,
+    BarcodeFormat::UPC_A,
+    BarcodeFormat::UPC_E,
+];
+
+impl PyRXingResult {
+    fn symbol_details(&self) -> Option<&symbol::SymbolDetails> {
+        self.symbol.as_ref().map(|symbol| symbol.details())
+    }
+
+    fn structured_append(&self) -> Option<symbol::StructuredAppend> {
+        self.symbol_details()?.structured_append
+    }
+
+    fn qr_landmarks(&self) -> Option<symbol::QrLandmarks> {
+        if self.barcode_format != BarcodeFormat::QR_CODE.to_string() {
+            return None;
+        }
+        let (modules, _) = self.symbol_size()?;
+        let [a, b, c, d] = self.result_points.as_deref()? else {
+            return None;
+        };
+        let corners = [a, b, c, d].map(|p| rxing::Point::new(p.x, p.y));
+        symbol::qr_landmarks(corners, modules)
+    }
+
+    // CODE_39_EXTENDED_MODE: 按全 ASCII 还原 text, code_39_extended 表示是否还原;
+    // 含有不合法的移位序列时保留读出的基本字符
+    fn with_code_39_full_ascii(mut self) -> Self {
+        let decoded = code_39::decode_full_ascii(&self.text);
+        self.code_39_extended = Some(decoded.is_some());
+        if let Some(text) = decoded {
+            self.text_bytes = Some(text.as_bytes().to_vec());
+            self.text = text;
+        }
+        self
+    }
+
+    // 改写符号标识符 ]Xm 的修饰符 m, 用于 rxing 没有区分的情况; 元数据中的值一并更新
+    fn with_symbology_modifier(mut self, modifier: u32) -> Self {
+        let Some(prefix) = self
+            .symbology_identifier
+            .as_deref()
+            .and_then(|id| id.get(..2))
+        else {
+            return self;
+        };
+        let id = format!("{}{}", prefix, modifier);
+        self.insert_metadata(
+            "SYMBOLOGY_IDENTIFIER",
+            RXingResultMetadataValue::SymbologyIdentifier(id.clone()),
+        );
+        self.symbology_identifier = Some(id);
+        self
+    }
+
+    // NORMALIZE_UPC_E_TO_UPC_A: text 换成展开后的 12 位 UPC-A, 原来的 8 位放在 upc_e_text 中
+    fn with_upc_a_text(mut self) -> Self {
+        if let Ok(upc_a) = encodable::upc_e_to_upc_a(&self.text) {
+            let upc_e = std::mem::replace(&mut self.text, upc_a);
+            self.text_bytes = Some(self.text.as_bytes().to_vec());
+            self.upc_e_text = Some(upc_e);
+        }
+        self
+    }
+
+    // 根据解码提示修正结果: 一维码指定 CHARACTER_SET 时按该字符集重新解释 text 的字节
+    fn with_decode_hints(mut self, hints: &DecodeHints) -> Self {
+        if self.barcode_format == BarcodeFormat::CODE_39.to_string() {
+            if hints.code_39_extended_mode == Some(true) {
+                self = self.with_code_39_full_ascii();
+            }
+            // rxing 总是报告 ]A0. 校验通过的校验位已被去掉时为 3, 按 Full ASCII 解释时再加 4
+            let full_ascii = self.code_39_extended == Some(true);
+            let check_digit = hints.AssumeCode39CheckDigit == Some(true);
+            let modifier = if check_digit { 3 } else { 0 } + if full_ascii { 4 } else { 0 };
+            if modifier > 0 {
+                self = self.with_symbology_modifier(modifier);
+            }
+        }
+        // ITF 的校验位校验通过并保留在 text 中
+        if hints.assume_itf_check_digit == Some(true)
+            && self.barcode_format == BarcodeFormat::ITF.to_string()
+        {
+            self = self.with_symbology_modifier(1);
+        }
+        if hints.normalize_upc_e_to_upc_a == Some(true)
+            && self.barcode_format == BarcodeFormat::UPC_E.to_string()
+        {
+            self = self.with_upc_a_text();
+        }
+        let Some(charset) = hints
+            .CharacterSet
+            .as_deref()
+            .and_then(CharacterSet::get_character_set_by_name)
+        else {
+            return self;
+        };
+        let format = BarcodeFormat::from(self.barcode_format.as_str());
+        if !ONE_D_FORMATS.contains(&format) {
+            return self;
+        }
+        let bytes: Option<Vec<u8>> = self
+            .text
+            .chars()
+            .map(|c| u8::try_from(c as u32).ok())
+            .collect();
+        if let Some(bytes) = bytes {
+            if let Ok(text) = charset.decode_replace(&bytes) {
+                self.text = text;
+                self.character_set = Some(charset.get_charset_name().to_string());
+            }
+        }
+        self
+    }
+}
+
+#[pymethods]
+impl PyRXingResult {
+    // 元数据键为 result_metadata 中的规范名称, 如 "ORIENTATION", "BYTE_SEGMENTS"
+    fn has_metadata(&self, key: &str) -> bool {
+        self.metadata_value(key).is_some()
+    }
+
+    fn get_metadata(&self, py: Python, key: &str) -> PyResult<PyObject> {
+        match self.metadata_value(key) {
+            Some(value) => metadata::to_object(py, &value),
+            None => Err(PyErr::new::<pyo3::exceptions::PyKeyError, _>(
+                key.to_string(),
+            )),
+        }
+    }
+
+    // 列出全部键时也要确定 BYTE_SEGMENTS 是否存在, 因此会恢复 QR 字节段
+    fn metadata_keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self.metadata.keys().cloned().collect();
+        if !self.metadata.contains_key("BYTE_SEGMENTS") && self.has_metadata("BYTE_SEGMENTS") {
+            keys.push("BYTE_SEGMENTS".to_string());
+        }
+        keys.sort();
+        keys
+    }
+
+    // 与 get_metadata 相同类型的全部元数据, 按键排序
+    #[getter(metadata)]
+    fn metadata_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        for key in self.metadata_keys() {
+            let value = self
+                .metadata_value(&key)
+                .expect("metadata_keys only lists present keys");
+            dict.set_item(&key, metadata::to_object(py, &value)?)?;
+        }
+        Ok(dict)
+    }
+
+    // timestamp 转换为带 UTC 时区的 datetime.datetime
+    #[getter]
+    fn timestamp_datetime<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDateTime>> {
+        let utc = timezone_utc(py);
+        PyDateTime::from_timestamp(py, self.timestamp as f64 / 1000.0, Some(&utc))
+    }
+
+    // BYTE_SEGMENTS 元数据: 字节模式各段未经字符集解码的内容, 按出现顺序排列;
+    // rxing 没有给出时第一次读取才从采样网格恢复
+    #[getter]
+    fn byte_segments<'py>(&self, py: Python<'py>) -> Option<Vec<Bound<'py, PyBytes>>> {
+        match self.metadata_value("BYTE_SEGMENTS")?.as_ref() {
+            RXingResultMetadataValue::ByteSegments(segments) => {
+                Some(segments.iter().map(|s| PyBytes::new(py, s)).collect())
+            }
+            _ => None,
+        }
+    }
+
+    // 二维码的模块数 (宽, 高); 第一次读取时重新定位符号, 之后使用缓存的值
+    #[getter]
+    fn symbol_size(&self) -> Option<(u32, u32)> {
+        self.symbol_details()?.size
+    }
+
+    // QR 版本 1-40 / Micro QR 的 M 级别 / Data Matrix 版本号; 其它格式为 None
+    #[getter]
+    fn symbol_version(&self) -> Option<u32> {
+        let format = self.symbol.as_ref()?.format();
+        symbol::symbol_version(format, self.symbol_size()?)
+    }
+
+    // QR 结构化追加: 本符号的序号 (从 0 开始), 符号总数, 整条消息的校验字节
+    #[getter]
+    fn structured_append_index(&self) -> Option<u32> {
+        self.structured_append().map(|append| append.index)
+    }
+
+    #[getter]
+    fn structured_append_total(&self) -> Option<u32> {
+        self.structured_append().map(|append| append.total)
+    }
+
+    #[getter]
+    fn structured_append_parity(&self) -> Option<u8> {
+        self.structured_append().map(|append| append.parity)
+    }
+
+    // orientation_degrees 取最接近的直角 (0 / 90 / 180 / 270), 用于把裁剪出的图像转正
+    #[getter]
+    fn orientation_right_angle(&self) -> Option<u32> {
+        let degrees = self.orientation_degrees?;
+        Some((degrees / 90.0).round() as u32 % 4 * 90)
+    }
+
+    // result_points 的外接矩形 (x, y, w, h); 一维码只有两个端点, 宽或高为 0 的边扩展为 1 像素
+    #[getter]
+    fn bounding_box(&self) -> Option<(f32, f32, f32, f32)> {
+        let points = self.result_points.as_deref().filter(|p| !p.is_empty())?;
+        let (mut min_x, mut min_y) = (f32::MAX, f32::MAX);
+        let (mut max_x, mut max_y) = (f32::MIN, f32::MIN);
+        for point in points {
+            min_x = min_x.min(point.x);
+            min_y = min_y.min(point.y);
+            max_x = max_x.max(point.x);
+            max_y = max_y.max(point.y);
+        }
+        let expand = |min: f32, max: f32| {
+            if max - min < 1.0 {
+                let center = (min + max) / 2.0;
+                (center - 0.5, 1.0)
+            } else {
+                (min, max - min)
+            }
+        };
+        let (x, width) = expand(min_x, max_x);
+        let (y, height) = expand(min_y, max_y);
+        Some((x, y, width, height))
+    }
+
+    // QR 码返回区分位置探测图形, 校正图形与右下角的 QRPoints; 需要符号尺寸来定位,
+    // 其它格式或尺寸未知时返回与 result_points 相同的列表
+    fn structured_points(&self, py: Python) -> PyResult<PyObject> {
+        if let Some(landmarks) = self.qr_landmarks() {
+            let to_py = |p: rxing::Point| PyPoint { x: p.x, y: p.y };
+            let qr_points = PyQRPoints {
+                finder_patterns: landmarks.finder_patterns.map(to_py).to_vec(),
+                alignment_pattern: landmarks.alignment_pattern.map(to_py),
+                bottom_right: to_py(landmarks.bottom_right),
+            };
+            return Ok(Py::new(py, qr_points)?.into_any());
+        }
+        let points = self.result_points.clone().unwrap_or_default();
+        Ok(points.into_pyobject(py)?.into_any().unbind())
+    }
+
+    // 二维码的四个角点, 在图像坐标中按顺时针排列, 从最靠近左上角的点开始;
+    // 结果点不是四个角点的格式 (一维码 / PDF417 等) 为 None
+    #[getter]
+    fn corners(&self) -> Option<Vec<(f32, f32)>> {
+        let points = self.result_points.as_deref().filter(|p| p.len() == 4)?;
+        let format = BarcodeFormat::from(self.barcode_format.as_str());
+        if ONE_D_FORMATS.contains(&format) {
+            return None;
+        }
+        Some(clockwise_corners(
+            points.iter().map(|p| (p.x, p.y)).collect(),
+        ))
+    }
+
+    // 用 rxing 的 ResultParser 解析 text: 网址, WiFi 配置, vCard / MeCard 联系人, geo: 坐标,
+    // 短信, 邮件, 日历事件, 商品条码与 ISBN 等; 无法识别的内容类型为 "TEXT"
+    #[getter]
+    fn parsed(&self, py: Python) -> PyResult<PyParsedResult> {
+        let result = InnerRXingResult::new(
+            &self.text,
+            self.raw_bytes.clone().unwrap_or_default(),
+            Vec::new(),
+            BarcodeFormat::from(self.barcode_format.as_str()),
+        );
+        let parsed = rxing::client::result::parseRXingResult(&result);
+        let (parsed_type, display_result, fields) = parsed::parsed_fields(py, &parsed)?;
+        Ok(PyParsedResult {
+            parsed_type,
+            display_result,
+            fields: fields.unbind(),
+        })
+    }
+}
+
+// RXingResult.parsed 的结果: type 为 rxing 的 ParsedRXingResultType 名称, 各类型的字段
+// 可作为属性读取, 也可通过 fields 取得全部字段
+#[pyclass(name = "ParsedResult")]
+struct PyParsedResult {
+    #[pyo3(get, name = "type")]
+    parsed_type: String,
+    // 适合直接显示的多行文本
+    #[pyo3(get)]
+    display_result: String,
+    fields: Py<PyDict>,
+}
+
+#[pymethods]
+impl PyParsedResult {
+    #[getter]
+    fn fields<'py>(&self, py: Python<'py>) -> Bound<'py, PyDict> {
+        self.fields
+            .bind(py)
+            .copy()
+            .unwrap_or_else(|_| PyDict::new(py))
+    }
+
+    fn __getattr__(&self, py: Python, name: &str) -> PyResult<PyObject> {
+        match self.fields.bind(py).get_item(name)? {
+            Some(value) => Ok(value.unbind()),
+            None => Err(PyErr::new::<pyo3::exceptions::PyAttributeError, _>(
+                format!("{} result has no field {:?}", self.parsed_type, name),
+            )),
         }
     }
+
+    fn __repr__(&self, py: Python) -> PyResult<String> {
+        Ok(format!(
+            "ParsedResult(type={:?}, fields={})",
+            self.parsed_type,
+            self.fields.bind(py).repr()?
+        ))
+    }
+}
+
+// 四个角点按图像坐标中的顺时针排列, 从最靠近左上角的点开始
+fn clockwise_corners(mut corners: Vec<(f32, f32)>) -> Vec<(f32, f32)> {
+    let count = corners.len() as f32;
+    let center_x = corners.iter().map(|p| p.0).sum::<f32>() / count;
+    let center_y = corners.iter().map(|p| p.1).sum::<f32>() / count;
+    // y 轴向下, 按 atan2 升序即为屏幕上的顺时针
+    corners.sort_by(|a, b| {
+        let angle_a = (a.1 - center_y).atan2(a.0 - center_x);
+        let angle_b = (b.1 - center_y).atan2(b.0 - center_x);
+        angle_a.total_cmp(&angle_b)
+    });
+    let top_left = (0..corners.len())
+        .min_by(|&i, &j| {
+            let (a, b) = (corners[i], corners[j]);
+            (a.0 + a.1).total_cmp(&(b.0 + b.1))
+        })
+        .unwrap_or(0);
+    corners.rotate_left(top_left);
+    corners
 }
 
 // PyPoint 定义
@@ -61,6 +880,35 @@ struct PyPoint {
     y: f32,
 }
 
+// QR 码结果点的结构化形式, 由 RXingResult.structured_points() 返回
+#[pyclass(name = "QRPoints")]
+#[derive(Clone)]
+struct PyQRPoints {
+    // 左上, 右上, 左下
+    #[pyo3(get)]
+    finder_patterns: Vec<PyPoint>,
+    #[pyo3(get)]
+    alignment_pattern: Option<PyPoint>,
+    #[pyo3(get)]
+    bottom_right: PyPoint,
+}
+
+#[pymethods]
+impl PyQRPoints {
+    fn __repr__(&self) -> String {
+        let point = |p: &PyPoint| format!("({}, {})", p.x, p.y);
+        let finders: Vec<String> = self.finder_patterns.iter().map(point).collect();
+        format!(
+            "QRPoints(finder_patterns=[{}], alignment_pattern={}, bottom_right={})",
+            finders.join(", "),
+            self.alignment_pattern
+                .as_ref()
+                .map_or_else(|| "None".to_string(), point),
+            point(&self.bottom_right)
+        )
+    }
+}
+
 // PyBitMatrix 定义 (如果包含编码功能)
 #[pyclass(name = "BitMatrix")]
 #[derive(Clone)]
@@ -86,9 +934,178 @@ impl PyBitMatrix {
         }
         data
     }
+
+    // 每个模块一个字节 (深色为 1, 浅色为 0), 与 from_bytes 的输入格式相同;
+    // row_major=False 时按列排列
+    #[pyo3(signature = (row_major=true))]
+    fn to_bytes<'py>(&self, py: Python<'py>, row_major: bool) -> Bound<'py, PyBytes> {
+        let (width, height) = (self.width as usize, self.height as usize);
+        PyBytes::new_with(py, width * height, |buf| {
+            for y in 0..self.height {
+                for x in 0..self.width {
+                    if self.inner_matrix.get(x, y) {
+                        let (x, y) = (x as usize, y as usize);
+                        let index = if row_major {
+                            y * width + x
+                        } else {
+                            x * height + y
+                        };
+                        buf[index] = 1;
+                    }
+                }
+            }
+            Ok(())
+        })
+        .expect("filling a new bytes object cannot fail")
+    }
+
+    #[classmethod]
+    fn from_rows(_cls: &Bound<'_, PyType>, rows: Vec<Vec<bool>>) -> PyResult<Self> {
+        let width = rows.first().map_or(0, |row| row.len());
+        if width == 0 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "BitMatrix rows must not be empty.",
+            ));
+        }
+        if let Some((y, row)) = rows.iter().enumerate().find(|(_, row)| row.len() != width) {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "BitMatrix rows must be rectangular: row {} has {} modules, expected {}.",
+                y,
+                row.len(),
+                width
+            )));
+        }
+        Ok(PyBitMatrix::from(rxing::common::BitMatrix::parse_bools(
+            &rows,
+        )))
+    }
+
+    #[classmethod]
+    fn from_bytes(_cls: &Bound<'_, PyType>, buf: &[u8], width: u32, height: u32) -> PyResult<Self> {
+        if width == 0 || height == 0 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "BitMatrix dimensions must be positive, got {}x{}.",
+                width, height
+            )));
+        }
+        if (width as usize) * (height as usize) != buf.len() {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "Buffer length does not match width * height.",
+            ));
+        }
+        let mut bm = rxing::common::BitMatrix::new(width, height).map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Failed to create BitMatrix: {:?}",
+                e
+            ))
+        })?;
+        for (i, &module) in buf.iter().enumerate() {
+            if module != 0 {
+                bm.set(i as u32 % width, i as u32 / width);
+            }
+        }
+        Ok(PyBitMatrix::from(bm))
+    }
+
+    // 按扩展名 (.png, .bmp, .pbm/.pgm/.ppm, .jpg, ...) 写成黑白图像: 每个模块 scale 像素,
+    // 四周另加 margin 个模块的白边. PNG 为 1 位灰度, 其它格式一般为 8 位灰度.
+    // 兼容旧的 save(path, "PNG") 写法: 第二个参数是字符串时作为 image_format
+    #[cfg(feature = "image")]
+    #[pyo3(signature = (path, scale=None, margin=4, *, image_format=None))]
+    fn save(
+        &self,
+        py: Python,
+        path: PathBuf,
+        scale: Option<&Bound<PyAny>>,
+        margin: u32,
+        mut image_format: Option<String>,
+    ) -> PyResult<()> {
+        let scale = match scale {
+            Some(value) if value.is_instance_of::<PyString>() => {
+                image_format.get_or_insert(value.extract()?);
+                1
+            }
+            Some(value) => value.extract()?,
+            None => 1,
+        };
+        let format = match &image_format {
+            Some(name) => image::ImageFormat::from_extension(name.to_lowercase()),
+            None => image::ImageFormat::from_path(&path).ok(),
+        };
+        let Some(format) = format else {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                match image_format {
+                    Some(name) => format!("Unknown image format {:?}", name),
+                    None => format!(
+                        "Cannot infer an image format from the extension of {}",
+                        path.display()
+                    ),
+                },
+            ));
+        };
+        if scale == 0 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "scale must be at least 1 pixel per module.",
+            ));
+        }
+        // GIF 编码器不接受灰度图像
+        let pixel_format = match format {
+            image::ImageFormat::Png => render::PixelFormat::OneBit,
+            image::ImageFormat::Gif => render::PixelFormat::Rgba,
+            _ => render::PixelFormat::Gray,
+        };
+        let options = render::RenderOptions {
+            module_size: scale,
+            border: margin,
+            ..Default::default()
+        };
+        py.allow_threads(|| {
+            let rendered = render::render_rgba(&self.inner_matrix, &options)?;
+            render::save_image(rendered, &path, format, pixel_format)
+        })
+    }
+
+    // 返回每个模块取反后的新矩阵 (用于深色背景上的浅色码), 原矩阵不变
+    fn invert(&self) -> Self {
+        // 逐个翻转而不用 flip_self, 以免行尾的填充位也被置位
+        let mut inverted = self.inner_matrix.clone();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                inverted.flip_coords(x, y);
+            }
+        }
+        PyBitMatrix::from(inverted)
+    }
+
+    // 尺寸与每个模块都相同时相等; 与其它类型比较时交给 Python 处理 (NotImplemented)
+    fn __eq__(&self, other: PyRef<Self>) -> bool {
+        self.width == other.width
+            && self.height == other.height
+            && self.differing_modules(&other).next().is_none()
+    }
+
+    // 按行优先顺序列出取值不同的模块坐标 (x, y); 尺寸不同时报错
+    fn diff(&self, other: PyRef<Self>) -> PyResult<Vec<(u32, u32)>> {
+        if (self.width, self.height) != (other.width, other.height) {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Cannot diff a {}x{} BitMatrix against a {}x{} one.",
+                self.width, self.height, other.width, other.height
+            )));
+        }
+        Ok(self.differing_modules(&other).collect())
+    }
     // 可以添加 to_pil_image (需要 Python 端处理) 或 save 方法
 }
 
+impl PyBitMatrix {
+    // 只比较 width x height 以内的模块, 行尾的填充位不参与比较
+    fn differing_modules<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = (u32, u32)> + 'a {
+        (0..self.height)
+            .flat_map(move |y| (0..self.width).map(move |x| (x, y)))
+            .filter(|&(x, y)| self.inner_matrix.get(x, y) != other.inner_matrix.get(x, y))
+    }
+}
+
 impl From<rxing::common::BitMatrix> for PyBitMatrix {
     fn from(bm: rxing::common::BitMatrix) -> Self {
         PyBitMatrix {
@@ -99,227 +1116,5268 @@ impl From<rxing::common::BitMatrix> for PyBitMatrix {
     }
 }
 
-fn py_dict_to_decode_hints(
-    _py: Python,
-    dict_opt: Option<&Bound<PyDict>>,
-) -> PyResult<RxingDecodeHints> {
-    let mut hints = RxingDecodeHints::default();
-    if let Some(dict) = dict_opt {
-        for (key_any, value_any) in dict.iter() {
-            let key_str: String = key_any.extract()?;
-            match key_str.to_uppercase().as_str() {
-                "TRY_HARDER" => hints.TryHarder = Some(value_any.extract()?),
-                "PURE_BARCODE" => hints.PureBarcode = Some(value_any.extract()?),
-                "POSSIBLE_FORMATS" => {
-                    let formats_list: &Bound<PyList> = value_any.downcast().map_err(|_| {
-                        PyErr::new::<pyo3::exceptions::PyTypeError, _>(
-                            "POSSIBLE_FORMATS value must be a list of strings",
-                        )
-                    })?;
-                    let mut possible_formats = HashSet::new();
-                    for format_any in formats_list.iter() {
-                        let format_str: String = format_any.extract()?;
-                        let format = BarcodeFormat::from(format_str.to_uppercase());
-                        possible_formats.insert(format);
-                    }
-                    if !possible_formats.is_empty() {
-                        hints.PossibleFormats = Some(possible_formats);
-                    }
-                }
-                "CHARACTER_SET" => hints.CharacterSet = Some(value_any.extract()?),
-                "ALSO_INVERTED" => hints.AlsoInverted = Some(value_any.extract()?),
-                // TODO: Implement more hint conversions as needed
-                _ => {
-                    eprintln!("Warning: Unknown decode hint: {}", key_str);
-                }
-            }
-        }
+// rxing 的名称表不认识 "RECTANGULAR_MICRO_QR_CODE" (只认 "rmqr" 等别名), 这里补上;
+// 无法识别的名称报错而不是静默忽略
+fn parse_barcode_format(name: &str) -> PyResult<BarcodeFormat> {
+    let format = match name.to_uppercase().as_str() {
+        "RECTANGULAR_MICRO_QR_CODE" => BarcodeFormat::RECTANGULAR_MICRO_QR_CODE,
+        upper => BarcodeFormat::from(upper),
+    };
+    if format == BarcodeFormat::UNSUPORTED_FORMAT {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Unsupported barcode format: {}",
+            name
+        )));
     }
-    Ok(hints)
+    Ok(format)
 }
 
-fn py_dict_to_encode_hints(
-    _py: Python,
-    dict_opt: Option<&Bound<PyDict>>,
-) -> PyResult<RxingEncodeHints> {
-    let mut hints = RxingEncodeHints::default();
-    if let Some(dict) = dict_opt {
-        for (key_any, value_any) in dict.iter() {
-            let key_str: String = key_any.extract()?;
-            match key_str.to_uppercase().as_str() {
-                "ERROR_CORRECTION" => hints.ErrorCorrection = Some(value_any.extract()?),
-                "CHARACTER_SET" => hints.CharacterSet = Some(value_any.extract()?),
-                "MARGIN" => hints.Margin = Some(value_any.extract()?),
-                "QR_VERSION" => hints.QrVersion = Some(value_any.extract()?),
-                _ => {
-                    eprintln!("Warning: Unknown encode hint: {}", key_str);
-                }
+// 提示值必须是正整数列表 (bool 虽然是 int 的子类, 也不接受)
+fn extract_positive_ints(value: &Bound<PyAny>, name: &str) -> PyResult<Vec<u32>> {
+    let list: &Bound<PyList> = value.downcast().map_err(|_| {
+        PyErr::new::<pyo3::exceptions::PyTypeError, _>(format!(
+            "{} value must be a list of positive integers",
+            name
+        ))
+    })?;
+    let mut values = Vec::with_capacity(list.len());
+    for item in list.iter() {
+        if item.is_instance_of::<PyBool>() || !item.is_instance_of::<PyInt>() {
+            return Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(format!(
+                "{} must only contain integers, got {}",
+                name,
+                item.get_type().name()?
+            )));
+        }
+        match item.extract::<u32>() {
+            Ok(v) if v > 0 => values.push(v),
+            _ => {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "{} must only contain positive integers, got {}",
+                    name, item
+                )))
             }
         }
     }
-    Ok(hints)
+    Ok(values)
 }
 
-#[pyfunction]
-fn decode_luma_pixels(
-    py: Python,
-    luma_data: &[u8],
-    width: u32,
-    height: u32,
-    hints_dict: Option<&Bound<PyDict>>,
-) -> PyResult<PyRXingResult> {
-    let pixels = luma_data.to_vec();
-    if (width * height) as usize != pixels.len() {
-        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
-            "Pixel data length does not match width * height.",
-        ));
+fn parse_possible_formats(names: &[String]) -> PyResult<Option<HashSet<BarcodeFormat>>> {
+    let mut possible_formats = HashSet::new();
+    for name in names {
+        possible_formats.insert(parse_barcode_format(name)?);
     }
+    Ok(Some(possible_formats).filter(|formats| !formats.is_empty()))
+}
 
-    let hints = py_dict_to_decode_hints(py, hints_dict)?;
-    let luma_source = Luma8LuminanceSource::new(pixels, width, height);
-    let binarizer = HybridBinarizer::new(luma_source);
-    let mut binary_bitmap = BinaryBitmap::new(binarizer);
-    let mut reader = MultiFormatReader::default();
+fn checked_character_set(name: String) -> PyResult<String> {
+    if CharacterSet::get_character_set_by_name(&name).is_none() {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Unknown CHARACTER_SET: {}",
+            name
+        )));
+    }
+    Ok(name)
+}
 
-    match reader.decode_with_hints(&mut binary_bitmap, &hints) {
-        Ok(result) => Ok(PyRXingResult::from(result)),
-        Err(e) => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-            "RXing decoding failed: {:?}",
-            e
-        ))),
+fn checked_code_set(code_set: String) -> PyResult<String> {
+    let upper = code_set.to_uppercase();
+    if !matches!(upper.as_str(), "A" | "B" | "C") {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "FORCE_CODE_SET must be \"A\", \"B\" or \"C\", got {:?}",
+            code_set
+        )));
     }
+    Ok(upper)
 }
 
-#[cfg(feature = "image")]
-#[pyfunction]
-fn decode_image_bytes(
-    py: Python,
-    image_file_bytes: &[u8],
-    hints_dict: Option<&Bound<PyDict>>,
-) -> PyResult<PyRXingResult> {
-    let hints = py_dict_to_decode_hints(py, hints_dict)?;
+// rxing 的编码提示都以字符串保存; 整数值可以写成 int 或十进制字符串
+fn extract_hint_int(value: &Bound<PyAny>, name: &str) -> PyResult<i64> {
+    if let Ok(text) = value.extract::<String>() {
+        return text.trim().parse().map_err(|_| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "{} must be an integer, got {:?}",
+                name, text
+            ))
+        });
+    }
+    if value.is_instance_of::<PyBool>() || !value.is_instance_of::<PyInt>() {
+        return Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(format!(
+            "{} must be an integer, got {}",
+            name,
+            value.get_type().name()?
+        )));
+    }
+    value.extract()
+}
 
-    match image::load_from_memory(image_file_bytes) {
-        Ok(dynamic_image) => {
-            let luma_source = BufferedImageLuminanceSource::new(dynamic_image);
+fn checked_margin(margin: i64) -> PyResult<String> {
+    if margin < 0 {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "MARGIN must be non-negative, got {}",
+            margin
+        )));
+    }
+    Ok(margin.to_string())
+}
+
+fn checked_qr_version(version: i64) -> PyResult<String> {
+    if !(1..=40).contains(&version) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "QR_VERSION must be between 1 and 40, got {}",
+            version
+        )));
+    }
+    Ok(version.to_string())
+}
+
+// QR 的纠错等级 L/M/Q/H, 或 Aztec (纠错码字百分比) / PDF417 (0-8 级) 的非负整数;
+// 是否适用于所编码的格式由 check_error_correction 在编码时检查
+fn checked_error_correction(value: &Bound<PyAny>) -> PyResult<String> {
+    let level = match value.extract::<String>() {
+        Ok(text) => text.trim().to_uppercase(),
+        Err(_) => extract_hint_int(value, "ERROR_CORRECTION")?.to_string(),
+    };
+    if matches!(level.as_str(), "L" | "M" | "Q" | "H") || level.parse::<u32>().is_ok() {
+        return Ok(level);
+    }
+    Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+        "ERROR_CORRECTION must be L, M, Q or H (QR_CODE) or a non-negative integer \
+         (AZTEC percentage, PDF_417 level), got {}",
+        value.repr()?
+    )))
+}
+
+// PDF417 的压缩模式: 名称 AUTO / TEXT / BYTE / NUMERIC 或 0-3; rxing 只接受数字字符串
+fn checked_pdf417_compaction(value: &Bound<PyAny>) -> PyResult<String> {
+    const MODES: [&str; 4] = ["AUTO", "TEXT", "BYTE", "NUMERIC"];
+    let mode = match value.extract::<String>() {
+        Ok(text) => {
+            let upper = text.trim().to_uppercase();
+            match MODES.iter().position(|mode| *mode == upper) {
+                Some(index) => index as i64,
+                None => extract_hint_int(value, "PDF417_COMPACTION").unwrap_or(-1),
+            }
+        }
+        Err(_) => extract_hint_int(value, "PDF417_COMPACTION")?,
+    };
+    if !(0..MODES.len() as i64).contains(&mode) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "PDF417_COMPACTION must be AUTO, TEXT, BYTE, NUMERIC or 0-3, got {}",
+            value.repr()?
+        )));
+    }
+    Ok(mode.to_string())
+}
+
+fn pdf417_compaction_name(mode: &str) -> Option<&'static str> {
+    let index: usize = mode.parse().ok()?;
+    ["AUTO", "TEXT", "BYTE", "NUMERIC"].get(index).copied()
+}
+
+// 整数序列, 如 MIN_SIZE 的 (宽, 高) 或 PDF417_DIMENSIONS 的四个值
+fn extract_hint_ints<const N: usize>(
+    value: &Bound<PyAny>,
+    name: &str,
+    fields: &str,
+) -> PyResult<[i64; N]> {
+    let type_error = || {
+        PyErr::new::<pyo3::exceptions::PyTypeError, _>(format!(
+            "{} must be a sequence of {} integers ({})",
+            name, N, fields
+        ))
+    };
+    if value.is_instance_of::<PyString>() {
+        return Err(type_error());
+    }
+    let items: Vec<Bound<PyAny>> = value.extract().map_err(|_| type_error())?;
+    if items.len() != N {
+        return Err(type_error());
+    }
+    let mut values = [0; N];
+    for (slot, item) in values.iter_mut().zip(&items) {
+        if item.is_instance_of::<PyBool>() || !item.is_instance_of::<PyInt>() {
+            return Err(type_error());
+        }
+        *slot = item.extract()?;
+    }
+    Ok(values)
+}
+
+// (min_cols, max_cols, min_rows, max_rows); PDF417 有 1-30 个数据列和 3-90 行
+fn extract_pdf417_dimensions(value: &Bound<PyAny>) -> PyResult<Dimensions> {
+    let fields = "min_cols, max_cols, min_rows, max_rows";
+    let [min_cols, max_cols, min_rows, max_rows] =
+        extract_hint_ints::<4>(value, "PDF417_DIMENSIONS", fields)?;
+    let cols_ok = 1 <= min_cols && min_cols <= max_cols && max_cols <= 30;
+    let rows_ok = 3 <= min_rows && min_rows <= max_rows && max_rows <= 90;
+    if !(cols_ok && rows_ok) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "PDF417_DIMENSIONS must have 1 <= min_cols <= max_cols <= 30 and \
+             3 <= min_rows <= max_rows <= 90, got ({}, {}, {}, {})",
+            min_cols, max_cols, min_rows, max_rows
+        )));
+    }
+    Ok(Dimensions::new(
+        min_cols as usize,
+        max_cols as usize,
+        min_rows as usize,
+        max_rows as usize,
+    ))
+}
+
+// 负数 (-1 到 -4) 为紧凑 Aztec 的层数, 正数 (1 到 32) 为完整 Aztec 的层数, 0 为最少层数
+fn checked_aztec_layers(layers: i64) -> PyResult<i32> {
+    if !(-4..=32).contains(&layers) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "AZTEC_LAYERS must be between -4 and 32, got {}",
+            layers
+        )));
+    }
+    Ok(layers as i32)
+}
+
+fn checked_data_matrix_shape(shape: String) -> PyResult<SymbolShapeHint> {
+    let upper = shape.trim().to_uppercase();
+    match upper.strip_prefix("FORCE_").unwrap_or(&upper) {
+        "NONE" => Ok(SymbolShapeHint::FORCE_NONE),
+        "SQUARE" => Ok(SymbolShapeHint::FORCE_SQUARE),
+        "RECTANGLE" => Ok(SymbolShapeHint::FORCE_RECTANGLE),
+        _ => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "DATA_MATRIX_SHAPE must be \"NONE\", \"SQUARE\" or \"RECTANGLE\", got {:?}",
+            shape
+        ))),
+    }
+}
+
+fn data_matrix_shape_name(shape: SymbolShapeHint) -> &'static str {
+    match shape {
+        SymbolShapeHint::FORCE_NONE => "NONE",
+        SymbolShapeHint::FORCE_SQUARE => "SQUARE",
+        SymbolShapeHint::FORCE_RECTANGLE => "RECTANGLE",
+    }
+}
+
+// Data Matrix 符号的 (宽, 高), 以模块计
+fn extract_symbol_size(value: &Bound<PyAny>, name: &str) -> PyResult<Dimension> {
+    let [width, height] = extract_hint_ints::<2>(value, name, "width, height")?;
+    if width <= 0 || height <= 0 {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "{} must be positive, got ({}, {})",
+            name, width, height
+        )));
+    }
+    Ok(Dimension::new(width as usize, height as usize))
+}
+
+// 两种方式构造的编码提示最后都经过这里: rxing 对互斥的提示只按其中一个编码, 不会报错
+#[allow(deprecated)]
+fn checked_encode_hints(hints: RxingEncodeHints) -> PyResult<RxingEncodeHints> {
+    let conflict = if hints.Code128Compact == Some(true) && hints.ForceCodeSet.is_some() {
+        Some("CODE128_COMPACT and FORCE_CODE_SET")
+    } else if hints.DataMatrixCompact == Some(true) && hints.ForceC40 == Some(true) {
+        Some("DATA_MATRIX_COMPACT and FORCE_C40")
+    } else {
+        None
+    };
+    if let Some(conflict) = conflict {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "{} are mutually exclusive",
+            conflict
+        )));
+    }
+    if let (Some(min), Some(max)) = (&hints.MinSize, &hints.MaxSize) {
+        if min.getWidth() > max.getWidth() || min.getHeight() > max.getHeight() {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "MIN_SIZE ({}, {}) is larger than MAX_SIZE ({}, {})",
+                min.getWidth(),
+                min.getHeight(),
+                max.getWidth(),
+                max.getHeight()
+            )));
+        }
+    }
+    Ok(hints)
+}
+
+fn checked_ean_extensions(extensions: Vec<u32>) -> PyResult<Vec<u32>> {
+    if let Some(ext) = extensions.iter().find(|ext| !matches!(ext, 2 | 5)) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "ALLOWED_EAN_EXTENSIONS values must be 2 or 5, got {}",
+            ext
+        )));
+    }
+    Ok(extensions)
+}
+
+// 解码提示: rxing 的 DecodeHints 加上 rxing 没有对应字段、由本模块在解码结果上自行处理的提示.
+// 通过 Deref 直接读写 rxing 的字段, 也可以直接传给 rxing 的 reader
+#[derive(Clone, Default)]
+struct DecodeHints {
+    rxing: RxingDecodeHints,
+    assume_itf_check_digit: Option<bool>,
+    code_39_extended_mode: Option<bool>,
+    normalize_upc_e_to_upc_a: Option<bool>,
+    threshold_sweep: Option<bool>,
+}
+
+impl std::ops::Deref for DecodeHints {
+    type Target = RxingDecodeHints;
+
+    fn deref(&self) -> &RxingDecodeHints {
+        &self.rxing
+    }
+}
+
+impl std::ops::DerefMut for DecodeHints {
+    fn deref_mut(&mut self) -> &mut RxingDecodeHints {
+        &mut self.rxing
+    }
+}
+
+// NEED_RESULT_POINT_CALLBACK: 解码过程中每找到一个候选点 (QR 的定位图形, 一维码的起止位置等)
+// 就以 (x, y) 调用一次. 解码在释放了 GIL 的线程上运行, 回调时重新获取 GIL;
+// 回调抛出的异常作为 unraisable 报告, 不会中断解码
+fn point_callback(callback: Py<PyAny>) -> PyResult<rxing::PointCallback> {
+    Python::with_gil(|py| {
+        let bound = callback.bind(py);
+        if !bound.is_callable() {
+            return Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(format!(
+                "NEED_RESULT_POINT_CALLBACK must be callable, got {}",
+                bound.get_type().name()?
+            )));
+        }
+        Ok(())
+    })?;
+    Ok(Arc::new(move |point: rxing::Point| {
+        Python::with_gil(|py| {
+            if let Err(e) = callback.call1(py, (point.x, point.y)) {
+                e.write_unraisable(py, Some(callback.bind(py)));
+            }
+        })
+    }))
+}
+
+fn py_dict_to_decode_hints(dict: &Bound<PyDict>) -> PyResult<DecodeHints> {
+    let mut hints = DecodeHints::default();
+    for (key_any, value_any) in dict.iter() {
+        let key_str: String = key_any.extract()?;
+        match key_str.to_uppercase().as_str() {
+            "TRY_HARDER" => hints.TryHarder = Some(value_any.extract()?),
+            "PURE_BARCODE" => hints.PureBarcode = Some(value_any.extract()?),
+            "POSSIBLE_FORMATS" => {
+                let formats_list: &Bound<PyList> = value_any.downcast().map_err(|_| {
+                    PyErr::new::<pyo3::exceptions::PyTypeError, _>(
+                        "POSSIBLE_FORMATS value must be a list of strings",
+                    )
+                })?;
+                let names = formats_list
+                    .iter()
+                    .map(|format_any| format_any.extract())
+                    .collect::<PyResult<Vec<String>>>()?;
+                hints.PossibleFormats = parse_possible_formats(&names)?;
+            }
+            "CHARACTER_SET" => {
+                hints.CharacterSet = Some(checked_character_set(value_any.extract()?)?)
+            }
+            "ALSO_INVERTED" => hints.AlsoInverted = Some(value_any.extract()?),
+            "RETURN_CODABAR_START_END" => hints.ReturnCodabarStartEnd = Some(value_any.extract()?),
+            "ASSUME_GS1" => hints.AssumeGs1 = Some(value_any.extract()?),
+            "TELEPEN_AS_NUMERIC" => hints.TelepenAsNumeric = Some(value_any.extract()?),
+            "NEED_RESULT_POINT_CALLBACK" => {
+                hints.NeedResultPointCallback = Some(point_callback(value_any.clone().unbind())?)
+            }
+            "ASSUME_CODE_39_CHECK_DIGIT" => {
+                hints.AssumeCode39CheckDigit = Some(value_any.extract()?)
+            }
+            "ALLOWED_LENGTHS" => {
+                hints.AllowedLengths = Some(extract_positive_ints(&value_any, &key_str)?)
+            }
+            "ALLOWED_EAN_EXTENSIONS" => {
+                let extensions = extract_positive_ints(&value_any, &key_str)?;
+                hints.AllowedEanExtensions = Some(checked_ean_extensions(extensions)?);
+            }
+            "ASSUME_ITF_CHECK_DIGIT" => hints.assume_itf_check_digit = Some(value_any.extract()?),
+            "CODE_39_EXTENDED_MODE" => hints.code_39_extended_mode = Some(value_any.extract()?),
+            "NORMALIZE_UPC_E_TO_UPC_A" => {
+                hints.normalize_upc_e_to_upc_a = Some(value_any.extract()?)
+            }
+            "THRESHOLD_SWEEP" => hints.threshold_sweep = Some(value_any.extract()?),
+            // rxing 没有 TRY_HARDER_WITHOUT_ROTATION; 需要时用 decode 的 rotations=[0]
+            _ => {
+                eprintln!("Warning: Unknown decode hint: {}", key_str);
+            }
+        }
+    }
+    Ok(hints)
+}
+
+fn py_dict_to_encode_hints(dict: &Bound<PyDict>) -> PyResult<RxingEncodeHints> {
+    let mut hints = RxingEncodeHints::default();
+    for (key_any, value_any) in dict.iter() {
+        let key_str: String = key_any.extract()?;
+        match key_str.to_uppercase().as_str() {
+            "ERROR_CORRECTION" => {
+                hints.ErrorCorrection = Some(checked_error_correction(&value_any)?)
+            }
+            "CHARACTER_SET" => {
+                hints.CharacterSet = Some(checked_character_set(value_any.extract()?)?)
+            }
+            "MARGIN" => {
+                hints.Margin = Some(checked_margin(extract_hint_int(&value_any, "MARGIN")?)?)
+            }
+            "QR_VERSION" => {
+                hints.QrVersion = Some(checked_qr_version(extract_hint_int(
+                    &value_any,
+                    "QR_VERSION",
+                )?)?)
+            }
+            "FORCE_CODE_SET" => hints.ForceCodeSet = Some(checked_code_set(value_any.extract()?)?),
+            "GS1_FORMAT" => hints.Gs1Format = Some(value_any.extract()?),
+            "PDF417_COMPACT" => {
+                hints.Pdf417Compact = Some(value_any.extract::<bool>()?.to_string())
+            }
+            "PDF417_COMPACTION" => {
+                hints.Pdf417Compaction = Some(checked_pdf417_compaction(&value_any)?)
+            }
+            "PDF417_DIMENSIONS" => {
+                hints.Pdf417Dimensions = Some(extract_pdf417_dimensions(&value_any)?)
+            }
+            "PDF417_AUTO_ECI" => {
+                hints.Pdf417AutoEci = Some(value_any.extract::<bool>()?.to_string())
+            }
+            "AZTEC_LAYERS" => {
+                hints.AztecLayers = Some(checked_aztec_layers(extract_hint_int(
+                    &value_any,
+                    "AZTEC_LAYERS",
+                )?)?)
+            }
+            "DATA_MATRIX_SHAPE" => {
+                hints.DataMatrixShape = Some(checked_data_matrix_shape(value_any.extract()?)?)
+            }
+            "DATA_MATRIX_COMPACT" => hints.DataMatrixCompact = Some(value_any.extract()?),
+            "MIN_SIZE" => {
+                #[allow(deprecated)]
+                {
+                    hints.MinSize = Some(extract_symbol_size(&value_any, "MIN_SIZE")?)
+                }
+            }
+            "MAX_SIZE" => {
+                #[allow(deprecated)]
+                {
+                    hints.MaxSize = Some(extract_symbol_size(&value_any, "MAX_SIZE")?)
+                }
+            }
+            "CODE128_COMPACT" => hints.Code128Compact = Some(value_any.extract()?),
+            "FORCE_C40" => hints.ForceC40 = Some(value_any.extract()?),
+            "TELEPEN_AS_NUMERIC" => hints.TelepenAsNumeric = Some(value_any.extract()?),
+            _ => {
+                eprintln!("Warning: Unknown encode hint: {}", key_str);
+            }
+        }
+    }
+    checked_encode_hints(hints)
+}
+
+// 预先解析好的提示, 可代替 dict 传给各解码/编码函数, 多次调用时省去重复的转换与校验
+#[pyclass(name = "DecodeHints")]
+#[derive(Clone)]
+struct PyDecodeHints {
+    hints: DecodeHints,
+    // need_result_point_callback 的原始对象, 供属性与 repr 使用
+    point_callback: Option<Arc<Py<PyAny>>>,
+}
+
+#[pymethods]
+impl PyDecodeHints {
+    #[new]
+    #[pyo3(signature = (*, try_harder=None, pure_barcode=None, possible_formats=None, character_set=None, also_inverted=None, return_codabar_start_end=None, allowed_lengths=None, allowed_ean_extensions=None, assume_itf_check_digit=None, assume_code_39_check_digit=None, code_39_extended_mode=None, normalize_upc_e_to_upc_a=None, threshold_sweep=None, assume_gs1=None, telepen_as_numeric=None, need_result_point_callback=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        py: Python,
+        try_harder: Option<bool>,
+        pure_barcode: Option<bool>,
+        possible_formats: Option<Vec<String>>,
+        character_set: Option<String>,
+        also_inverted: Option<bool>,
+        return_codabar_start_end: Option<bool>,
+        allowed_lengths: Option<&Bound<PyAny>>,
+        allowed_ean_extensions: Option<&Bound<PyAny>>,
+        assume_itf_check_digit: Option<bool>,
+        assume_code_39_check_digit: Option<bool>,
+        code_39_extended_mode: Option<bool>,
+        normalize_upc_e_to_upc_a: Option<bool>,
+        threshold_sweep: Option<bool>,
+        assume_gs1: Option<bool>,
+        telepen_as_numeric: Option<bool>,
+        need_result_point_callback: Option<Py<PyAny>>,
+    ) -> PyResult<Self> {
+        let point_callback = need_result_point_callback.map(Arc::new);
+        let rxing = RxingDecodeHints {
+            TryHarder: try_harder,
+            PureBarcode: pure_barcode,
+            PossibleFormats: match possible_formats {
+                Some(names) => parse_possible_formats(&names)?,
+                None => None,
+            },
+            CharacterSet: character_set.map(checked_character_set).transpose()?,
+            AlsoInverted: also_inverted,
+            ReturnCodabarStartEnd: return_codabar_start_end,
+            AssumeCode39CheckDigit: assume_code_39_check_digit,
+            AllowedLengths: allowed_lengths
+                .map(|value| extract_positive_ints(value, "allowed_lengths"))
+                .transpose()?,
+            AllowedEanExtensions: match allowed_ean_extensions {
+                Some(value) => Some(checked_ean_extensions(extract_positive_ints(
+                    value,
+                    "allowed_ean_extensions",
+                )?)?),
+                None => None,
+            },
+            AssumeGs1: assume_gs1,
+            TelepenAsNumeric: telepen_as_numeric,
+            NeedResultPointCallback: point_callback
+                .as_ref()
+                .map(|callback| self::point_callback(callback.clone_ref(py)))
+                .transpose()?,
+            ..Default::default()
+        };
+        Ok(PyDecodeHints {
+            hints: DecodeHints {
+                rxing,
+                assume_itf_check_digit,
+                code_39_extended_mode,
+                normalize_upc_e_to_upc_a,
+                threshold_sweep,
+            },
+            point_callback,
+        })
+    }
+
+    // decode_pure() 使用: 返回设置了 pure_barcode=True 的副本
+    #[pyo3(name = "_with_pure_barcode")]
+    fn with_pure_barcode(&self) -> Self {
+        let mut copy = self.clone();
+        copy.hints.PureBarcode = Some(true);
+        copy
+    }
+
+    // 只读属性, 未设置的提示为 None
+    #[getter]
+    fn try_harder(&self) -> Option<bool> {
+        self.hints.TryHarder
+    }
+
+    #[getter]
+    fn pure_barcode(&self) -> Option<bool> {
+        self.hints.PureBarcode
+    }
+
+    // 按 rxing 的格式名称 (与 BarcodeFormat 的值相同) 排序
+    #[getter]
+    fn possible_formats(&self) -> Option<Vec<String>> {
+        self.hints.PossibleFormats.as_ref().map(|formats| {
+            let mut names: Vec<String> = formats.iter().map(|f| f.to_string()).collect();
+            names.sort();
+            names
+        })
+    }
+
+    #[getter]
+    fn character_set(&self) -> Option<String> {
+        self.hints.CharacterSet.clone()
+    }
+
+    #[getter]
+    fn also_inverted(&self) -> Option<bool> {
+        self.hints.AlsoInverted
+    }
+
+    #[getter]
+    fn return_codabar_start_end(&self) -> Option<bool> {
+        self.hints.ReturnCodabarStartEnd
+    }
+
+    #[getter]
+    fn allowed_lengths(&self) -> Option<Vec<u32>> {
+        self.hints.AllowedLengths.clone()
+    }
+
+    #[getter]
+    fn allowed_ean_extensions(&self) -> Option<Vec<u32>> {
+        self.hints.AllowedEanExtensions.clone()
+    }
+
+    #[getter]
+    fn assume_code_39_check_digit(&self) -> Option<bool> {
+        self.hints.AssumeCode39CheckDigit
+    }
+
+    #[getter]
+    fn assume_gs1(&self) -> Option<bool> {
+        self.hints.AssumeGs1
+    }
+
+    #[getter]
+    fn telepen_as_numeric(&self) -> Option<bool> {
+        self.hints.TelepenAsNumeric
+    }
+
+    #[getter]
+    fn need_result_point_callback(&self, py: Python) -> Option<Py<PyAny>> {
+        self.point_callback
+            .as_ref()
+            .map(|callback| callback.clone_ref(py))
+    }
+
+    #[getter]
+    fn assume_itf_check_digit(&self) -> Option<bool> {
+        self.hints.assume_itf_check_digit
+    }
+
+    #[getter]
+    fn code_39_extended_mode(&self) -> Option<bool> {
+        self.hints.code_39_extended_mode
+    }
+
+    #[getter]
+    fn normalize_upc_e_to_upc_a(&self) -> Option<bool> {
+        self.hints.normalize_upc_e_to_upc_a
+    }
+
+    #[getter]
+    fn threshold_sweep(&self) -> Option<bool> {
+        self.hints.threshold_sweep
+    }
+
+    fn __repr__(&self) -> String {
+        let hints = &self.hints;
+        let mut fields = Vec::new();
+        if let Some(v) = hints.TryHarder {
+            fields.push(format!("try_harder={}", py_bool(v)));
+        }
+        if let Some(v) = hints.PureBarcode {
+            fields.push(format!("pure_barcode={}", py_bool(v)));
+        }
+        if let Some(formats) = &hints.PossibleFormats {
+            let mut names: Vec<String> = formats.iter().map(|f| format!("'{}'", f)).collect();
+            names.sort();
+            fields.push(format!("possible_formats=[{}]", names.join(", ")));
+        }
+        if let Some(v) = &hints.CharacterSet {
+            fields.push(format!("character_set='{}'", v));
+        }
+        if let Some(v) = hints.AlsoInverted {
+            fields.push(format!("also_inverted={}", py_bool(v)));
+        }
+        if let Some(v) = hints.ReturnCodabarStartEnd {
+            fields.push(format!("return_codabar_start_end={}", py_bool(v)));
+        }
+        if let Some(v) = &hints.AllowedLengths {
+            fields.push(format!("allowed_lengths={:?}", v));
+        }
+        if let Some(v) = &hints.AllowedEanExtensions {
+            fields.push(format!("allowed_ean_extensions={:?}", v));
+        }
+        if let Some(v) = hints.assume_itf_check_digit {
+            fields.push(format!("assume_itf_check_digit={}", py_bool(v)));
+        }
+        if let Some(v) = hints.AssumeCode39CheckDigit {
+            fields.push(format!("assume_code_39_check_digit={}", py_bool(v)));
+        }
+        if let Some(v) = hints.code_39_extended_mode {
+            fields.push(format!("code_39_extended_mode={}", py_bool(v)));
+        }
+        if let Some(v) = hints.normalize_upc_e_to_upc_a {
+            fields.push(format!("normalize_upc_e_to_upc_a={}", py_bool(v)));
+        }
+        if let Some(v) = hints.threshold_sweep {
+            fields.push(format!("threshold_sweep={}", py_bool(v)));
+        }
+        if let Some(v) = hints.AssumeGs1 {
+            fields.push(format!("assume_gs1={}", py_bool(v)));
+        }
+        if let Some(v) = hints.TelepenAsNumeric {
+            fields.push(format!("telepen_as_numeric={}", py_bool(v)));
+        }
+        if let Some(callback) = &self.point_callback {
+            let callback = Python::with_gil(|py| {
+                callback
+                    .bind(py)
+                    .repr()
+                    .map(|repr| repr.to_string())
+                    .unwrap_or_else(|_| "<callable>".to_string())
+            });
+            fields.push(format!("need_result_point_callback={}", callback));
+        }
+        format!("DecodeHints({})", fields.join(", "))
+    }
+}
+
+#[pyclass(name = "EncodeHints")]
+#[derive(Clone)]
+struct PyEncodeHints {
+    hints: RxingEncodeHints,
+}
+
+#[pymethods]
+impl PyEncodeHints {
+    #[new]
+    #[pyo3(signature = (*, error_correction=None, character_set=None, margin=None, qr_version=None, force_code_set=None, gs1_format=None, pdf417_compact=None, pdf417_compaction=None, pdf417_dimensions=None, pdf417_auto_eci=None, aztec_layers=None, data_matrix_shape=None, data_matrix_compact=None, min_size=None, max_size=None, code128_compact=None, force_c40=None, telepen_as_numeric=None))]
+    #[allow(clippy::too_many_arguments, deprecated)]
+    fn new(
+        error_correction: Option<&Bound<PyAny>>,
+        character_set: Option<String>,
+        margin: Option<i64>,
+        qr_version: Option<i64>,
+        force_code_set: Option<String>,
+        gs1_format: Option<bool>,
+        pdf417_compact: Option<bool>,
+        pdf417_compaction: Option<&Bound<PyAny>>,
+        pdf417_dimensions: Option<&Bound<PyAny>>,
+        pdf417_auto_eci: Option<bool>,
+        aztec_layers: Option<i64>,
+        data_matrix_shape: Option<String>,
+        data_matrix_compact: Option<bool>,
+        min_size: Option<&Bound<PyAny>>,
+        max_size: Option<&Bound<PyAny>>,
+        code128_compact: Option<bool>,
+        force_c40: Option<bool>,
+        telepen_as_numeric: Option<bool>,
+    ) -> PyResult<Self> {
+        let hints = RxingEncodeHints {
+            ErrorCorrection: error_correction.map(checked_error_correction).transpose()?,
+            CharacterSet: character_set.map(checked_character_set).transpose()?,
+            Margin: margin.map(checked_margin).transpose()?,
+            QrVersion: qr_version.map(checked_qr_version).transpose()?,
+            ForceCodeSet: force_code_set.map(checked_code_set).transpose()?,
+            Gs1Format: gs1_format,
+            Pdf417Compact: pdf417_compact.map(|v| v.to_string()),
+            Pdf417Compaction: pdf417_compaction
+                .map(checked_pdf417_compaction)
+                .transpose()?,
+            Pdf417Dimensions: pdf417_dimensions
+                .map(extract_pdf417_dimensions)
+                .transpose()?,
+            Pdf417AutoEci: pdf417_auto_eci.map(|v| v.to_string()),
+            AztecLayers: aztec_layers.map(checked_aztec_layers).transpose()?,
+            DataMatrixShape: data_matrix_shape
+                .map(checked_data_matrix_shape)
+                .transpose()?,
+            DataMatrixCompact: data_matrix_compact,
+            MinSize: min_size
+                .map(|v| extract_symbol_size(v, "MIN_SIZE"))
+                .transpose()?,
+            MaxSize: max_size
+                .map(|v| extract_symbol_size(v, "MAX_SIZE"))
+                .transpose()?,
+            Code128Compact: code128_compact,
+            ForceC40: force_c40,
+            TelepenAsNumeric: telepen_as_numeric,
+            ..Default::default()
+        };
+        Ok(PyEncodeHints {
+            hints: checked_encode_hints(hints)?,
+        })
+    }
+
+    // 只读属性, 未设置的提示为 None
+    #[getter]
+    fn error_correction(&self) -> Option<String> {
+        self.hints.ErrorCorrection.clone()
+    }
+
+    #[getter]
+    fn character_set(&self) -> Option<String> {
+        self.hints.CharacterSet.clone()
+    }
+
+    #[getter]
+    fn margin(&self) -> Option<u32> {
+        self.hints.Margin.as_ref().and_then(|v| v.parse().ok())
+    }
+
+    #[getter]
+    fn qr_version(&self) -> Option<u32> {
+        self.hints.QrVersion.as_ref().and_then(|v| v.parse().ok())
+    }
+
+    #[getter]
+    fn force_code_set(&self) -> Option<String> {
+        self.hints.ForceCodeSet.clone()
+    }
+
+    #[getter]
+    fn gs1_format(&self) -> Option<bool> {
+        self.hints.Gs1Format
+    }
+
+    #[getter]
+    fn pdf417_compact(&self) -> Option<bool> {
+        self.hints
+            .Pdf417Compact
+            .as_ref()
+            .and_then(|v| v.parse().ok())
+    }
+
+    #[getter]
+    fn pdf417_compaction(&self) -> Option<&'static str> {
+        self.hints
+            .Pdf417Compaction
+            .as_deref()
+            .and_then(pdf417_compaction_name)
+    }
+
+    #[getter]
+    fn pdf417_dimensions(&self) -> Option<(usize, usize, usize, usize)> {
+        self.hints.Pdf417Dimensions.as_ref().map(|d| {
+            (
+                d.getMinCols(),
+                d.getMaxCols(),
+                d.getMinRows(),
+                d.getMaxRows(),
+            )
+        })
+    }
+
+    #[getter]
+    fn pdf417_auto_eci(&self) -> Option<bool> {
+        self.hints
+            .Pdf417AutoEci
+            .as_ref()
+            .and_then(|v| v.parse().ok())
+    }
+
+    #[getter]
+    fn aztec_layers(&self) -> Option<i32> {
+        self.hints.AztecLayers
+    }
+
+    #[getter]
+    fn data_matrix_shape(&self) -> Option<&'static str> {
+        self.hints.DataMatrixShape.map(data_matrix_shape_name)
+    }
+
+    #[getter]
+    fn data_matrix_compact(&self) -> Option<bool> {
+        self.hints.DataMatrixCompact
+    }
+
+    #[getter]
+    #[allow(deprecated)]
+    fn min_size(&self) -> Option<(usize, usize)> {
+        self.hints.MinSize.map(|d| (d.getWidth(), d.getHeight()))
+    }
+
+    #[getter]
+    #[allow(deprecated)]
+    fn max_size(&self) -> Option<(usize, usize)> {
+        self.hints.MaxSize.map(|d| (d.getWidth(), d.getHeight()))
+    }
+
+    #[getter]
+    fn code128_compact(&self) -> Option<bool> {
+        self.hints.Code128Compact
+    }
+
+    #[getter]
+    fn force_c40(&self) -> Option<bool> {
+        self.hints.ForceC40
+    }
+
+    #[getter]
+    fn telepen_as_numeric(&self) -> Option<bool> {
+        self.hints.TelepenAsNumeric
+    }
+
+    fn __repr__(&self) -> String {
+        let hints = &self.hints;
+        let mut fields = Vec::new();
+        if let Some(v) = &hints.ErrorCorrection {
+            fields.push(format!("error_correction='{}'", v));
+        }
+        if let Some(v) = &hints.CharacterSet {
+            fields.push(format!("character_set='{}'", v));
+        }
+        if let Some(v) = &hints.Margin {
+            fields.push(format!("margin={}", v));
+        }
+        if let Some(v) = &hints.QrVersion {
+            fields.push(format!("qr_version={}", v));
+        }
+        if let Some(v) = &hints.ForceCodeSet {
+            fields.push(format!("force_code_set='{}'", v));
+        }
+        if let Some(v) = hints.Gs1Format {
+            fields.push(format!("gs1_format={}", py_bool(v)));
+        }
+        if let Some(v) = self.pdf417_compact() {
+            fields.push(format!("pdf417_compact={}", py_bool(v)));
+        }
+        if let Some(v) = self.pdf417_compaction() {
+            fields.push(format!("pdf417_compaction='{}'", v));
+        }
+        if let Some(v) = self.pdf417_dimensions() {
+            fields.push(format!("pdf417_dimensions={:?}", v));
+        }
+        if let Some(v) = self.pdf417_auto_eci() {
+            fields.push(format!("pdf417_auto_eci={}", py_bool(v)));
+        }
+        if let Some(v) = hints.AztecLayers {
+            fields.push(format!("aztec_layers={:?}", v));
+        }
+        if let Some(v) = self.data_matrix_shape() {
+            fields.push(format!("data_matrix_shape='{}'", v));
+        }
+        if let Some(v) = hints.DataMatrixCompact {
+            fields.push(format!("data_matrix_compact={}", py_bool(v)));
+        }
+        if let Some(v) = self.min_size() {
+            fields.push(format!("min_size={:?}", v));
+        }
+        if let Some(v) = self.max_size() {
+            fields.push(format!("max_size={:?}", v));
+        }
+        if let Some(v) = hints.Code128Compact {
+            fields.push(format!("code128_compact={}", py_bool(v)));
+        }
+        if let Some(v) = hints.ForceC40 {
+            fields.push(format!("force_c40={}", py_bool(v)));
+        }
+        if let Some(v) = hints.TelepenAsNumeric {
+            fields.push(format!("telepen_as_numeric={}", py_bool(v)));
+        }
+        format!("EncodeHints({})", fields.join(", "))
+    }
+}
+
+fn py_bool(value: bool) -> &'static str {
+    if value {
+        "True"
+    } else {
+        "False"
+    }
+}
+
+// hints 可以是 dict 或预先构造的 DecodeHints / EncodeHints 对象
+fn extract_decode_hints(hints: Option<&Bound<PyAny>>) -> PyResult<DecodeHints> {
+    let Some(hints) = hints else {
+        return Ok(DecodeHints::default());
+    };
+    if let Ok(parsed) = hints.downcast::<PyDecodeHints>() {
+        return Ok(parsed.borrow().hints.clone());
+    }
+    match hints.downcast::<PyDict>() {
+        Ok(dict) => py_dict_to_decode_hints(dict),
+        Err(_) => Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(format!(
+            "hints must be a dict or DecodeHints, got {}",
+            hints.get_type().name()?
+        ))),
+    }
+}
+
+fn extract_encode_hints(hints: Option<&Bound<PyAny>>) -> PyResult<RxingEncodeHints> {
+    let Some(hints) = hints else {
+        return Ok(RxingEncodeHints::default());
+    };
+    if let Ok(parsed) = hints.downcast::<PyEncodeHints>() {
+        return Ok(parsed.borrow().hints.clone());
+    }
+    match hints.downcast::<PyDict>() {
+        Ok(dict) => py_dict_to_encode_hints(dict),
+        Err(_) => Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(format!(
+            "hints must be a dict or EncodeHints, got {}",
+            hints.get_type().name()?
+        ))),
+    }
+}
+
+// 二值化算法. rxing 的 HybridBinarizer 块大小 (8px) 与最小对比度 (24) 都是常量, 无法调整;
+// GlobalHistogramBinarizer 用整幅图像的直方图取单一阈值, 能保留对比度很低的条码,
+// 但在光照不均的图像上效果较差; Threshold 为本模块实现的阈值算法, 见 ThresholdMethod
+#[derive(Clone, Copy, Default, PartialEq)]
+enum BinarizerKind {
+    #[default]
+    Hybrid,
+    Global,
+    Threshold(bit_binarizer::ThresholdMethod),
+}
+
+const DEFAULT_FIXED_THRESHOLD: u8 = 128;
+
+impl BinarizerKind {
+    // 固定阈值写作 "fixed_threshold:N" (N 为 1..255), 省略时为 128;
+    // 自适应阈值的窗口写作 "adaptive:W" (W 为像素数, 至少 3), 省略时按图像大小取
+    fn parse(binarizer: &str) -> PyResult<Self> {
+        let invalid = || {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Unknown binarizer {:?}; expected \"hybrid\", \"global_histogram\", \
+                 \"fixed_threshold\" (optionally \"fixed_threshold:N\" with N from 1 to 255), \
+                 \"otsu\" or \"adaptive\" (optionally \"adaptive:W\" with W at least 3).",
+                binarizer
+            ))
+        };
+        let method = match binarizer.split_once(':') {
+            None => match binarizer {
+                "hybrid" => return Ok(BinarizerKind::Hybrid),
+                "global" | "global_histogram" => return Ok(BinarizerKind::Global),
+                "fixed_threshold" => bit_binarizer::ThresholdMethod::Fixed(DEFAULT_FIXED_THRESHOLD),
+                "otsu" => bit_binarizer::ThresholdMethod::Otsu,
+                "adaptive" => bit_binarizer::ThresholdMethod::Adaptive(None),
+                _ => return Err(invalid()),
+            },
+            Some(("fixed_threshold", cutoff)) => match cutoff.parse::<u8>() {
+                Ok(cutoff) if cutoff > 0 => bit_binarizer::ThresholdMethod::Fixed(cutoff),
+                _ => return Err(invalid()),
+            },
+            Some(("adaptive", window)) => match window.parse::<u32>() {
+                Ok(window) if window >= 3 => bit_binarizer::ThresholdMethod::Adaptive(Some(window)),
+                _ => return Err(invalid()),
+            },
+            Some(_) => return Err(invalid()),
+        };
+        Ok(BinarizerKind::Threshold(method))
+    }
+
+    fn name(self) -> String {
+        match self {
+            BinarizerKind::Hybrid => "hybrid".to_string(),
+            BinarizerKind::Global => "global".to_string(),
+            BinarizerKind::Threshold(bit_binarizer::ThresholdMethod::Fixed(cutoff)) => {
+                format!("fixed_threshold:{}", cutoff)
+            }
+            BinarizerKind::Threshold(bit_binarizer::ThresholdMethod::Otsu) => "otsu".to_string(),
+            BinarizerKind::Threshold(bit_binarizer::ThresholdMethod::Adaptive(None)) => {
+                "adaptive".to_string()
+            }
+            BinarizerKind::Threshold(bit_binarizer::ThresholdMethod::Adaptive(Some(window))) => {
+                format!("adaptive:{}", window)
+            }
+        }
+    }
+
+    fn decode<L: LuminanceSource>(
+        self,
+        luma_source: L,
+        hints: &DecodeHints,
+    ) -> Result<Decoded, rxing::Exceptions> {
+        let mut reader = MultiFormatReader::default();
+        reader.set_hints(hints);
+        self.decode_with_state(luma_source, &mut reader, hints)
+    }
+
+    // reader 须已通过 set_hints 设置为 hints
+    fn decode_with_state<L: LuminanceSource>(
+        self,
+        luma_source: L,
+        reader: &mut MultiFormatReader,
+        hints: &DecodeHints,
+    ) -> Result<Decoded, rxing::Exceptions> {
+        // 只有开启 THRESHOLD_SWEEP 时才复制一份亮度供重试使用
+        let sweep =
+            (self == BinarizerKind::Hybrid && hints.threshold_sweep == Some(true)).then(|| {
+                let (width, height) = (luma_source.get_width(), luma_source.get_height());
+                (luma_source.get_matrix(), width, height)
+            });
+        let result = match self {
+            BinarizerKind::Hybrid => decode_bitmap_with_state(
+                reader,
+                &mut BinaryBitmap::new(HybridBinarizer::new(luma_source)),
+                hints,
+            ),
+            BinarizerKind::Global => decode_bitmap_with_state(
+                reader,
+                &mut BinaryBitmap::new(GlobalHistogramBinarizer::new(luma_source)),
+                hints,
+            ),
+            BinarizerKind::Threshold(method) => decode_bitmap_with_state(
+                reader,
+                &mut BinaryBitmap::new(threshold_binarizer(&luma_source, method)),
+                hints,
+            ),
+        };
+        match (result, sweep) {
+            (Err(rxing::Exceptions::NotFoundException(_)), Some((luma, width, height))) => {
+                decode_threshold_sweep(&luma, width, height, reader, hints)
+            }
+            (result, _) => result,
+        }
+    }
+}
+
+fn threshold_binarizer<L: LuminanceSource>(
+    luma_source: &L,
+    method: bit_binarizer::ThresholdMethod,
+) -> bit_binarizer::BitMatrixBinarizer {
+    bit_binarizer::BitMatrixBinarizer::with_threshold(
+        &luma_source.get_matrix(),
+        luma_source.get_width(),
+        luma_source.get_height(),
+        method,
+    )
+}
+
+impl BinarizerKind {
+    // 读码器看到的黑白矩阵. GlobalHistogramBinarizer 在直方图过于平坦时返回 NotFoundException
+    fn black_matrix<L: LuminanceSource>(
+        self,
+        luma_source: L,
+    ) -> Result<rxing::common::BitMatrix, rxing::Exceptions> {
+        match self {
+            BinarizerKind::Hybrid => HybridBinarizer::new(luma_source)
+                .get_black_matrix()
+                .cloned(),
+            BinarizerKind::Global => GlobalHistogramBinarizer::new(luma_source)
+                .get_black_matrix()
+                .cloned(),
+            BinarizerKind::Threshold(method) => threshold_binarizer(&luma_source, method)
+                .get_black_matrix()
+                .cloned(),
+        }
+    }
+}
+
+// THRESHOLD_SWEEP: 混合二值化找不到条码时, 在图像亮度范围的 1/8 .. 7/8 处依次取全局阈值,
+// 从中间向两端逐个重试, 返回第一个结果. 每个阈值是一次完整的解码
+fn decode_threshold_sweep(
+    luma: &[u8],
+    width: usize,
+    height: usize,
+    reader: &mut MultiFormatReader,
+    hints: &DecodeHints,
+) -> Result<Decoded, rxing::Exceptions> {
+    let (darkest, lightest) = luma
+        .iter()
+        .fold((u8::MAX, u8::MIN), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+    let range = u32::from(lightest.saturating_sub(darkest));
+    let mut tried = Vec::with_capacity(7);
+    let mut last_result = Err(rxing::Exceptions::NOT_FOUND);
+    for eighth in [4, 3, 5, 2, 6, 1, 7] {
+        let cutoff = u32::from(darkest) + range * eighth / 8;
+        if cutoff == u32::from(darkest) || tried.contains(&cutoff) {
+            continue;
+        }
+        tried.push(cutoff);
+        let thresholded = luma
+            .iter()
+            .map(|&v| if u32::from(v) < cutoff { 0 } else { u8::MAX })
+            .collect();
+        let luma_source = Luma8LuminanceSource::new(thresholded, width as u32, height as u32);
+        last_result = decode_bitmap_with_state(
+            reader,
+            &mut BinaryBitmap::new(HybridBinarizer::new(luma_source)),
+            hints,
+        );
+        if !matches!(last_result, Err(rxing::Exceptions::NotFoundException(_))) {
+            return last_result;
+        }
+    }
+    last_result
+}
+
+// 每次调用的图像预处理选项 (裁剪 / 补白边 / 缩放 / 旋转 / 二值化), 以及结果是否省略 result_points
+#[derive(Default)]
+struct ImageDecodeOptions {
+    // (x, y, 宽, 高), 在其它步骤之前裁剪
+    roi: Option<(u32, u32, u32, u32)>,
+    max_dimension: Option<u32>,
+    // 按 max_dimension 缩小后读不出时, 是否再以原始分辨率解码一次; 只有图像文件的解码会缩小
+    #[cfg(feature = "image")]
+    retry_full_resolution: bool,
+    try_scales: Option<Vec<f32>>,
+    rotations: Option<Vec<f32>>,
+    binarizer: BinarizerKind,
+    padding: u32,
+    skip_points: bool,
+}
+
+impl ImageDecodeOptions {
+    fn validate(&self) -> PyResult<()> {
+        if let Some((_, _, 0, _) | (_, _, _, 0)) = self.roi {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "roi width and height must be positive.",
+            ));
+        }
+        if self.max_dimension == Some(0) {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "max_dimension must be a positive number of pixels.",
+            ));
+        }
+        if let Some(scales) = &self.try_scales {
+            if scales.is_empty() {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    "try_scales must contain at least one scale factor.",
+                ));
+            }
+            if let Some(scale) = scales.iter().find(|s| !(**s > 0.0 && **s <= 1.0)) {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "try_scales values must be in (0, 1], got {}.",
+                    scale
+                )));
+            }
+        }
+        if let Some(rotations) = &self.rotations {
+            if rotations.is_empty() {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    "rotations must contain at least one angle.",
+                ));
+            }
+            if let Some(angle) = rotations.iter().find(|a| !a.is_finite()) {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "rotations values must be finite angles in degrees, got {}.",
+                    angle
+                )));
+            }
+        }
+        #[cfg(not(feature = "image"))]
+        if !self.is_default() {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "Scaling and rotation options require the `image` feature.",
+            ));
+        }
+        Ok(())
+    }
+
+    fn is_default(&self) -> bool {
+        self.max_dimension.is_none() && self.try_scales.is_none() && self.rotations.is_none()
+    }
+
+    // roi 必须完全落在 width x height 的图像内
+    fn check_roi(&self, width: u32, height: u32) -> Result<(), String> {
+        let Some((x, y, roi_width, roi_height)) = self.roi else {
+            return Ok(());
+        };
+        let fits = |start: u32, size: u32, limit: u32| {
+            start.checked_add(size).is_some_and(|end| end <= limit)
+        };
+        if fits(x, roi_width, width) && fits(y, roi_height, height) {
+            Ok(())
+        } else {
+            Err(format!(
+                "roi {:?} does not fit in the {}x{} image.",
+                (x, y, roi_width, roi_height),
+                width,
+                height
+            ))
+        }
+    }
+
+    // 补白边后的坐标减去 padding, 裁剪后的坐标加上 roi 的原点, 映射回原始图像
+    fn unpad_points(&self, result: &mut InnerRXingResult) {
+        let (x, y) = self.roi.map_or((0, 0), |(x, y, _, _)| (x, y));
+        let offset_x = x as f32 - self.padding as f32;
+        let offset_y = y as f32 - self.padding as f32;
+        if offset_x == 0.0 && offset_y == 0.0 {
+            return;
+        }
+        for point in result.getPointsMut() {
+            point.x += offset_x;
+            point.y += offset_y;
+        }
+    }
+
+    // 裁剪出 roi; roi 超出图像时报 IllegalArgumentException
+    #[cfg(feature = "image")]
+    fn crop_image(
+        &self,
+        dynamic_image: image::DynamicImage,
+    ) -> Result<image::DynamicImage, rxing::Exceptions> {
+        let Some((x, y, width, height)) = self.roi else {
+            return Ok(dynamic_image);
+        };
+        self.check_roi(dynamic_image.width(), dynamic_image.height())
+            .map_err(rxing::Exceptions::illegal_argument_with)?;
+        Ok(dynamic_image.crop_imm(x, y, width, height))
+    }
+
+    // 旋转扫描时记下成功的角度
+    #[cfg(feature = "image")]
+    fn to_py_result(&self, decoded: Decoded, rotation: f32, hints: &DecodeHints) -> PyRXingResult {
+        let mut py_result = decoded
+            .into_py_result(!self.skip_points)
+            .with_decode_hints(hints);
+        if self.rotations.is_some() {
+            py_result.rotation = Some(rotation);
+        }
+        py_result
+    }
+}
+
+#[pyfunction]
+#[pyo3(signature = (luma_data, width, height, hints_dict=None, rotations=None, timeout_ms=None, binarizer="hybrid", padding=0, collect_points=true, stride=None, roi=None))]
+#[allow(clippy::too_many_arguments)]
+fn decode_luma_pixels(
+    py: Python,
+    luma_data: PyBuffer<u8>,
+    width: u32,
+    height: u32,
+    hints_dict: Option<&Bound<PyAny>>,
+    rotations: Option<Vec<f32>>,
+    timeout_ms: Option<u64>,
+    binarizer: &str,
+    padding: i64,
+    collect_points: bool,
+    stride: Option<usize>,
+    roi: Option<(u32, u32, u32, u32)>,
+) -> PyResult<PyRXingResult> {
+    let pixels = luma_buffer_pixels(py, &luma_data, width, height, stride)?;
+    decode_luma8(
+        py,
+        pixels,
+        width,
+        height,
+        hints_dict,
+        rotations,
+        timeout_ms,
+        binarizer,
+        padding,
+        collect_points,
+        roi,
+    )
+}
+
+// 8 位灰度像素的多码解码, 参数与 decode_luma_pixels / decode_multiple_image_bytes 相同
+#[pyfunction]
+#[pyo3(signature = (luma_data, width, height, hints_dict=None, sort="none", row_tolerance=10.0, collect_points=true, stride=None, roi=None))]
+#[allow(clippy::too_many_arguments)]
+fn decode_multiple_luma_pixels(
+    py: Python,
+    luma_data: PyBuffer<u8>,
+    width: u32,
+    height: u32,
+    hints_dict: Option<&Bound<PyAny>>,
+    sort: &str,
+    row_tolerance: f32,
+    collect_points: bool,
+    stride: Option<usize>,
+    roi: Option<(u32, u32, u32, u32)>,
+) -> PyResult<Vec<PyRXingResult>> {
+    let pixels = luma_buffer_pixels(py, &luma_data, width, height, stride)?;
+    if (width * height) as usize != pixels.len() {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "Pixel data length does not match width * height.",
+        ));
+    }
+    let order = ResultOrder::parse(sort)?;
+    validate_row_tolerance(row_tolerance)?;
+    let options = roi_options(roi, width, height)?;
+    let hints = extract_decode_hints(hints_dict)?;
+    let pure_barcode = hints.PureBarcode == Some(true);
+
+    let decoded = py
+        .allow_threads(|| {
+            let (pixels, width, height) = crop_luma(pixels, width, height, options.roi);
+            let luma_source = Luma8LuminanceSource::new(pixels, width, height);
+            let mut binary_bitmap = BinaryBitmap::new(HybridBinarizer::new(luma_source));
+            decode_multiple_bitmap(&mut binary_bitmap, &hints)
+        })
+        .map_err(|e| decode_error(py, "RXing decoding failed", e, pure_barcode))?;
+    multiple_py_results(
+        py,
+        decoded,
+        &options,
+        order,
+        row_tolerance,
+        collect_points,
+        &hints,
+    )
+}
+
+// 16 位灰度: 默认线性映射 v >> 8; 给定 gamma 时为 255 * (v / 65535) ^ (1 / gamma)
+#[pyfunction]
+#[pyo3(signature = (luma_data, width, height, hints_dict=None, gamma=None, rotations=None, timeout_ms=None, binarizer="hybrid", padding=0, collect_points=true, stride=None, roi=None))]
+#[allow(clippy::too_many_arguments)]
+fn decode_luma16_pixels(
+    py: Python,
+    luma_data: PyBuffer<u16>,
+    width: u32,
+    height: u32,
+    hints_dict: Option<&Bound<PyAny>>,
+    gamma: Option<f32>,
+    rotations: Option<Vec<f32>>,
+    timeout_ms: Option<u64>,
+    binarizer: &str,
+    padding: i64,
+    collect_points: bool,
+    stride: Option<usize>,
+    roi: Option<(u32, u32, u32, u32)>,
+) -> PyResult<PyRXingResult> {
+    // stride 以 16 位的像素值计, 不是字节
+    let pixels16 = match stride {
+        Some(stride) => unstride_luma(&luma_data.to_vec(py)?, width, height, stride, |v| *v)?,
+        None => luma_data.to_vec(py)?,
+    };
+    let pixels = match gamma {
+        None => pixels16.iter().map(|v| (v >> 8) as u8).collect(),
+        Some(gamma) if gamma.is_finite() && gamma > 0.0 => {
+            let lut: Vec<u8> = (0..=u16::MAX)
+                .map(|v| {
+                    let normalized = v as f32 / u16::MAX as f32;
+                    (normalized.powf(1.0 / gamma) * 255.0).round() as u8
+                })
+                .collect();
+            pixels16.iter().map(|v| lut[*v as usize]).collect()
+        }
+        Some(gamma) => {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "gamma must be a positive number, got {}.",
+                gamma
+            )))
+        }
+    };
+    decode_luma8(
+        py,
+        pixels,
+        width,
+        height,
+        hints_dict,
+        rotations,
+        timeout_ms,
+        binarizer,
+        padding,
+        collect_points,
+        roi,
+    )
+}
+
+// 直接读取 PIL Image 的 mode/size/tobytes(), 避免重新编码为 PNG
+#[pyfunction]
+#[pyo3(signature = (image, hints_dict=None, rotations=None, timeout_ms=None, binarizer="hybrid", padding=0, collect_points=true, roi=None))]
+#[allow(clippy::too_many_arguments)]
+fn decode_pil_image(
+    py: Python,
+    image: &Bound<PyAny>,
+    hints_dict: Option<&Bound<PyAny>>,
+    rotations: Option<Vec<f32>>,
+    timeout_ms: Option<u64>,
+    binarizer: &str,
+    padding: i64,
+    collect_points: bool,
+    roi: Option<(u32, u32, u32, u32)>,
+) -> PyResult<PyRXingResult> {
+    let mode: String = image.getattr("mode")?.extract()?;
+    let (width, height): (u32, u32) = image.getattr("size")?.extract()?;
+    let raw = image.call_method0("tobytes")?;
+    let data = raw.downcast::<PyBytes>()?.as_bytes();
+    let pixels = match mode.as_str() {
+        "L" => data.to_vec(),
+        "RGB" => pixels_to_luma(data, 3),
+        "RGBA" => pixels_to_luma(data, 4),
+        _ => {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Unsupported PIL image mode {:?}; expected \"L\", \"RGB\" or \"RGBA\".",
+                mode
+            )))
+        }
+    };
+    decode_luma8(
+        py,
+        pixels,
+        width,
+        height,
+        hints_dict,
+        rotations,
+        timeout_ms,
+        binarizer,
+        padding,
+        collect_points,
+        roi,
+    )
+}
+
+// 通过缓冲区协议读取 uint8 的 NumPy 数组 (OpenCV 帧等): (高, 宽) 灰度, 或 (高, 宽, 1 / 3 / 4)
+// 的灰度 / 彩色; channel_order 为 "BGR" 时按 OpenCV 的 BGR / BGRA 顺序读取. 非连续的切片会先复制为连续数组
+#[pyfunction]
+#[pyo3(signature = (array, hints=None, channel_order="RGB", rotations=None, timeout_ms=None, binarizer="hybrid", padding=0, collect_points=true, roi=None))]
+#[allow(clippy::too_many_arguments)]
+fn decode_numpy(
+    py: Python,
+    array: &Bound<PyAny>,
+    hints: Option<&Bound<PyAny>>,
+    channel_order: &str,
+    rotations: Option<Vec<f32>>,
+    timeout_ms: Option<u64>,
+    binarizer: &str,
+    padding: i64,
+    collect_points: bool,
+    roi: Option<(u32, u32, u32, u32)>,
+) -> PyResult<PyRXingResult> {
+    let bgr = match channel_order {
+        "RGB" => false,
+        "BGR" => true,
+        _ => {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Unknown channel_order {:?}; expected \"RGB\" or \"BGR\".",
+                channel_order
+            )))
+        }
+    };
+    let buffer = PyBuffer::<u8>::get(array).map_err(|_| {
+        PyErr::new::<pyo3::exceptions::PyTypeError, _>(
+            "decode_numpy() expects a uint8 array (use decode_luma16_pixels for uint16).",
+        )
+    })?;
+    let (height, width, channels) = match *buffer.shape() {
+        [height, width] => (height, width, 1),
+        [height, width, channels @ (1 | 3 | 4)] => (height, width, channels),
+        ref shape => {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Array must have shape (height, width) or (height, width, 1 | 3 | 4), got {:?}.",
+                shape
+            )))
+        }
+    };
+    let (Ok(width), Ok(height)) = (u32::try_from(width), u32::try_from(height)) else {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "Array is too large to decode.",
+        ));
+    };
+    let pixels = interleaved_to_luma(buffer.to_vec(py)?, channels, bgr);
+    decode_luma8(
+        py,
+        pixels,
+        width,
+        height,
+        hints,
+        rotations,
+        timeout_ms,
+        binarizer,
+        padding,
+        collect_points,
+        roi,
+    )
+}
+
+// 交错排列的彩色像素 (RGB / RGBA / BGR / BGRA), 在 Rust 中转为灰度后解码. 缓冲区与 stride
+// 的用法同 decode_luma_pixels, stride 为每行的字节数 (至少 width * 通道数)
+#[pyfunction]
+#[pyo3(signature = (pixel_data, width, height, channel_order="RGB", hints_dict=None, rotations=None, timeout_ms=None, binarizer="hybrid", padding=0, collect_points=true, stride=None, roi=None))]
+#[allow(clippy::too_many_arguments)]
+fn decode_rgb_pixels(
+    py: Python,
+    pixel_data: PyBuffer<u8>,
+    width: u32,
+    height: u32,
+    channel_order: &str,
+    hints_dict: Option<&Bound<PyAny>>,
+    rotations: Option<Vec<f32>>,
+    timeout_ms: Option<u64>,
+    binarizer: &str,
+    padding: i64,
+    collect_points: bool,
+    stride: Option<usize>,
+    roi: Option<(u32, u32, u32, u32)>,
+) -> PyResult<PyRXingResult> {
+    let (channels, bgr) = match channel_order {
+        "RGB" => (3, false),
+        "RGBA" => (4, false),
+        "BGR" => (3, true),
+        "BGRA" => (4, true),
+        _ => {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Unknown channel_order {:?}; expected \"RGB\", \"RGBA\", \"BGR\" or \"BGRA\".",
+                channel_order
+            )))
+        }
+    };
+    let too_large =
+        || PyErr::new::<pyo3::exceptions::PyValueError, _>("Image is too large to decode.");
+    let expected = pixel_count(width, height)?
+        .checked_mul(channels)
+        .ok_or_else(too_large)?;
+    let row_bytes = width.checked_mul(channels as u32).ok_or_else(too_large)?;
+    let data = luma_buffer_pixels(py, &pixel_data, row_bytes, height, stride)?;
+    if data.len() != expected {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Pixel data has {} bytes, expected {} for {}x{} {} pixels.",
+            data.len(),
+            expected,
+            width,
+            height,
+            channel_order
+        )));
+    }
+    decode_luma8(
+        py,
+        interleaved_to_luma(data, channels, bgr),
+        width,
+        height,
+        hints_dict,
+        rotations,
+        timeout_ms,
+        binarizer,
+        padding,
+        collect_points,
+        roi,
+    )
+}
+
+// YUV 相机帧只取亮度 (Y) 平面解码, 不做颜色转换. NV12 / NV21 (半平面) 与 I420 / YV12 (平面)
+// 的 Y 平面都在最前面; YUYV / UYVY 为打包格式, Y 与色度字节交替. stride 为 Y 平面 (打包格式为整帧)
+// 每行的字节数, 省略时缓冲区必须恰好是一帧
+#[pyfunction]
+#[pyo3(signature = (data, width, height, format="NV12", hints=None, rotations=None, timeout_ms=None, binarizer="hybrid", padding=0, collect_points=true, stride=None, roi=None))]
+#[allow(clippy::too_many_arguments)]
+fn decode_yuv(
+    py: Python,
+    data: PyBuffer<u8>,
+    width: u32,
+    height: u32,
+    format: &str,
+    hints: Option<&Bound<PyAny>>,
+    rotations: Option<Vec<f32>>,
+    timeout_ms: Option<u64>,
+    binarizer: &str,
+    padding: i64,
+    collect_points: bool,
+    stride: Option<usize>,
+    roi: Option<(u32, u32, u32, u32)>,
+) -> PyResult<PyRXingResult> {
+    let luma_size = pixel_count(width, height)?;
+    let too_large =
+        || PyErr::new::<pyo3::exceptions::PyValueError, _>("Frame is too large to decode.");
+    let (w, h) = (width as usize, height as usize);
+    // 打包格式中 Y 在每两个字节中的位置; 平面格式为 None
+    let (packed_offset, frame_size) = match format {
+        "NV12" | "NV21" | "I420" | "YV12" => {
+            // 色度为 4:2:0 采样, 奇数尺寸向上取整
+            let chroma = w.div_ceil(2) * h.div_ceil(2) * 2;
+            (None, luma_size.checked_add(chroma))
+        }
+        "YUYV" | "UYVY" => {
+            if !width.is_multiple_of(2) {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "{} frames must have an even width, got {}.",
+                    format, width
+                )));
+            }
+            let offset = if format == "YUYV" { 0 } else { 1 };
+            (Some(offset), luma_size.checked_mul(2))
+        }
+        _ => {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Unknown YUV format {:?}; expected \"NV12\", \"NV21\", \"I420\", \"YV12\", \"YUYV\" or \"UYVY\".",
+                format
+            )))
+        }
+    };
+    let frame_size = frame_size.ok_or_else(too_large)?;
+    if stride.is_none() && data.item_count() != frame_size {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Frame has {} bytes, expected {} for a {}x{} {} frame.",
+            data.item_count(),
+            frame_size,
+            width,
+            height,
+            format
+        )));
+    }
+    let row_bytes = match packed_offset {
+        Some(_) => width.checked_mul(2).ok_or_else(too_large)?,
+        None => width,
+    };
+    let rows = luma_buffer_pixels(
+        py,
+        &data,
+        row_bytes,
+        height,
+        Some(stride.unwrap_or(row_bytes as usize)),
+    )?;
+    let pixels = match packed_offset {
+        Some(offset) => rows.into_iter().skip(offset).step_by(2).collect(),
+        None => rows,
+    };
+    decode_luma8(
+        py,
+        pixels,
+        width,
+        height,
+        hints,
+        rotations,
+        timeout_ms,
+        binarizer,
+        padding,
+        collect_points,
+        roi,
+    )
+}
+
+// 1 / 3 / 4 通道的交错像素转灰度; bgr 时前三个通道按 B, G, R 的顺序读取
+fn interleaved_to_luma(mut data: Vec<u8>, channels: usize, bgr: bool) -> Vec<u8> {
+    if channels == 1 {
+        return data;
+    }
+    if bgr {
+        for pixel in data.chunks_exact_mut(channels) {
+            pixel.swap(0, 2);
+        }
+    }
+    pixels_to_luma(&data, channels)
+}
+
+// 与 BufferedImageLuminanceSource 相同的加权灰度公式
+fn rgb_to_luma(r: u8, g: u8, b: u8) -> u8 {
+    ((306 * r as u32 + 601 * g as u32 + 117 * b as u32 + 0x200) >> 10) as u8
+}
+
+// RGB / RGBA 像素转灰度; 与 BufferedImageLuminanceSource 一样, 完全透明的像素视为白色
+fn pixels_to_luma(data: &[u8], channels: usize) -> Vec<u8> {
+    data.chunks_exact(channels).map(pixel_luma).collect()
+}
+
+// 就地转换为灰度, 结果在前 len / channels 个字节. 第 i 个像素写到下标 i,
+// 不会覆盖尚未读取的像素
+fn pixels_to_luma_in_place(data: &mut [u8], channels: usize) {
+    for i in 0..data.len() / channels {
+        data[i] = pixel_luma(&data[i * channels..(i + 1) * channels]);
+    }
+}
+
+fn pixel_luma(p: &[u8]) -> u8 {
+    if p.len() == 4 && p[3] == 0 {
+        u8::MAX
+    } else {
+        rgb_to_luma(p[0], p[1], p[2])
+    }
+}
+
+// 把 RGB / RGBA 缓冲区转换成解码器实际使用的 8 位灰度, 供外部预处理与解码器保持一致
+#[pyfunction]
+fn to_luma<'py>(
+    py: Python<'py>,
+    rgb_bytes: &[u8],
+    width: u32,
+    height: u32,
+    channels: usize,
+) -> PyResult<Bound<'py, PyBytes>> {
+    if !matches!(channels, 1 | 3 | 4) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "channels must be 1, 3 or 4, got {}.",
+            channels
+        )));
+    }
+    let expected = width as usize * height as usize * channels;
+    if rgb_bytes.len() != expected {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Buffer has {} bytes, expected {} for a {}x{} image with {} channels.",
+            rgb_bytes.len(),
+            expected,
+            width,
+            height,
+            channels
+        )));
+    }
+    if channels == 1 {
+        return Ok(PyBytes::new(py, rgb_bytes));
+    }
+    Ok(PyBytes::new(py, &pixels_to_luma(rgb_bytes, channels)))
+}
+
+fn parse_padding(padding: i64) -> PyResult<u32> {
+    u32::try_from(padding).map_err(|_| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "padding must be a non-negative number of pixels, got {}.",
+            padding
+        ))
+    })
+}
+
+// 补上四周各 padding 像素后的尺寸, 溢出时报错
+fn padded_size(width: u32, height: u32, padding: u32) -> Result<(u32, u32), rxing::Exceptions> {
+    let grow = |size: u32| {
+        padding
+            .checked_mul(2)
+            .and_then(|border| border.checked_add(size))
+    };
+    match (grow(width), grow(height)) {
+        (Some(padded_width), Some(padded_height))
+            if (padded_width as usize)
+                .checked_mul(padded_height as usize)
+                .is_some_and(|len| len <= isize::MAX as usize / 4) =>
+        {
+            Ok((padded_width, padded_height))
+        }
+        _ => Err(rxing::Exceptions::illegal_argument_with(format!(
+            "padding {} makes the {}x{} image too large.",
+            padding, width, height
+        ))),
+    }
+}
+
+// 通过缓冲区协议读取 8 位灰度像素: bytes, bytearray, memoryview, NumPy 数组等都不必先转为 bytes.
+// 缓冲区只在调用期间借用, 像素直接复制一次进 rxing 的亮度源 (Luma8LuminanceSource 持有自己的 Vec),
+// 之后释放 GIL 解码; 调用返回后调用方即可复用或改写缓冲区
+fn luma_buffer_pixels(
+    py: Python,
+    buffer: &PyBuffer<u8>,
+    width: u32,
+    height: u32,
+    stride: Option<usize>,
+) -> PyResult<Vec<u8>> {
+    match (stride, buffer.as_slice(py)) {
+        (Some(stride), Some(cells)) => unstride_luma(cells, width, height, stride, |c| c.get()),
+        (Some(stride), None) => unstride_luma(&buffer.to_vec(py)?, width, height, stride, |v| *v),
+        (None, _) => buffer.to_vec(py),
+    }
+}
+
+// 宽高须为正数; 返回像素数 width * height, 按 usize 计算, 溢出时报错
+fn pixel_count(width: u32, height: u32) -> PyResult<usize> {
+    if width == 0 || height == 0 {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Image dimensions must be positive, got {}x{}.",
+            width, height
+        )));
+    }
+    (width as usize)
+        .checked_mul(height as usize)
+        .ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Image dimensions {}x{} are too large.",
+                width, height
+            ))
+        })
+}
+
+// 每行末尾带填充的缓冲区 (stride > width): 逐行取出前 width 个像素拼成紧凑的图像.
+// 最后一行之后的填充可以省略
+fn unstride_luma<T, V>(
+    data: &[T],
+    width: u32,
+    height: u32,
+    stride: usize,
+    value: impl Fn(&T) -> V,
+) -> PyResult<Vec<V>> {
+    pixel_count(width, height)?;
+    let width = width as usize;
+    // width 至少为 1, 因此 stride 为 0 时也在这里报错, 不会走到 chunks(0)
+    if stride < width {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "stride must be at least width ({}), got {}.",
+            width, stride
+        )));
+    }
+    let required = stride
+        .checked_mul(height as usize - 1)
+        .and_then(|n| n.checked_add(width));
+    match required {
+        Some(required) if data.len() >= required => Ok(data
+            .chunks(stride)
+            .take(height as usize)
+            .flat_map(|row| &row[..width])
+            .map(value)
+            .collect()),
+        _ => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Pixel data length {} is too short for {} rows of stride {}.",
+            data.len(),
+            height,
+            stride
+        ))),
+    }
+}
+
+// 只设置 roi 的选项, 用于不做缩放与旋转的灰度解码; roi 须落在 width x height 的图像内
+fn roi_options(
+    roi: Option<(u32, u32, u32, u32)>,
+    width: u32,
+    height: u32,
+) -> PyResult<ImageDecodeOptions> {
+    let options = ImageDecodeOptions {
+        roi,
+        ..Default::default()
+    };
+    options.validate()?;
+    options
+        .check_roi(width, height)
+        .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
+    Ok(options)
+}
+
+// 只保留 roi 内的像素; roi 已由 check_roi 检查
+fn crop_luma(
+    pixels: Vec<u8>,
+    width: u32,
+    height: u32,
+    roi: Option<(u32, u32, u32, u32)>,
+) -> (Vec<u8>, u32, u32) {
+    let Some((x, y, roi_width, roi_height)) = roi else {
+        return (pixels, width, height);
+    };
+    let cropped = pixels
+        .chunks_exact(width as usize)
+        .skip(y as usize)
+        .take(roi_height as usize)
+        .flat_map(|row| &row[x as usize..(x + roi_width) as usize])
+        .copied()
+        .collect();
+    (cropped, roi_width, roi_height)
+}
+
+// 把 roi 内的各行依次移到缓冲区开头, 返回裁剪后的尺寸; 目标位置不在来源之后, 不需要另一块缓冲区
+fn crop_luma_in_place(
+    pixels: &mut [u8],
+    width: u32,
+    height: u32,
+    roi: Option<(u32, u32, u32, u32)>,
+) -> (u32, u32) {
+    let Some((x, y, roi_width, roi_height)) = roi else {
+        return (width, height);
+    };
+    for row in 0..roi_height as usize {
+        let start = (y as usize + row) * width as usize + x as usize;
+        pixels.copy_within(start..start + roi_width as usize, row * roi_width as usize);
+    }
+    (roi_width, roi_height)
+}
+
+// 在四周补上白边, 给贴边裁切的条码补出静区
+fn pad_luma(
+    pixels: &[u8],
+    width: u32,
+    height: u32,
+    padding: u32,
+) -> Result<(Vec<u8>, u32, u32), rxing::Exceptions> {
+    let (padded_width, padded_height) = padded_size(width, height, padding)?;
+    let mut padded = vec![u8::MAX; padded_width as usize * padded_height as usize];
+    if width > 0 {
+        for (row, line) in pixels.chunks_exact(width as usize).enumerate() {
+            let start = (row + padding as usize) * padded_width as usize + padding as usize;
+            padded[start..start + width as usize].copy_from_slice(line);
+        }
+    }
+    Ok((padded, padded_width, padded_height))
+}
+
+// 灰度图直接补白边, 其它颜色类型转为 RGBA 后补不透明的白边
+#[cfg(feature = "image")]
+fn pad_image(
+    dynamic_image: image::DynamicImage,
+    padding: u32,
+) -> Result<image::DynamicImage, rxing::Exceptions> {
+    if padding == 0 {
+        return Ok(dynamic_image);
+    }
+    let (width, height) = (dynamic_image.width(), dynamic_image.height());
+    if let image::DynamicImage::ImageLuma8(gray) = &dynamic_image {
+        let (pixels, padded_width, padded_height) = pad_luma(gray, width, height, padding)?;
+        return Ok(
+            image::GrayImage::from_raw(padded_width, padded_height, pixels)
+                .expect("padded buffer matches its dimensions")
+                .into(),
+        );
+    }
+    let (padded_width, padded_height) = padded_size(width, height, padding)?;
+    let mut canvas =
+        image::RgbaImage::from_pixel(padded_width, padded_height, image::Rgba([u8::MAX; 4]));
+    image::imageops::replace(
+        &mut canvas,
+        &dynamic_image.to_rgba8(),
+        padding as i64,
+        padding as i64,
+    );
+    Ok(canvas.into())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn decode_luma8(
+    py: Python,
+    pixels: Vec<u8>,
+    width: u32,
+    height: u32,
+    hints_dict: Option<&Bound<PyAny>>,
+    rotations: Option<Vec<f32>>,
+    timeout_ms: Option<u64>,
+    binarizer: &str,
+    padding: i64,
+    collect_points: bool,
+    roi: Option<(u32, u32, u32, u32)>,
+) -> PyResult<PyRXingResult> {
+    if (width * height) as usize != pixels.len() {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "Pixel data length does not match width * height.",
+        ));
+    }
+
+    let options = ImageDecodeOptions {
+        roi,
+        rotations,
+        binarizer: BinarizerKind::parse(binarizer)?,
+        padding: parse_padding(padding)?,
+        skip_points: !collect_points,
+        ..Default::default()
+    };
+    options.validate()?;
+    options
+        .check_roi(width, height)
+        .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
+    let hints = extract_decode_hints(hints_dict)?;
+    let pure_barcode = hints.PureBarcode == Some(true);
+
+    let decoded = run_decode(py, timeout_ms, move || {
+        #[cfg(feature = "image")]
+        if !options.is_default() {
+            let gray_image =
+                image::GrayImage::from_raw(width, height, pixels).ok_or_else(|| {
+                    rxing::Exceptions::illegal_argument_with(
+                        "Pixel data length does not match width * height.",
+                    )
+                })?;
+            return decode_dynamic_image(gray_image.into(), &hints, &options)
+                .map(|(result, rotation)| options.to_py_result(result, rotation, &hints));
+        }
+
+        let (pixels, width, height) = crop_luma(pixels, width, height, options.roi);
+        let (pixels, width, height) = pad_luma(&pixels, width, height, options.padding)?;
+        let luma_source = Luma8LuminanceSource::new(pixels, width, height);
+        let mut result = options.binarizer.decode(luma_source, &hints)?;
+        options.unpad_points(&mut result.result);
+        Ok::<_, rxing::Exceptions>(
+            result
+                .into_py_result(!options.skip_points)
+                .with_decode_hints(&hints),
+        )
+    })?;
+    decoded.map_err(|e| decode_error(py, "RXing decoding failed", e, pure_barcode))
+}
+
+// 解码已二值化的位图 (例如前级硬件输出的 1-bit 图像), 跳过亮度与二值化阶段;
+// 置位的模块视为黑色
+#[pyfunction]
+#[pyo3(signature = (matrix, hints_dict=None, timeout_ms=None, collect_points=true))]
+fn decode_from_bit_matrix(
+    py: Python,
+    matrix: &PyBitMatrix,
+    hints_dict: Option<&Bound<PyAny>>,
+    timeout_ms: Option<u64>,
+    collect_points: bool,
+) -> PyResult<PyRXingResult> {
+    decode_bits(
+        py,
+        matrix.inner_matrix.clone(),
+        hints_dict,
+        timeout_ms,
+        collect_points,
+    )
+}
+
+fn decode_bits(
+    py: Python,
+    bits: rxing::common::BitMatrix,
+    hints_dict: Option<&Bound<PyAny>>,
+    timeout_ms: Option<u64>,
+    collect_points: bool,
+) -> PyResult<PyRXingResult> {
+    let hints = extract_decode_hints(hints_dict)?;
+    let pure_barcode = hints.PureBarcode == Some(true);
+    let decoded = run_decode(py, timeout_ms, move || {
+        // 一维读码器在 PURE_BARCODE 下读取第 1 行, 只有一行的矩阵 (一维码的编码结果)
+        // 会越界, 因此复制为两行, 结果点再映射回第 0 行
+        let single_row = bits.getHeight() == 1;
+        let bits = if single_row {
+            let mut doubled = rxing::common::BitMatrix::new(bits.getWidth(), 2)?;
+            doubled.setRow(0, &bits.getRow(0));
+            doubled.setRow(1, &bits.getRow(0));
+            doubled
+        } else {
+            bits
+        };
+        let mut binary_bitmap = BinaryBitmap::new(bit_binarizer::BitMatrixBinarizer::new(bits));
+        let mut result = decode_bitmap(&mut binary_bitmap, &hints)?;
+        if single_row {
+            for point in result.result.getPointsMut() {
+                point.y = 0.0;
+            }
+        }
+        Ok::<_, rxing::Exceptions>(
+            result
+                .into_py_result(collect_points)
+                .with_decode_hints(&hints),
+        )
+    })?;
+    decoded.map_err(|e| decode_error(py, "RXing decoding failed", e, pure_barcode))
+}
+
+// 解码失败的异常信息. PURE_BARCODE 要求图像中只有一个未旋转, 无透视且边缘整齐的符号,
+// 在相机拍摄的图像上设置时会直接 NotFound, 因此在信息中提示这一可能的原因
+fn decode_failure(e: &rxing::Exceptions, pure_barcode: bool) -> String {
+    let mut message = format!("{:?}", e);
+    if pure_barcode && matches!(e, rxing::Exceptions::NotFoundException(_)) {
+        message.push_str(
+            " (PURE_BARCODE is set: it only works on clean, unrotated images that contain \
+             nothing but the barcode; disable it for camera captures and scans)",
+        );
+    }
+    message
+}
+
+// 解码失败时抛出的异常: 消息为 "{context}: {decode_failure}", 类型由 rxing 的错误决定
+fn decode_error(py: Python, context: &str, e: rxing::Exceptions, pure_barcode: bool) -> PyErr {
+    let message = format!("{}: {}", context, decode_failure(&e, pure_barcode));
+    errors::reader_error(py, message, &e)
+}
+
+// 在工作线程中解码并释放 GIL. 给定 timeout_ms 时超过时限即抛出 TimeoutError;
+// rxing 的解码无法中断, 超时后工作线程仍会在后台运行到结束, 其结果被丢弃
+fn run_decode<T, F>(py: Python, timeout_ms: Option<u64>, task: F) -> PyResult<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let Some(timeout_ms) = timeout_ms else {
+        return Ok(py.allow_threads(task));
+    };
+    if timeout_ms == 0 {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "timeout_ms must be a positive number of milliseconds.",
+        ));
+    }
+    let (sender, receiver) = std::sync::mpsc::channel();
+    std::thread::Builder::new()
+        .name("rxing-decode".to_string())
+        .spawn(move || {
+            // 超时后接收端已被丢弃, 发送失败可以忽略
+            let _ = sender.send(task());
+        })
+        .map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
+                "Failed to start decode thread: {}",
+                e
+            ))
+        })?;
+    match py.allow_threads(move || receiver.recv_timeout(Duration::from_millis(timeout_ms))) {
+        Ok(value) => Ok(value),
+        Err(RecvTimeoutError::Timeout) => Err(PyErr::new::<pyo3::exceptions::PyTimeoutError, _>(
+            format!("Decoding did not finish within {} ms.", timeout_ms),
+        )),
+        Err(RecvTimeoutError::Disconnected) => Err(
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("The decode thread panicked."),
+        ),
+    }
+}
+
+// 视频流扫描器: 解码提示, MultiFormatReader 与帧缓冲区在构造时准备一次,
+// 每帧拷贝到同一块缓冲区, 就地转换为灰度后解码
+#[pyclass(name = "VideoScanner")]
+struct PyVideoScanner {
+    #[pyo3(get)]
+    width: u32,
+    #[pyo3(get)]
+    height: u32,
+    #[pyo3(get)]
+    pixel_format: String,
+    channels: usize,
+    frame: Vec<u8>,
+    // 只带 roi, 每帧都裁剪同一块区域
+    options: ImageDecodeOptions,
+    binarizer: BinarizerKind,
+    hints: DecodeHints,
+    reader: MultiFormatReader,
+}
+
+#[pymethods]
+impl PyVideoScanner {
+    #[new]
+    #[pyo3(signature = (width, height, pixel_format="L", hints_dict=None, binarizer="hybrid", roi=None))]
+    fn new(
+        width: u32,
+        height: u32,
+        pixel_format: &str,
+        hints_dict: Option<&Bound<PyAny>>,
+        binarizer: &str,
+        roi: Option<(u32, u32, u32, u32)>,
+    ) -> PyResult<Self> {
+        if width == 0 || height == 0 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Frame size must be positive, got {}x{}.",
+                width, height
+            )));
+        }
+        let channels = match pixel_format {
+            "L" => 1,
+            "RGB" => 3,
+            "RGBA" => 4,
+            _ => {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Unsupported pixel format {:?}; expected \"L\", \"RGB\" or \"RGBA\".",
+                    pixel_format
+                )))
+            }
+        };
+        let frame_size = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|pixels| pixels.checked_mul(channels))
+            .ok_or_else(|| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Frame size {}x{} is too large.",
+                    width, height
+                ))
+            })?;
+        let options = roi_options(roi, width, height)?;
+        let hints = extract_decode_hints(hints_dict)?;
+        let mut reader = MultiFormatReader::default();
+        reader.set_hints(&hints);
+        Ok(PyVideoScanner {
+            width,
+            height,
+            pixel_format: pixel_format.to_string(),
+            channels,
+            frame: vec![0; frame_size],
+            options,
+            binarizer: BinarizerKind::parse(binarizer)?,
+            hints,
+            reader,
+        })
+    }
+
+    // 解码一帧; 帧中没有可识别的条码时返回 None
+    fn push_frame(&mut self, py: Python, frame: PyBuffer<u8>) -> PyResult<Option<PyRXingResult>> {
+        // 缓冲区在构造时按 width * height * channels 分配, 长度即一帧的字节数
+        let expected = self.frame.len();
+        if frame.item_count() != expected {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Frame has {} bytes, expected {} for a {}x{} {} frame.",
+                frame.item_count(),
+                expected,
+                self.width,
+                self.height,
+                self.pixel_format
+            )));
+        }
+        frame.copy_to_slice(py, &mut self.frame)?;
+        let channels = self.channels;
+        let (width, height) = (self.width, self.height);
+        let binarizer = self.binarizer;
+        let data = &mut self.frame;
+        let reader = &mut self.reader;
+        let (options, hints) = (&self.options, &self.hints);
+        let decoded = py.allow_threads(move || {
+            if channels > 1 {
+                pixels_to_luma_in_place(data, channels);
+            }
+            let (width, height) = crop_luma_in_place(data, width, height, options.roi);
+            let (width, height) = (width as usize, height as usize);
+            let luma_source =
+                luma_source::BorrowedLumaSource::new(&data[..width * height], width, height);
+            let mut decoded = binarizer.decode_with_state(luma_source, reader, hints)?;
+            options.unpad_points(&mut decoded.result);
+            Ok(decoded)
+        });
+        match decoded {
+            Ok(decoded) => Ok(Some(
+                PyRXingResult::from(decoded).with_decode_hints(&self.hints),
+            )),
+            Err(rxing::Exceptions::NotFoundException(_))
+            | Err(rxing::Exceptions::ChecksumException(_))
+            | Err(rxing::Exceptions::FormatException(_)) => Ok(None),
+            Err(e) => Err(decode_error(py, "RXing decoding failed", e, false)),
+        }
+    }
+
+    #[getter]
+    fn roi(&self) -> Option<(u32, u32, u32, u32)> {
+        self.options.roi
+    }
+}
+
+// 可复用的读码器: 解码提示在构造时转换一次, MultiFormatReader 在各次调用之间保留.
+// reader 由 Mutex 保护, 同一实例可以在多个线程间共享, 并发的调用依次解码
+#[pyclass(name = "BarcodeReader")]
+struct PyBarcodeReader {
+    binarizer: BinarizerKind,
+    hints: DecodeHints,
+    reader: Mutex<MultiFormatReader>,
+}
+
+impl PyBarcodeReader {
+    fn decode_source<L: LuminanceSource>(
+        &self,
+        luma_source: L,
+    ) -> Result<Decoded, rxing::Exceptions> {
+        let mut reader = self
+            .reader
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        self.binarizer
+            .decode_with_state(luma_source, &mut reader, &self.hints)
+    }
+
+    fn to_result(
+        &self,
+        py: Python,
+        decoded: Result<Decoded, rxing::Exceptions>,
+    ) -> PyResult<PyRXingResult> {
+        match decoded {
+            Ok(decoded) => Ok(PyRXingResult::from(decoded).with_decode_hints(&self.hints)),
+            Err(e) => Err(decode_error(
+                py,
+                "RXing decoding failed",
+                e,
+                self.hints.PureBarcode == Some(true),
+            )),
+        }
+    }
+}
+
+#[pymethods]
+impl PyBarcodeReader {
+    #[new]
+    #[pyo3(signature = (hints=None, binarizer="hybrid"))]
+    fn new(hints: Option<&Bound<PyAny>>, binarizer: &str) -> PyResult<Self> {
+        let hints = extract_decode_hints(hints)?;
+        let mut reader = MultiFormatReader::default();
+        reader.set_hints(&hints);
+        Ok(PyBarcodeReader {
+            binarizer: BinarizerKind::parse(binarizer)?,
+            hints,
+            reader: Mutex::new(reader),
+        })
+    }
+
+    // 解码图像文件, source 为路径或图像文件的 bytes
+    #[cfg(feature = "image")]
+    #[pyo3(signature = (source, apply_exif_orientation=true, roi=None))]
+    fn decode(
+        &self,
+        py: Python,
+        source: &Bound<PyAny>,
+        apply_exif_orientation: bool,
+        roi: Option<(u32, u32, u32, u32)>,
+    ) -> PyResult<PyRXingResult> {
+        let options = ImageDecodeOptions {
+            roi,
+            ..Default::default()
+        };
+        options.validate()?;
+        let image_bytes = match source.downcast::<PyBytes>() {
+            Ok(bytes) => Some(bytes.as_bytes().to_vec()),
+            Err(_) => None,
+        };
+        let path = match image_bytes {
+            Some(_) => None,
+            None => Some(extract_path(source)?),
+        };
+        let decoded = py.allow_threads(|| {
+            let dynamic_image = match (&path, image_bytes) {
+                (Some(path), _) => open_image(path, apply_exif_orientation),
+                (None, bytes) => {
+                    load_image_bytes(&bytes.unwrap_or_default(), apply_exif_orientation)
+                }
+            }?;
+            Ok(options.crop_image(dynamic_image).and_then(|dynamic_image| {
+                let mut decoded =
+                    self.decode_source(BufferedImageLuminanceSource::new(dynamic_image))?;
+                options.unpad_points(&mut decoded.result);
+                Ok(decoded)
+            }))
+        });
+        match (decoded, &path) {
+            (Ok(decoded), _) => self.to_result(py, decoded),
+            (Err(image::ImageError::IoError(e)), Some(path)) => Err(file_io_error(path, e)),
+            (Err(e), _) => Err(PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
+                "Failed to load image: {:?}",
+                e
+            ))),
+        }
+    }
+
+    // 解码 8 位灰度像素, 参数与 decode_luma_pixels 相同
+    #[pyo3(signature = (luma_data, width, height, stride=None, roi=None))]
+    fn decode_pixels(
+        &self,
+        py: Python,
+        luma_data: PyBuffer<u8>,
+        width: u32,
+        height: u32,
+        stride: Option<usize>,
+        roi: Option<(u32, u32, u32, u32)>,
+    ) -> PyResult<PyRXingResult> {
+        let expected = pixel_count(width, height)?;
+        let pixels = luma_buffer_pixels(py, &luma_data, width, height, stride)?;
+        if pixels.len() != expected {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "Pixel data length does not match width * height.",
+            ));
+        }
+        let options = roi_options(roi, width, height)?;
+        let decoded = py.allow_threads(|| {
+            let (pixels, width, height) = crop_luma(pixels, width, height, options.roi);
+            let mut decoded =
+                self.decode_source(Luma8LuminanceSource::new(pixels, width, height))?;
+            options.unpad_points(&mut decoded.result);
+            Ok(decoded)
+        });
+        self.to_result(py, decoded)
+    }
+
+    // 清除 reader 在各次调用之间保留的状态, 解码提示不变
+    fn reset(&self) {
+        self.reader
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .reset();
+    }
+
+    #[getter]
+    fn binarizer(&self) -> String {
+        self.binarizer.name()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("BarcodeReader(binarizer={:?})", self.binarizer.name())
+    }
+}
+
+// 跨帧收集同一条 QR 结构化追加消息的各个符号, 全部到齐后返回拼接的内容并清空,
+// 以便接着收集下一条消息
+#[pyclass(name = "AppendCollector")]
+#[derive(Default)]
+struct PyAppendCollector {
+    parity: Option<u8>,
+    parts: Vec<Option<Vec<u8>>>,
+}
+
+#[pymethods]
+impl PyAppendCollector {
+    #[new]
+    fn new() -> Self {
+        PyAppendCollector::default()
+    }
+
+    // 尚未收到的符号序号
+    #[getter]
+    fn missing(&self) -> Vec<u32> {
+        (0..self.parts.len() as u32)
+            .filter(|&index| self.parts[index as usize].is_none())
+            .collect()
+    }
+
+    fn reset(&mut self) {
+        *self = PyAppendCollector::default();
+    }
+
+    fn add<'py>(
+        &mut self,
+        py: Python<'py>,
+        result: &PyRXingResult,
+    ) -> PyResult<Option<Bound<'py, PyBytes>>> {
+        let Some(symbol::StructuredAppend {
+            index,
+            total,
+            parity,
+        }) = result.structured_append()
+        else {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "Result is not part of a QR structured append sequence.",
+            ));
+        };
+        if index >= total {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Structured append index {} is out of range for {} symbols.",
+                index, total
+            )));
+        }
+        match self.parity {
+            Some(expected) if expected != parity || self.parts.len() != total as usize => {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Symbol belongs to a different message (parity {}, {} symbols); \
+                     collecting parity {}, {} symbols. Call reset() to switch messages.",
+                    parity,
+                    total,
+                    expected,
+                    self.parts.len()
+                )));
+            }
+            Some(_) => {}
+            None => {
+                self.parity = Some(parity);
+                self.parts = vec![None; total as usize];
+            }
+        }
+        // 各部分按原始字节拼接; 无法还原原始字节时退回 UTF-8 编码的 text
+        let bytes = match &result.text_bytes {
+            Some(bytes) => bytes.clone(),
+            None => result.text.as_bytes().to_vec(),
+        };
+        self.parts[index as usize] = Some(bytes);
+        if self.parts.iter().any(Option::is_none) {
+            return Ok(None);
+        }
+        let payload: Vec<u8> = self.parts.iter().flatten().flatten().copied().collect();
+        self.reset();
+        Ok(Some(PyBytes::new(py, &payload)))
+    }
+}
+
+// 诊断解码中一种格式的尝试结果
+#[pyclass(name = "FormatAttempt")]
+#[derive(Clone)]
+struct PyFormatAttempt {
+    #[pyo3(get)]
+    format: String,
+    #[pyo3(get)]
+    success: bool,
+    // 失败时的异常类型, 如 "NotFoundException" / "ChecksumException"
+    #[pyo3(get)]
+    error_kind: Option<String>,
+    #[pyo3(get)]
+    error_message: Option<String>,
+    #[pyo3(get)]
+    result: Option<PyRXingResult>,
+}
+
+#[pymethods]
+impl PyFormatAttempt {
+    fn __repr__(&self) -> String {
+        match &self.error_kind {
+            Some(kind) => format!("FormatAttempt({}, failed: {})", self.format, kind),
+            None => format!("FormatAttempt({}, success)", self.format),
+        }
+    }
+}
+
+// 依次只用一种格式解码, 报告每种格式的结果; POSSIBLE_FORMATS 限定尝试的格式
+#[pyfunction]
+#[pyo3(signature = (luma_data, width, height, hints_dict=None, stride=None))]
+fn diagnose_luma_pixels(
+    py: Python,
+    luma_data: PyBuffer<u8>,
+    width: u32,
+    height: u32,
+    hints_dict: Option<&Bound<PyAny>>,
+    stride: Option<usize>,
+) -> PyResult<Vec<PyFormatAttempt>> {
+    let pixels = luma_buffer_pixels(py, &luma_data, width, height, stride)?;
+    if (width * height) as usize != pixels.len() {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "Pixel data length does not match width * height.",
+        ));
+    }
+    let hints = extract_decode_hints(hints_dict)?;
+    let formats: Vec<BarcodeFormat> = match &hints.PossibleFormats {
+        Some(formats) => SUPPORTED_READ_FORMATS
+            .iter()
+            .filter(|format| formats.contains(format))
+            .copied()
+            .collect(),
+        None => SUPPORTED_READ_FORMATS.to_vec(),
+    };
+
+    Ok(py.allow_threads(|| {
+        formats
+            .into_iter()
+            .map(|format| {
+                let mut format_hints = hints.clone();
+                format_hints.PossibleFormats = Some(HashSet::from([format]));
+                // ALSO_INVERTED 会就地反转位图, 每种格式都从新的位图开始
+                let luma_source = Luma8LuminanceSource::new(pixels.clone(), width, height);
+                let mut binary_bitmap = BinaryBitmap::new(HybridBinarizer::new(luma_source));
+                match decode_bitmap(&mut binary_bitmap, &format_hints) {
+                    Ok(decoded) => PyFormatAttempt {
+                        format: format.to_string(),
+                        success: true,
+                        error_kind: None,
+                        error_message: None,
+                        result: Some(PyRXingResult::from(decoded).with_decode_hints(&hints)),
+                    },
+                    Err(e) => PyFormatAttempt {
+                        format: format.to_string(),
+                        success: false,
+                        error_kind: Some(errors::error_kind(&e).to_string()),
+                        error_message: Some(format!("{:?}", e)),
+                        result: None,
+                    },
+                }
+            })
+            .collect()
+    }))
+}
+
+// 一维码候选结果: 从中间向外逐行 (及其反向) 用每种一维格式单独解码, 相同 text 合并,
+// 按读出该 text 的扫描行数从多到少排序, 票数相同时靠近中线的在前.
+// rxing 不提供置信度, 各扫描行结果的一致程度是唯一可用的信号
+#[pyfunction]
+#[pyo3(signature = (luma_data, width, height, hints_dict=None, binarizer="hybrid", stride=None, roi=None))]
+#[allow(clippy::too_many_arguments)]
+fn decode_luma_candidates(
+    py: Python,
+    luma_data: PyBuffer<u8>,
+    width: u32,
+    height: u32,
+    hints_dict: Option<&Bound<PyAny>>,
+    binarizer: &str,
+    stride: Option<usize>,
+    roi: Option<(u32, u32, u32, u32)>,
+) -> PyResult<Vec<PyRXingResult>> {
+    let pixels = luma_buffer_pixels(py, &luma_data, width, height, stride)?;
+    if (width * height) as usize != pixels.len() {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "Pixel data length does not match width * height.",
+        ));
+    }
+    let binarizer = BinarizerKind::parse(binarizer)?;
+    let options = roi_options(roi, width, height)?;
+    let hints = extract_decode_hints(hints_dict)?;
+
+    let mut candidates = py.allow_threads(|| {
+        let (pixels, width, height) = crop_luma(pixels, width, height, options.roi);
+        let luma_source = Luma8LuminanceSource::new(pixels, width, height);
+        match binarizer {
+            BinarizerKind::Hybrid => one_d_candidates(
+                &BinaryBitmap::new(HybridBinarizer::new(luma_source)),
+                &hints,
+            ),
+            BinarizerKind::Global => one_d_candidates(
+                &BinaryBitmap::new(GlobalHistogramBinarizer::new(luma_source)),
+                &hints,
+            ),
+            BinarizerKind::Threshold(method) => one_d_candidates(
+                &BinaryBitmap::new(threshold_binarizer(&luma_source, method)),
+                &hints,
+            ),
+        }
+    });
+    for (result, _) in &mut candidates {
+        options.unpad_points(result);
+    }
+    Ok(
+        drop_itf_check_failures(py, candidates, &hints, |(result, _)| result)?
+            .into_iter()
+            .map(|(result, votes)| {
+                let mut py_result = PyRXingResult::from(result).with_decode_hints(&hints);
+                py_result.scan_lines = Some(votes);
+                py_result
+            })
+            .collect(),
+    )
+}
+
+// 返回解码前二值化得到的黑白矩阵, 置位为黑, 用于判断读不出是二值化还是定位的问题.
+// rxing 的二值化器不读取解码提示; 这里仍解析 hints_dict, 以便与解码调用使用相同的参数
+#[pyfunction]
+#[pyo3(signature = (luma_data, width, height, binarizer="hybrid", hints_dict=None, stride=None))]
+fn binarize_luma_pixels(
+    py: Python,
+    luma_data: PyBuffer<u8>,
+    width: u32,
+    height: u32,
+    binarizer: &str,
+    hints_dict: Option<&Bound<PyAny>>,
+    stride: Option<usize>,
+) -> PyResult<PyBitMatrix> {
+    let pixels = luma_buffer_pixels(py, &luma_data, width, height, stride)?;
+    if (width * height) as usize != pixels.len() {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "Pixel data length does not match width * height.",
+        ));
+    }
+    let binarizer = BinarizerKind::parse(binarizer)?;
+    extract_decode_hints(hints_dict)?;
+
+    py.allow_threads(|| binarizer.black_matrix(Luma8LuminanceSource::new(pixels, width, height)))
+        .map(PyBitMatrix::from)
+        .map_err(|e| binarization_error(py, e))
+}
+
+// detect 找到的一个候选符号: 只经过定位, 没有解码, 因此格式只是推测, 也可能是误检
+#[pyclass(name = "Detection")]
+#[derive(Clone)]
+struct PyDetection {
+    #[pyo3(get)]
+    format: String,
+    // 与 RXingResult.corners 相同的顺序
+    #[pyo3(get)]
+    corners: Vec<(f32, f32)>,
+}
+
+#[pymethods]
+impl PyDetection {
+    fn __repr__(&self) -> String {
+        let corners: Vec<String> = self
+            .corners
+            .iter()
+            .map(|(x, y)| format!("({}, {})", x, y))
+            .collect();
+        format!(
+            "Detection(format={:?}, corners=[{}])",
+            self.format,
+            corners.join(", ")
+        )
+    }
+}
+
+// 只运行二维码的定位器, 不解码; formats 为 None 时尝试 detect::DETECTABLE_FORMATS 中的全部格式
+#[pyfunction]
+#[pyo3(signature = (luma_data, width, height, formats=None, binarizer="hybrid", stride=None))]
+fn detect_luma_pixels(
+    py: Python,
+    luma_data: PyBuffer<u8>,
+    width: u32,
+    height: u32,
+    formats: Option<Vec<String>>,
+    binarizer: &str,
+    stride: Option<usize>,
+) -> PyResult<Vec<PyDetection>> {
+    let pixels = luma_buffer_pixels(py, &luma_data, width, height, stride)?;
+    if (width * height) as usize != pixels.len() {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "Pixel data length does not match width * height.",
+        ));
+    }
+    let formats = match formats {
+        None => detect::DETECTABLE_FORMATS.to_vec(),
+        Some(names) if names.is_empty() => {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "formats must contain at least one format.",
+            ))
+        }
+        Some(names) => names
+            .iter()
+            .map(|name| {
+                let format = parse_barcode_format(name)?;
+                if detect::DETECTABLE_FORMATS.contains(&format) {
+                    Ok(format)
+                } else {
+                    Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                        "{} has no detector; detect() supports QR_CODE, DATA_MATRIX, AZTEC \
+                         and PDF_417. 1D codes are only found by decoding them.",
+                        name
+                    )))
+                }
+            })
+            .collect::<PyResult<_>>()?,
+    };
+    let binarizer = BinarizerKind::parse(binarizer)?;
+
+    let detected = py.allow_threads(|| {
+        let matrix = binarizer.black_matrix(Luma8LuminanceSource::new(pixels, width, height))?;
+        Ok::<_, rxing::Exceptions>(detect::detect_symbols(&matrix, &formats))
+    });
+    // 直方图过于平坦, 无法二值化的图像中没有可定位的符号
+    let detected = match detected {
+        Ok(detected) => detected,
+        Err(rxing::Exceptions::NotFoundException(_)) => Vec::new(),
+        Err(e) => return Err(binarization_error(py, e)),
+    };
+    Ok(detected
+        .into_iter()
+        .map(|(format, corners)| PyDetection {
+            format: format.to_string(),
+            corners: clockwise_corners(corners.to_vec()),
+        })
+        .collect())
+}
+
+fn binarization_error(py: Python, e: rxing::Exceptions) -> PyErr {
+    let message = format!("Binarization failed: {}", e);
+    errors::reader_error(py, message, &e)
+}
+
+// 8 位灰度图, 用于自定义的预处理流程. crop / rotate / invert 返回新的对象, 原对象不变
+#[pyclass(name = "LuminanceSource")]
+#[derive(Clone)]
+struct PyLuminanceSource {
+    #[pyo3(get)]
+    width: u32,
+    #[pyo3(get)]
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+#[pymethods]
+impl PyLuminanceSource {
+    // 缓冲区规则与 decode_luma_pixels 相同
+    #[new]
+    #[pyo3(signature = (luma_data, width, height, stride=None))]
+    fn new(
+        py: Python,
+        luma_data: PyBuffer<u8>,
+        width: u32,
+        height: u32,
+        stride: Option<usize>,
+    ) -> PyResult<Self> {
+        let expected = pixel_count(width, height)?;
+        let pixels = luma_buffer_pixels(py, &luma_data, width, height, stride)?;
+        if pixels.len() != expected {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "Pixel data length does not match width * height.",
+            ));
+        }
+        Ok(PyLuminanceSource {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    fn crop(&self, left: u32, top: u32, width: u32, height: u32) -> PyResult<Self> {
+        check_crop((left, top, width, height), self.width, self.height)?;
+        let (pixels, width, height) = crop_luma(
+            self.pixels.clone(),
+            self.width,
+            self.height,
+            Some((left, top, width, height)),
+        );
+        Ok(PyLuminanceSource {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    // 顺时针旋转, 与 decode 的 rotations 方向相同
+    fn rotate(&self, degrees: i32) -> PyResult<Self> {
+        let turns = quarter_turns(degrees)?;
+        let (width, height) = (self.width as usize, self.height as usize);
+        let pixels: Vec<u8> = match turns {
+            0 => self.pixels.clone(),
+            1 => (0..width)
+                .flat_map(|y| (0..height).map(move |x| (height - 1 - x) * width + y))
+                .map(|i| self.pixels[i])
+                .collect(),
+            2 => self.pixels.iter().rev().copied().collect(),
+            _ => (0..width)
+                .flat_map(|y| (0..height).map(move |x| x * width + (width - 1 - y)))
+                .map(|i| self.pixels[i])
+                .collect(),
+        };
+        let (width, height) = match turns % 2 {
+            0 => (self.width, self.height),
+            _ => (self.height, self.width),
+        };
+        Ok(PyLuminanceSource {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    fn invert(&self) -> Self {
+        PyLuminanceSource {
+            width: self.width,
+            height: self.height,
+            pixels: self.pixels.iter().map(|v| u8::MAX - v).collect(),
+        }
+    }
+
+    fn to_bytes<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.pixels)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "LuminanceSource(width={}, height={})",
+            self.width, self.height
+        )
+    }
+}
+
+// 二值化一次后保存的黑白矩阵, 可以多次解码或检查而不再重复二值化
+#[pyclass(name = "BinaryBitmap")]
+#[derive(Clone)]
+struct PyBinaryBitmap {
+    binarizer: BinarizerKind,
+    matrix: rxing::common::BitMatrix,
+}
+
+#[pymethods]
+impl PyBinaryBitmap {
+    #[new]
+    #[pyo3(signature = (source, binarizer="hybrid"))]
+    fn new(py: Python, source: &PyLuminanceSource, binarizer: &str) -> PyResult<Self> {
+        let binarizer = BinarizerKind::parse(binarizer)?;
+        let luma_source =
+            Luma8LuminanceSource::new(source.pixels.clone(), source.width, source.height);
+        let matrix = py
+            .allow_threads(|| binarizer.black_matrix(luma_source))
+            .map_err(|e| binarization_error(py, e))?;
+        Ok(PyBinaryBitmap { binarizer, matrix })
+    }
+
+    #[getter]
+    fn width(&self) -> u32 {
+        self.matrix.getWidth()
+    }
+
+    #[getter]
+    fn height(&self) -> u32 {
+        self.matrix.getHeight()
+    }
+
+    #[getter]
+    fn binarizer(&self) -> String {
+        self.binarizer.name()
+    }
+
+    fn crop(&self, left: u32, top: u32, width: u32, height: u32) -> PyResult<Self> {
+        check_crop((left, top, width, height), self.width(), self.height())?;
+        let mut matrix = rxing::common::BitMatrix::new(width, height)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
+        for y in 0..height {
+            for x in 0..width {
+                if self.matrix.get(left + x, top + y) {
+                    matrix.set(x, y);
+                }
+            }
+        }
+        Ok(PyBinaryBitmap {
+            binarizer: self.binarizer,
+            matrix,
+        })
+    }
+
+    // 顺时针旋转; rxing 的 BitMatrix::rotate 为逆时针
+    fn rotate(&self, degrees: i32) -> PyResult<Self> {
+        let mut matrix = self.matrix.clone();
+        matrix
+            .rotate((4 - quarter_turns(degrees)?) % 4 * 90)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
+        Ok(PyBinaryBitmap {
+            binarizer: self.binarizer,
+            matrix,
+        })
+    }
+
+    fn invert(&self) -> Self {
+        let mut matrix = self.matrix.clone();
+        matrix.flip_self();
+        PyBinaryBitmap {
+            binarizer: self.binarizer,
+            matrix,
+        }
+    }
+
+    fn get_black_matrix(&self) -> PyBitMatrix {
+        PyBitMatrix::from(self.matrix.clone())
+    }
+
+    // 与 decode_from_bit_matrix 相同: 矩阵原样交给读码器
+    #[pyo3(signature = (hints_dict=None, timeout_ms=None, collect_points=true))]
+    fn decode(
+        &self,
+        py: Python,
+        hints_dict: Option<&Bound<PyAny>>,
+        timeout_ms: Option<u64>,
+        collect_points: bool,
+    ) -> PyResult<PyRXingResult> {
+        decode_bits(
+            py,
+            self.matrix.clone(),
+            hints_dict,
+            timeout_ms,
+            collect_points,
+        )
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "BinaryBitmap(width={}, height={}, binarizer={:?})",
+            self.width(),
+            self.height(),
+            self.binarizer.name()
+        )
+    }
+}
+
+fn check_crop(
+    (left, top, width, height): (u32, u32, u32, u32),
+    image_width: u32,
+    image_height: u32,
+) -> PyResult<()> {
+    if width == 0 || height == 0 {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "crop width and height must be at least 1.",
+        ));
+    }
+    let fits =
+        |start: u32, size: u32, limit: u32| start.checked_add(size).is_some_and(|end| end <= limit);
+    if fits(left, width, image_width) && fits(top, height, image_height) {
+        Ok(())
+    } else {
+        Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "crop {:?} does not fit in the {}x{} image.",
+            (left, top, width, height),
+            image_width,
+            image_height
+        )))
+    }
+}
+
+// 角度须为 90 的倍数, 可以为负; 返回顺时针旋转的 1/4 圈数 (0..4)
+fn quarter_turns(degrees: i32) -> PyResult<u32> {
+    if degrees % 90 != 0 {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "degrees must be a multiple of 90, got {}.",
+            degrees
+        )));
+    }
+    Ok((degrees / 90).rem_euclid(4) as u32)
+}
+
+fn one_d_candidates<B: Binarizer>(
+    binary_bitmap: &BinaryBitmap<B>,
+    hints: &DecodeHints,
+) -> Vec<(InnerRXingResult, usize)> {
+    let mut readers: Vec<(MultiFormatOneDReader, RxingDecodeHints)> = ONE_D_FORMATS
+        .iter()
+        .filter(|format| {
+            hints
+                .PossibleFormats
+                .as_ref()
+                .is_none_or(|formats| formats.contains(format))
+        })
+        .map(|format| {
+            let mut format_hints = hints.rxing.clone();
+            format_hints.PossibleFormats = Some(HashSet::from([*format]));
+            (MultiFormatOneDReader::new(&format_hints), format_hints)
+        })
+        .collect();
+
+    let (width, height) = (binary_bitmap.get_width(), binary_bitmap.get_height());
+    let middle = height / 2;
+    let mut rows: Vec<usize> = (0..height).step_by((height >> 8).max(1)).collect();
+    rows.sort_by_key(|y| y.abs_diff(middle));
+
+    let mut candidates: Vec<(InnerRXingResult, usize)> = Vec::new();
+    for y in rows {
+        let Ok(row) = binary_bitmap.get_black_row(y) else {
+            continue;
+        };
+        let mut reversed = row.clone().into_owned();
+        reversed.reverse();
+        for (reader, format_hints) in readers.iter_mut() {
+            for (line, upside_down) in [(&*row, false), (&reversed, true)] {
+                let Ok(mut result) = reader.decode_row(y as u32, line, format_hints) else {
+                    continue;
+                };
+                if !has_allowed_length(&result, hints) {
+                    continue;
+                }
+                if upside_down {
+                    for point in result.getPointsMut().iter_mut().take(2) {
+                        point.x = width as f32 - point.x - 1.0;
+                    }
+                }
+                match candidates
+                    .iter_mut()
+                    .find(|(candidate, _)| candidate.getText() == result.getText())
+                {
+                    Some((_, votes)) => *votes += 1,
+                    None => candidates.push((result, 1)),
+                }
+            }
+        }
+    }
+    // sort_by_key 是稳定排序, 票数相同时保持扫描顺序
+    candidates.sort_by_key(|candidate| std::cmp::Reverse(candidate.1));
+    candidates
+}
+
+// 所有解码路径共用的 MultiFormatReader 调用.
+// rxing 只在 ITF 中使用 ALLOWED_LENGTHS, 这里对其它一维码按 text 长度补充过滤
+fn decode_bitmap<B: Binarizer>(
+    binary_bitmap: &mut BinaryBitmap<B>,
+    hints: &DecodeHints,
+) -> Result<Decoded, rxing::Exceptions> {
+    let mut reader = MultiFormatReader::default();
+    reader.set_hints(hints);
+    decode_bitmap_with_state(&mut reader, binary_bitmap, hints)
+}
+
+// 复用已 set_hints 的 reader, 连续解码时不必每次重建各格式的 reader
+fn decode_bitmap_with_state<B: Binarizer>(
+    reader: &mut MultiFormatReader,
+    binary_bitmap: &mut BinaryBitmap<B>,
+    hints: &DecodeHints,
+) -> Result<Decoded, rxing::Exceptions> {
+    // decode_with_state 在未指定 POSSIBLE_FORMATS 时会重置为默认提示, 这里直接调用 decode_internal
+    let result = match reader.decode_internal(binary_bitmap) {
+        Err(e @ rxing::Exceptions::NotFoundException(_)) => {
+            return Err(code_39_check_digit_error(binary_bitmap, hints).unwrap_or(e));
+        }
+        result => result?,
+    };
+    if !has_allowed_length(&result, hints) {
+        return Err(rxing::Exceptions::NOT_FOUND);
+    }
+    if !has_valid_itf_check_digit(&result, hints) {
+        return Err(rxing::Exceptions::checksum_with(format!(
+            "ITF check digit does not match {:?}",
+            result.getText()
+        )));
+    }
+    let mut matrix = symbol::MatrixCopy::new(binary_bitmap.get_black_matrix());
+    Ok(Decoded::new(result, &mut matrix))
+}
+
+// ASSUME_ITF_CHECK_DIGIT: ITF 的最后一位是 GS1 的 mod-10 校验位 (ITF-14 等), 校验位保留在 text 中
+fn has_valid_itf_check_digit(result: &InnerRXingResult, hints: &DecodeHints) -> bool {
+    if *result.getBarcodeFormat() != BarcodeFormat::ITF
+        || hints.assume_itf_check_digit != Some(true)
+    {
+        return true;
+    }
+    let text = result.getText();
+    match text.len().checked_sub(1) {
+        Some(body) if body > 0 && text.bytes().all(|b| b.is_ascii_digit()) => {
+            text.as_bytes()[body] == encodable::check_digit(&text[..body])
+        }
+        _ => false,
+    }
+}
+
+// ASSUME_CODE_39_CHECK_DIGIT: rxing 去掉正确的 mod-43 校验位, 校验位错误时该行读不出,
+// 最终只报 NotFoundException. 这里不带校验位重读一次 Code 39, 读出则说明校验位错误
+fn code_39_check_digit_error<B: Binarizer>(
+    binary_bitmap: &mut BinaryBitmap<B>,
+    hints: &DecodeHints,
+) -> Option<rxing::Exceptions> {
+    let code_39_possible = hints
+        .PossibleFormats
+        .as_ref()
+        .is_none_or(|formats| formats.contains(&BarcodeFormat::CODE_39));
+    if hints.AssumeCode39CheckDigit != Some(true) || !code_39_possible {
+        return None;
+    }
+    let retry_hints = RxingDecodeHints {
+        PossibleFormats: Some(HashSet::from([BarcodeFormat::CODE_39])),
+        AssumeCode39CheckDigit: Some(false),
+        ..hints.rxing.clone()
+    };
+    let mut reader = MultiFormatReader::default();
+    reader.set_hints(&retry_hints);
+    let result = reader.decode_internal(binary_bitmap).ok()?;
+    Some(rxing::Exceptions::checksum_with(format!(
+        "Code 39 check digit does not match {:?}",
+        result.getText()
+    )))
+}
+
+// 多码与候选结果中校验位不符的 ITF 读数不返回, 但以 UserWarning 列出, 不会悄悄丢失.
+// 要在取得 GIL 后调用; 警告过滤器设为 "error" 时返回异常
+fn drop_itf_check_failures<T>(
+    py: Python,
+    results: Vec<T>,
+    hints: &DecodeHints,
+    result: impl Fn(&T) -> &InnerRXingResult,
+) -> PyResult<Vec<T>> {
+    let (kept, failed): (Vec<T>, Vec<T>) = results
+        .into_iter()
+        .partition(|item| has_valid_itf_check_digit(result(item), hints));
+    if failed.is_empty() {
+        return Ok(kept);
+    }
+    let texts: Vec<String> = failed
+        .iter()
+        .map(|item| format!("{:?}", result(item).getText()))
+        .collect();
+    let message = std::ffi::CString::new(format!(
+        "ASSUME_ITF_CHECK_DIGIT: dropped {} ITF read(s) whose check digit does not match: {}",
+        texts.len(),
+        texts.join(", ")
+    ))?;
+    PyErr::warn(
+        py,
+        &py.get_type::<pyo3::exceptions::PyUserWarning>(),
+        &message,
+        1,
+    )?;
+    Ok(kept)
+}
+
+fn has_allowed_length(result: &InnerRXingResult, hints: &DecodeHints) -> bool {
+    match &hints.AllowedLengths {
+        Some(lengths) if ONE_D_FORMATS.contains(result.getBarcodeFormat()) => {
+            lengths.contains(&(result.getText().chars().count() as u32))
+        }
+        _ => true,
+    }
+}
+
+// 多码解码: 找不到任何条码时返回空列表而不是报错
+fn decode_multiple_bitmap<B: Binarizer>(
+    binary_bitmap: &mut BinaryBitmap<B>,
+    hints: &DecodeHints,
+) -> Result<Vec<Decoded>, rxing::Exceptions> {
+    let mut reader = GenericMultipleBarcodeReader::new(MultiUseMultiFormatReader::default());
+    match reader.decode_multiple_with_hints(binary_bitmap, hints) {
+        Ok(results) => {
+            let mut matrix = symbol::MatrixCopy::new(binary_bitmap.get_black_matrix());
+            Ok(results
+                .into_iter()
+                .filter(|result| has_allowed_length(result, hints))
+                .map(|result| Decoded::new(result, &mut matrix))
+                .collect())
+        }
+        Err(rxing::Exceptions::NotFoundException(_)) => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+// 多码结果的排序方式
+#[derive(Clone, Copy, PartialEq)]
+enum ResultOrder {
+    Detector,
+    ReadingOrder,
+}
+
+impl ResultOrder {
+    fn parse(sort: &str) -> PyResult<Self> {
+        match sort {
+            "none" => Ok(ResultOrder::Detector),
+            "reading_order" => Ok(ResultOrder::ReadingOrder),
+            _ => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Unknown sort {:?}; expected \"reading_order\" or \"none\".",
+                sort
+            ))),
+        }
+    }
+}
+
+fn result_centroid(result: &InnerRXingResult) -> Option<(f32, f32)> {
+    let points = result.getPoints();
+    if points.is_empty() {
+        return None;
+    }
+    let count = points.len() as f32;
+    let x = points.iter().map(|p| p.x).sum::<f32>() / count;
+    let y = points.iter().map(|p| p.y).sum::<f32>() / count;
+    Some((x, y))
+}
+
+// 按 result_points 的中心点排成阅读顺序: 先自上而下分行, 中心纵坐标与行首相差不超过
+// row_tolerance 像素的结果视为同一行, 行内再自左向右; 没有坐标点的结果保持原顺序排在最后
+fn sort_reading_order(results: Vec<Decoded>, row_tolerance: f32) -> Vec<Decoded> {
+    let (mut located, unlocated): (Vec<_>, Vec<_>) = results
+        .into_iter()
+        .map(|decoded| (result_centroid(&decoded.result), decoded))
+        .partition(|(centroid, _)| centroid.is_some());
+    located.sort_by(|(a, _), (b, _)| a.unwrap().1.total_cmp(&b.unwrap().1));
+
+    let mut sorted = Vec::with_capacity(located.len() + unlocated.len());
+    let mut rows = located.into_iter().peekable();
+    while let Some((centroid, result)) = rows.next() {
+        let row_top = centroid.unwrap().1;
+        let mut row = vec![(centroid.unwrap().0, result)];
+        while let Some((next, _)) = rows.peek() {
+            if next.unwrap().1 - row_top > row_tolerance {
+                break;
+            }
+            let (next, result) = rows.next().unwrap();
+            row.push((next.unwrap().0, result));
+        }
+        row.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        sorted.extend(row.into_iter().map(|(_, result)| result));
+    }
+    sorted.extend(unlocated.into_iter().map(|(_, result)| result));
+    sorted
+}
+
+fn order_results(results: Vec<Decoded>, order: ResultOrder, row_tolerance: f32) -> Vec<Decoded> {
+    match order {
+        ResultOrder::Detector => results,
+        ResultOrder::ReadingOrder => sort_reading_order(results, row_tolerance),
+    }
+}
+
+fn validate_row_tolerance(row_tolerance: f32) -> PyResult<()> {
+    if row_tolerance.is_finite() && row_tolerance >= 0.0 {
+        Ok(())
+    } else {
+        Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "row_tolerance must be a non-negative number of pixels, got {}.",
+            row_tolerance
+        )))
+    }
+}
+
+// 按顺时针角度旋转图像; 90 的整数倍使用无损旋转, 其它角度绕中心旋转并保持尺寸
+#[cfg(feature = "image")]
+fn rotate_image(img: &image::DynamicImage, degrees: f32) -> image::DynamicImage {
+    let normalized = degrees.rem_euclid(360.0);
+    if normalized == 90.0 {
+        img.rotate90()
+    } else if normalized == 180.0 {
+        img.rotate180()
+    } else if normalized == 270.0 {
+        img.rotate270()
+    } else {
+        image::DynamicImage::ImageRgba8(imageproc::geometric_transformations::rotate_about_center(
+            &img.to_rgba8(),
+            normalized.to_radians(),
+            imageproc::geometric_transformations::Interpolation::Bilinear,
+            image::Rgba([u8::MAX; 4]),
+        ))
+    }
+}
+
+// 把旋转后图像中的点映射回旋转前的坐标, size 为旋转前的尺寸
+#[cfg(feature = "image")]
+fn unrotate_point(point: &mut rxing::Point, degrees: f32, size: (u32, u32)) {
+    let (width, height) = (size.0 as f32, size.1 as f32);
+    let (x, y) = (point.x, point.y);
+    let normalized = degrees.rem_euclid(360.0);
+    let (orig_x, orig_y) = if normalized == 0.0 {
+        (x, y)
+    } else if normalized == 90.0 {
+        (y, height - x)
+    } else if normalized == 180.0 {
+        (width - x, height - y)
+    } else if normalized == 270.0 {
+        (width - y, x)
+    } else {
+        let (sin, cos) = normalized.to_radians().sin_cos();
+        let (cx, cy) = (width / 2.0, height / 2.0);
+        (
+            cx + (x - cx) * cos + (y - cy) * sin,
+            cy - (x - cx) * sin + (y - cy) * cos,
+        )
+    };
+    point.x = orig_x;
+    point.y = orig_y;
+}
+
+// 在缩放后的图像上解码, 并把 result_points 映射回 target_size 的坐标空间
+#[cfg(feature = "image")]
+fn decode_scaled_image(
+    scaled_image: image::DynamicImage,
+    target_size: (u32, u32),
+    hints: &DecodeHints,
+    binarizer: BinarizerKind,
+) -> Result<Decoded, rxing::Exceptions> {
+    let x_ratio = target_size.0 as f32 / scaled_image.width() as f32;
+    let y_ratio = target_size.1 as f32 / scaled_image.height() as f32;
+
+    let luma_source = BufferedImageLuminanceSource::new(scaled_image);
+    let mut decoded = binarizer.decode(luma_source, hints)?;
+    if x_ratio != 1.0 || y_ratio != 1.0 {
+        for point in decoded.result.getPointsMut() {
+            point.x *= x_ratio;
+            point.y *= y_ratio;
+        }
+    }
+    Ok(decoded)
+}
+
+// 依次尝试 rotations x try_scales, 返回第一个成功的结果及其旋转角度;
+// result_points 始终映射回原始图像的坐标
+#[cfg(feature = "image")]
+fn decode_dynamic_image(
+    dynamic_image: image::DynamicImage,
+    hints: &DecodeHints,
+    options: &ImageDecodeOptions,
+) -> Result<(Decoded, f32), rxing::Exceptions> {
+    let dynamic_image = options.crop_image(dynamic_image)?;
+    let full_image = pad_image(dynamic_image, options.padding)?;
+    let full_size = (full_image.width(), full_image.height());
+    let downscaled = match options.max_dimension {
+        Some(max_dim) if full_size.0.max(full_size.1) > max_dim => {
+            Some(full_image.resize(max_dim, max_dim, image::imageops::FilterType::Triangle))
+        }
+        _ => None,
+    };
+    let mut result = decode_base_image(
+        downscaled.as_ref().unwrap_or(&full_image),
+        full_size,
+        hints,
+        options,
+    );
+    // 缩小后读不出时再用原始分辨率试一次
+    if result.is_err() && downscaled.is_some() && options.retry_full_resolution {
+        result = decode_base_image(&full_image, full_size, hints, options);
+    }
+    result.map(|(mut decoded, degrees)| {
+        options.unpad_points(&mut decoded.result);
+        (decoded, degrees)
+    })
+}
+
+#[cfg(feature = "image")]
+fn decode_base_image(
+    base_image: &image::DynamicImage,
+    full_size: (u32, u32),
+    hints: &DecodeHints,
+    options: &ImageDecodeOptions,
+) -> Result<(Decoded, f32), rxing::Exceptions> {
+    let base_size = (base_image.width(), base_image.height());
+    let rotations = options.rotations.as_deref().unwrap_or(&[0.0]);
+    let scales = options.try_scales.as_deref().unwrap_or(&[1.0]);
+    let mut last_result = Err(rxing::Exceptions::NOT_FOUND);
+    for &degrees in rotations {
+        let rotated_image = if degrees.rem_euclid(360.0) == 0.0 {
+            None
+        } else {
+            Some(rotate_image(base_image, degrees))
+        };
+        let rotated = rotated_image.as_ref().unwrap_or(base_image);
+        let rotated_size = (rotated.width(), rotated.height());
+        for &scale in scales {
+            let scaled_image = if scale == 1.0 {
+                rotated.clone()
+            } else {
+                let width = ((rotated_size.0 as f32 * scale).round() as u32).max(1);
+                let height = ((rotated_size.1 as f32 * scale).round() as u32).max(1);
+                rotated.resize_exact(width, height, image::imageops::FilterType::Triangle)
+            };
+            last_result = decode_scaled_image(scaled_image, rotated_size, hints, options.binarizer);
+            if let Ok(decoded) = last_result.as_mut() {
+                let x_ratio = full_size.0 as f32 / base_size.0 as f32;
+                let y_ratio = full_size.1 as f32 / base_size.1 as f32;
+                for point in decoded.result.getPointsMut() {
+                    unrotate_point(point, degrees, base_size);
+                    point.x *= x_ratio;
+                    point.y *= y_ratio;
+                }
+                return last_result.map(|result| (result, degrees));
+            }
+        }
+    }
+    last_result.map(|result| (result, 0.0))
+}
+
+#[cfg(feature = "image")]
+#[pyfunction]
+#[pyo3(signature = (image_file_bytes, hints_dict=None, max_dimension=None, try_scales=None, rotations=None, timeout_ms=None, binarizer="hybrid", padding=0, apply_exif_orientation=true, collect_points=true, roi=None, retry_full_resolution=true))]
+#[allow(clippy::too_many_arguments)]
+fn decode_image_bytes(
+    py: Python,
+    image_file_bytes: &[u8],
+    hints_dict: Option<&Bound<PyAny>>,
+    max_dimension: Option<u32>,
+    try_scales: Option<Vec<f32>>,
+    rotations: Option<Vec<f32>>,
+    timeout_ms: Option<u64>,
+    binarizer: &str,
+    padding: i64,
+    apply_exif_orientation: bool,
+    collect_points: bool,
+    roi: Option<(u32, u32, u32, u32)>,
+    retry_full_resolution: bool,
+) -> PyResult<PyRXingResult> {
+    let options = ImageDecodeOptions {
+        roi,
+        max_dimension,
+        retry_full_resolution,
+        try_scales,
+        rotations,
+        binarizer: BinarizerKind::parse(binarizer)?,
+        padding: parse_padding(padding)?,
+        skip_points: !collect_points,
+    };
+    options.validate()?;
+    let hints = extract_decode_hints(hints_dict)?;
+    let pure_barcode = hints.PureBarcode == Some(true);
+
+    // 图像加载与解码期间释放 GIL, 便于在线程池中并发调用
+    let image_file_bytes = image_file_bytes.to_vec();
+    let decoded = run_decode(py, timeout_ms, move || {
+        load_image_bytes(&image_file_bytes, apply_exif_orientation).map(|dynamic_image| {
+            decode_dynamic_image(dynamic_image, &hints, &options)
+                .map(|(result, rotation)| options.to_py_result(result, rotation, &hints))
+        })
+    })?;
+    match decoded {
+        Ok(decoded) => match decoded {
+            Ok(result) => Ok(result),
+            Err(e) => Err(decode_error(py, "RXing decoding failed", e, pure_barcode)),
+        },
+        Err(e) => Err(PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
+            "Failed to load image from bytes: {:?}",
+            e
+        ))),
+    }
+}
+
+#[cfg(feature = "image")]
+#[pyfunction]
+#[pyo3(signature = (file_path, hints_dict=None, max_dimension=None, try_scales=None, rotations=None, timeout_ms=None, binarizer="hybrid", padding=0, apply_exif_orientation=true, collect_points=true, roi=None, retry_full_resolution=true))]
+#[allow(clippy::too_many_arguments)]
+fn decode_from_file_path(
+    py: Python,
+    file_path: &Bound<PyAny>,
+    hints_dict: Option<&Bound<PyAny>>,
+    max_dimension: Option<u32>,
+    try_scales: Option<Vec<f32>>,
+    rotations: Option<Vec<f32>>,
+    timeout_ms: Option<u64>,
+    binarizer: &str,
+    padding: i64,
+    apply_exif_orientation: bool,
+    collect_points: bool,
+    roi: Option<(u32, u32, u32, u32)>,
+    retry_full_resolution: bool,
+) -> PyResult<PyRXingResult> {
+    let path = extract_path(file_path)?;
+    if let Err(e) = std::fs::metadata(&path) {
+        return Err(file_io_error(&path, e));
+    }
+
+    let options = ImageDecodeOptions {
+        roi,
+        max_dimension,
+        retry_full_resolution,
+        try_scales,
+        rotations,
+        binarizer: BinarizerKind::parse(binarizer)?,
+        padding: parse_padding(padding)?,
+        skip_points: !collect_points,
+    };
+    options.validate()?;
+    let hints = extract_decode_hints(hints_dict)?;
+    let pure_barcode = hints.PureBarcode == Some(true);
+
+    let image_path = path.clone();
+    let decoded = run_decode(py, timeout_ms, move || {
+        open_image(&image_path, apply_exif_orientation).map(|dynamic_image| {
+            decode_dynamic_image(dynamic_image, &hints, &options)
+                .map(|(result, rotation)| options.to_py_result(result, rotation, &hints))
+        })
+    })?;
+    match decoded {
+        Ok(decoded) => match decoded {
+            Ok(result) => Ok(result),
+            Err(e) => Err(decode_error(
+                py,
+                &format!("RXing decoding failed for file {}", path.display()),
+                e,
+                pure_barcode,
+            )),
+        },
+        Err(image::ImageError::IoError(e)) => Err(file_io_error(&path, e)),
+        Err(e) => Err(PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
+            "Failed to open or decode image file {}: {:?}",
+            path.display(),
+            e
+        ))),
+    }
+}
+
+// image::open / load_from_memory 不处理 EXIF 方向标记, 手机照片按存储方向解码时
+// 条码可能是横躺的; 这里按标记旋转或翻转, 与相册中看到的方向一致
+#[cfg(feature = "image")]
+fn decode_image_reader<R: std::io::BufRead + std::io::Seek>(
+    reader: image::ImageReader<R>,
+    apply_exif_orientation: bool,
+) -> image::ImageResult<image::DynamicImage> {
+    use image::ImageDecoder;
+    let mut decoder = reader.into_decoder()?;
+    let orientation = decoder.orientation()?;
+    let mut dynamic_image = image::DynamicImage::from_decoder(decoder)?;
+    if apply_exif_orientation {
+        dynamic_image.apply_orientation(orientation);
+    }
+    Ok(dynamic_image)
+}
+
+#[cfg(feature = "image")]
+fn open_image(
+    path: &std::path::Path,
+    apply_exif_orientation: bool,
+) -> image::ImageResult<image::DynamicImage> {
+    decode_image_reader(image::ImageReader::open(path)?, apply_exif_orientation)
+}
+
+#[cfg(feature = "image")]
+fn load_image_bytes(
+    bytes: &[u8],
+    apply_exif_orientation: bool,
+) -> image::ImageResult<image::DynamicImage> {
+    let reader = image::ImageReader::new(std::io::Cursor::new(bytes)).with_guessed_format()?;
+    decode_image_reader(reader, apply_exif_orientation)
+}
+
+// 路径可以是 str, bytes 或 os.PathLike. 经 os.fsdecode 转为 str (无法解码的字节以
+// surrogateescape 保留), 再由 pyo3 按文件系统编码还原, 非 UTF-8 文件名也不会损坏
+#[cfg(feature = "image")]
+fn extract_path(path: &Bound<PyAny>) -> PyResult<PathBuf> {
+    let os = path.py().import("os")?;
+    os.call_method1("fsdecode", (path,))?.extract()
+}
+
+// 按 io::ErrorKind 区分 "文件不存在" 与 "没有权限" 等, 对应 Python 的 OSError 子类
+#[cfg(feature = "image")]
+fn file_io_error(path: &std::path::Path, e: std::io::Error) -> PyErr {
+    match e.kind() {
+        std::io::ErrorKind::NotFound => PyErr::new::<pyo3::exceptions::PyFileNotFoundError, _>(
+            format!("File not found: {}", path.display()),
+        ),
+        std::io::ErrorKind::PermissionDenied => {
+            PyErr::new::<pyo3::exceptions::PyPermissionError, _>(format!(
+                "Permission denied: {}",
+                path.display()
+            ))
+        }
+        _ => PyErr::new::<pyo3::exceptions::PyOSError, _>(format!(
+            "Failed to read image file {}: {}",
+            path.display(),
+            e
+        )),
+    }
+}
+
+// decode_timed 的结果: 图像加载与解码分开计时 (微秒), 均在 Rust 中测量, 不含 Python 调用开销
+#[cfg(feature = "image")]
+#[pyclass(name = "DecodeTiming")]
+struct PyDecodeTiming {
+    // 没有找到条码时为 None
+    #[pyo3(get)]
+    result: Option<PyRXingResult>,
+    #[pyo3(get)]
+    load_us: u64,
+    #[pyo3(get)]
+    decode_us: u64,
+}
+
+#[cfg(feature = "image")]
+#[pymethods]
+impl PyDecodeTiming {
+    fn __repr__(&self) -> String {
+        let result = match &self.result {
+            Some(result) => format!("{:?}", result.text),
+            None => "None".to_string(),
+        };
+        format!(
+            "DecodeTiming(result={}, load_us={}, decode_us={})",
+            result, self.load_us, self.decode_us
+        )
+    }
+}
+
+// 加载计时包括读取文件与解码图像格式; 解码计时包括灰度转换, 二值化与条码识别
+#[cfg(feature = "image")]
+#[pyfunction]
+#[pyo3(signature = (source, hints=None, binarizer="hybrid", apply_exif_orientation=true))]
+fn decode_timed(
+    py: Python,
+    source: &Bound<PyAny>,
+    hints: Option<&Bound<PyAny>>,
+    binarizer: &str,
+    apply_exif_orientation: bool,
+) -> PyResult<PyDecodeTiming> {
+    let image_bytes = match source.downcast::<PyBytes>() {
+        Ok(bytes) => Some(bytes.as_bytes().to_vec()),
+        Err(_) => None,
+    };
+    let path = match image_bytes {
+        Some(_) => None,
+        None => Some(extract_path(source)?),
+    };
+    let options = ImageDecodeOptions {
+        binarizer: BinarizerKind::parse(binarizer)?,
+        ..Default::default()
+    };
+    let hints = extract_decode_hints(hints)?;
+
+    let image_path = path.clone();
+    let timed = py.allow_threads(move || {
+        let start = std::time::Instant::now();
+        let dynamic_image = match (&image_path, image_bytes) {
+            (Some(path), _) => open_image(path, apply_exif_orientation),
+            (None, bytes) => load_image_bytes(&bytes.unwrap_or_default(), apply_exif_orientation),
+        }?;
+        let load_us = start.elapsed().as_micros() as u64;
+        let start = std::time::Instant::now();
+        let decoded = decode_dynamic_image(dynamic_image, &hints, &options)
+            .map(|(result, rotation)| options.to_py_result(result, rotation, &hints));
+        Ok((decoded, load_us, start.elapsed().as_micros() as u64))
+    });
+    let (decoded, load_us, decode_us) = match (timed, &path) {
+        (Ok(timed), _) => timed,
+        (Err(image::ImageError::IoError(e)), Some(path)) => return Err(file_io_error(path, e)),
+        (Err(e), _) => {
+            return Err(PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
+                "Failed to load image: {:?}",
+                e
+            )))
+        }
+    };
+    // 与 VideoScanner 相同, 没有可识别的条码时返回 None, 便于在整个数据集上计时
+    let result = match decoded {
+        Ok(result) => Some(result),
+        Err(rxing::Exceptions::NotFoundException(_))
+        | Err(rxing::Exceptions::ChecksumException(_))
+        | Err(rxing::Exceptions::FormatException(_)) => None,
+        Err(e) => return Err(decode_error(py, "RXing decoding failed", e, false)),
+    };
+    Ok(PyDecodeTiming {
+        result,
+        load_us,
+        decode_us,
+    })
+}
+
+// decode_batch / scan_directory 的一项: 与 decode_timed 相同, bytes 为图像文件内容, 其它对象按路径处理
+#[cfg(feature = "image")]
+enum ImageSource {
+    Path(PathBuf),
+    Bytes(Vec<u8>),
+}
+
+#[cfg(feature = "image")]
+enum ImageSourceError {
+    Load(image::ImageError),
+    Decode(rxing::Exceptions),
+}
+
+#[cfg(feature = "image")]
+impl ImageSource {
+    fn extract(source: &Bound<PyAny>) -> PyResult<Self> {
+        match source.downcast::<PyBytes>() {
+            Ok(bytes) => Ok(ImageSource::Bytes(bytes.as_bytes().to_vec())),
+            Err(_) => extract_path(source).map(ImageSource::Path),
+        }
+    }
+
+    fn path(&self) -> Option<&std::path::Path> {
+        match self {
+            ImageSource::Path(path) => Some(path),
+            ImageSource::Bytes(_) => None,
+        }
+    }
+
+    // 不需要 GIL, 错误回到 Python 线程后再由 ImageSourceError::into_py_err 转换
+    fn decode(
+        &self,
+        hints: &DecodeHints,
+        options: &ImageDecodeOptions,
+    ) -> Result<PyRXingResult, ImageSourceError> {
+        let dynamic_image = match self {
+            ImageSource::Path(path) => open_image(path, true),
+            ImageSource::Bytes(bytes) => load_image_bytes(bytes, true),
+        }
+        .map_err(ImageSourceError::Load)?;
+        decode_dynamic_image(dynamic_image, hints, options)
+            .map(|(result, rotation)| options.to_py_result(result, rotation, hints))
+            .map_err(ImageSourceError::Decode)
+    }
+}
+
+#[cfg(feature = "image")]
+impl ImageSourceError {
+    // 与 decode_from_file_path / decode_image_bytes 抛出的异常相同
+    fn into_py_err(self, py: Python, path: Option<&std::path::Path>, pure_barcode: bool) -> PyErr {
+        match (self, path) {
+            (ImageSourceError::Load(image::ImageError::IoError(e)), Some(path)) => {
+                file_io_error(path, e)
+            }
+            (ImageSourceError::Load(e), Some(path)) => {
+                PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
+                    "Failed to open or decode image file {}: {:?}",
+                    path.display(),
+                    e
+                ))
+            }
+            (ImageSourceError::Load(e), None) => PyErr::new::<pyo3::exceptions::PyIOError, _>(
+                format!("Failed to load image from bytes: {:?}", e),
+            ),
+            (ImageSourceError::Decode(e), Some(path)) => decode_error(
+                py,
+                &format!("RXing decoding failed for file {}", path.display()),
+                e,
+                pure_barcode,
+            ),
+            (ImageSourceError::Decode(e), None) => {
+                decode_error(py, "RXing decoding failed", e, pure_barcode)
+            }
+        }
+    }
+}
+
+// decode_batch 中解码失败的条目; 与 EncodeFailure 相同为假值. error 为单独解码这一项时
+// 会抛出的异常 (NotFoundException, FileNotFoundError 等)
+#[cfg(feature = "image")]
+#[pyclass(name = "DecodeFailure")]
+struct PyDecodeFailure {
+    #[pyo3(get)]
+    index: usize,
+    #[pyo3(get)]
+    path: Option<PathBuf>,
+    #[pyo3(get)]
+    error: PyObject,
+    #[pyo3(get)]
+    message: String,
+}
+
+#[cfg(feature = "image")]
+#[pymethods]
+impl PyDecodeFailure {
+    fn __bool__(&self) -> bool {
+        false
+    }
+
+    fn __repr__(&self) -> String {
+        let path = match &self.path {
+            Some(path) => format!("{:?}", path.display().to_string()),
+            None => "None".to_string(),
+        };
+        format!(
+            "DecodeFailure(index={}, path={}, message={:?})",
+            self.index, path, self.message
+        )
+    }
+}
+
+// 批量解码, 结果与 items 一一对应; threads 与 encode_many 的 workers 相同.
+// 图像的加载与解码都在线程池中进行, 期间释放 GIL
+#[cfg(feature = "image")]
+#[pyfunction]
+#[pyo3(signature = (items, hints=None, threads=None, roi=None))]
+fn decode_batch(
+    py: Python,
+    items: Vec<Bound<PyAny>>,
+    hints: Option<&Bound<PyAny>>,
+    threads: Option<usize>,
+    roi: Option<(u32, u32, u32, u32)>,
+) -> PyResult<Vec<PyObject>> {
+    if threads == Some(0) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "threads must be at least 1, or None for one thread per CPU.",
+        ));
+    }
+    let sources = items
+        .iter()
+        .enumerate()
+        .map(|(index, item)| {
+            ImageSource::extract(item).map_err(|e| {
+                let err = PyErr::new::<pyo3::exceptions::PyTypeError, _>(format!(
+                    "Invalid item {}: expected a path or image file bytes",
+                    index
+                ));
+                err.set_cause(py, Some(e));
+                err
+            })
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+    let hints = extract_decode_hints(hints)?;
+    let pure_barcode = hints.PureBarcode == Some(true);
+    // 每张图像各自检查 roi, 放不下的条目成为 DecodeFailure
+    let options = ImageDecodeOptions {
+        roi,
+        ..Default::default()
+    };
+    options.validate()?;
+
+    let decode_all = || {
+        sources
+            .par_iter()
+            .map(|source| source.decode(&hints, &options))
+            .collect::<Vec<_>>()
+    };
+    let outputs = py
+        .allow_threads(|| run_in_pool(threads, decode_all))
+        .map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
+                "Failed to start {} decode threads: {}",
+                threads.unwrap_or_default(),
+                e
+            ))
+        })?;
+
+    sources
+        .iter()
+        .zip(outputs)
+        .enumerate()
+        .map(|(index, (source, output))| match output {
+            Ok(result) => Ok(Py::new(py, result)?.into_any()),
+            Err(e) => {
+                let error = e.into_py_err(py, source.path(), pure_barcode);
+                let failure = PyDecodeFailure {
+                    index,
+                    path: source.path().map(PathBuf::from),
+                    message: error.value(py).to_string(),
+                    error: error.into_value(py).into_any(),
+                };
+                Ok(Py::new(py, failure)?.into_any())
+            }
+        })
+        .collect()
+}
+
+// scan_directory 返回的迭代器: 每次 __next__ 只查找并解码下一个匹配的文件, 期间释放 GIL
+#[cfg(feature = "image")]
+#[pyclass(name = "DirectoryScanner")]
+struct PyDirectoryScanner {
+    walk: walk::DirectoryWalk,
+    hints: DecodeHints,
+    options: ImageDecodeOptions,
+}
+
+#[cfg(feature = "image")]
+#[pymethods]
+impl PyDirectoryScanner {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    // (路径, 结果或异常实例); 无法读取的子目录也以 (路径, OSError) 的形式给出
+    fn __next__(&mut self, py: Python) -> PyResult<Option<(PathBuf, PyObject)>> {
+        let pure_barcode = self.hints.PureBarcode == Some(true);
+        let (walk, hints, options) = (&mut self.walk, &self.hints, &self.options);
+        let next = py.allow_threads(move || {
+            walk.next().map(|entry| {
+                entry.map(|path| {
+                    let decoded = ImageSource::Path(path.clone()).decode(hints, options);
+                    (path, decoded)
+                })
+            })
+        });
+        let (path, value) = match next {
+            None => return Ok(None),
+            Some(Err((path, e))) => {
+                let value = PyErr::from(e).into_value(py).into_any();
+                (path, value)
+            }
+            Some(Ok((path, decoded))) => {
+                let value = match decoded {
+                    Ok(result) => Py::new(py, result)?.into_any(),
+                    Err(e) => e
+                        .into_py_err(py, Some(&path), pure_barcode)
+                        .into_value(py)
+                        .into_any(),
+                };
+                (path, value)
+            }
+        };
+        Ok(Some((path, value)))
+    }
+}
+
+// 遍历目录并逐个解码匹配 pattern 的文件, 文件名不经过 Python 列表; recursive 时包含子目录
+#[cfg(feature = "image")]
+#[pyfunction]
+#[pyo3(signature = (path, pattern="*.png", recursive=false, hints=None))]
+fn scan_directory(
+    path: &Bound<PyAny>,
+    pattern: &str,
+    recursive: bool,
+    hints: Option<&Bound<PyAny>>,
+) -> PyResult<PyDirectoryScanner> {
+    let root = extract_path(path)?;
+    if pattern.is_empty() || pattern.contains(std::path::is_separator) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "pattern must be a non-empty file name pattern without path separators, got {:?}",
+            pattern
+        )));
+    }
+    let metadata = std::fs::metadata(&root).map_err(|e| file_io_error(&root, e))?;
+    if !metadata.is_dir() {
+        return Err(PyErr::new::<pyo3::exceptions::PyNotADirectoryError, _>(
+            format!("Not a directory: {}", root.display()),
+        ));
+    }
+    Ok(PyDirectoryScanner {
+        walk: walk::DirectoryWalk::new(&root, pattern, recursive)?,
+        hints: extract_decode_hints(hints)?,
+        options: ImageDecodeOptions::default(),
+    })
+}
+
+// workers 为 None 时使用 rayon 的全局线程池 (默认每个 CPU 一个线程), 否则使用 workers 个线程的
+// 线程池. 每种大小的线程池在第一次使用时建立, 之后的调用复用它; 线程保留到进程结束
+fn run_in_pool<T: Send>(
+    workers: Option<usize>,
+    task: impl FnOnce() -> T + Send,
+) -> Result<T, rayon::ThreadPoolBuildError> {
+    static POOLS: std::sync::OnceLock<Mutex<HashMap<usize, Arc<rayon::ThreadPool>>>> =
+        std::sync::OnceLock::new();
+    let Some(workers) = workers else {
+        return Ok(task());
+    };
+    let pool = {
+        let mut pools = POOLS
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        match pools.get(&workers) {
+            Some(pool) => pool.clone(),
+            None => {
+                let pool = Arc::new(
+                    rayon::ThreadPoolBuilder::new()
+                        .num_threads(workers)
+                        .build()?,
+                );
+                pools.insert(workers, pool.clone());
+                pool
+            }
+        }
+    };
+    Ok(pool.install(task))
+}
+
+// 解码图像中的所有条码; sort="reading_order" 时按阅读顺序排列
+#[cfg(feature = "image")]
+#[pyfunction]
+#[pyo3(signature = (image_file_bytes, hints_dict=None, sort="none", row_tolerance=10.0, apply_exif_orientation=true, collect_points=true, roi=None))]
+#[allow(clippy::too_many_arguments)]
+fn decode_multiple_image_bytes(
+    py: Python,
+    image_file_bytes: &[u8],
+    hints_dict: Option<&Bound<PyAny>>,
+    sort: &str,
+    row_tolerance: f32,
+    apply_exif_orientation: bool,
+    collect_points: bool,
+    roi: Option<(u32, u32, u32, u32)>,
+) -> PyResult<Vec<PyRXingResult>> {
+    let order = ResultOrder::parse(sort)?;
+    validate_row_tolerance(row_tolerance)?;
+    let options = ImageDecodeOptions {
+        roi,
+        ..Default::default()
+    };
+    options.validate()?;
+    let hints = extract_decode_hints(hints_dict)?;
+    let pure_barcode = hints.PureBarcode == Some(true);
+
+    let decoded = py.allow_threads(|| {
+        load_image_bytes(image_file_bytes, apply_exif_orientation).map(|dynamic_image| {
+            let luma_source = BufferedImageLuminanceSource::new(options.crop_image(dynamic_image)?);
             let binarizer = HybridBinarizer::new(luma_source);
             let mut binary_bitmap = BinaryBitmap::new(binarizer);
-            let mut reader = MultiFormatReader::default();
+            decode_multiple_bitmap(&mut binary_bitmap, &hints)
+        })
+    });
+    let decoded = match decoded {
+        Ok(decoded) => {
+            decoded.map_err(|e| decode_error(py, "RXing decoding failed", e, pure_barcode))?
+        }
+        Err(e) => {
+            return Err(PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
+                "Failed to load image from bytes: {:?}",
+                e
+            )))
+        }
+    };
+    multiple_py_results(
+        py,
+        decoded,
+        &options,
+        order,
+        row_tolerance,
+        collect_points,
+        &hints,
+    )
+}
 
-            match reader.decode_with_hints(&mut binary_bitmap, &hints) {
-                Ok(result) => Ok(PyRXingResult::from(result)),
-                Err(e) => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-                    "RXing decoding failed: {:?}",
-                    e
-                ))),
-            }
+#[cfg(feature = "image")]
+#[pyfunction]
+#[pyo3(signature = (file_path, hints_dict=None, sort="none", row_tolerance=10.0, apply_exif_orientation=true, collect_points=true, roi=None))]
+#[allow(clippy::too_many_arguments)]
+fn decode_multiple_from_file_path(
+    py: Python,
+    file_path: &Bound<PyAny>,
+    hints_dict: Option<&Bound<PyAny>>,
+    sort: &str,
+    row_tolerance: f32,
+    apply_exif_orientation: bool,
+    collect_points: bool,
+    roi: Option<(u32, u32, u32, u32)>,
+) -> PyResult<Vec<PyRXingResult>> {
+    let path = extract_path(file_path)?;
+    let order = ResultOrder::parse(sort)?;
+    validate_row_tolerance(row_tolerance)?;
+    let options = ImageDecodeOptions {
+        roi,
+        ..Default::default()
+    };
+    options.validate()?;
+    let hints = extract_decode_hints(hints_dict)?;
+    let pure_barcode = hints.PureBarcode == Some(true);
+
+    let decoded = py.allow_threads(|| {
+        open_image(&path, apply_exif_orientation).map(|dynamic_image| {
+            let luma_source = BufferedImageLuminanceSource::new(options.crop_image(dynamic_image)?);
+            let mut binary_bitmap = BinaryBitmap::new(HybridBinarizer::new(luma_source));
+            decode_multiple_bitmap(&mut binary_bitmap, &hints)
+        })
+    });
+    let decoded = match decoded {
+        Ok(decoded) => decoded.map_err(|e| {
+            decode_error(
+                py,
+                &format!("RXing decoding failed for file {}", path.display()),
+                e,
+                pure_barcode,
+            )
+        })?,
+        Err(image::ImageError::IoError(e)) => return Err(file_io_error(&path, e)),
+        Err(e) => {
+            return Err(PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
+                "Failed to open or decode image file {}: {:?}",
+                path.display(),
+                e
+            )))
         }
-        Err(e) => Err(PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
-            "Failed to load image from bytes: {:?}",
-            e
-        ))),
+    };
+    multiple_py_results(
+        py,
+        decoded,
+        &options,
+        order,
+        row_tolerance,
+        collect_points,
+        &hints,
+    )
+}
+
+// 把坐标映射回 roi 之前的图像, 再排序并转换多码解码的结果; 解码错误由调用方加上来源信息后报告
+fn multiple_py_results(
+    py: Python,
+    mut decoded: Vec<Decoded>,
+    options: &ImageDecodeOptions,
+    order: ResultOrder,
+    row_tolerance: f32,
+    collect_points: bool,
+    hints: &DecodeHints,
+) -> PyResult<Vec<PyRXingResult>> {
+    for decoded in &mut decoded {
+        options.unpad_points(&mut decoded.result);
     }
+    let decoded = drop_itf_check_failures(py, decoded, hints, |decoded| &decoded.result)?;
+    Ok(order_results(decoded, order, row_tolerance)
+        .into_iter()
+        .map(|result| {
+            result
+                .into_py_result(collect_points)
+                .with_decode_hints(hints)
+        })
+        .collect())
 }
 
+// 多帧图像 (GIF / APNG / 动画 WebP / 多页 TIFF) 逐帧做多码解码; 没有条码的帧对应空列表,
+// 以保持帧序号与列表位置一致
 #[cfg(feature = "image")]
 #[pyfunction]
-fn decode_from_file_path(
+#[pyo3(signature = (image_bytes, hints=None))]
+fn decode_all_frames(
     py: Python,
-    file_path_str: &str,
-    hints_dict: Option<&Bound<PyDict>>,
-) -> PyResult<PyRXingResult> {
-    let path = PathBuf::from(file_path_str);
+    image_bytes: &[u8],
+    hints: Option<&Bound<PyAny>>,
+) -> PyResult<Vec<(usize, Vec<PyRXingResult>)>> {
+    let hints = extract_decode_hints(hints)?;
+    let pure_barcode = hints.PureBarcode == Some(true);
+    let mut frames = Vec::new();
+    let loaded = py.allow_threads(|| {
+        frames::for_each_frame(image_bytes, |dynamic_image| {
+            let luma_source = BufferedImageLuminanceSource::new(dynamic_image);
+            let mut binary_bitmap = BinaryBitmap::new(HybridBinarizer::new(luma_source));
+            frames.push(decode_multiple_bitmap(&mut binary_bitmap, &hints));
+        })
+    });
+    if let Err(e) = loaded {
+        return Err(PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
+            "Failed to load image frame {} from bytes: {:?}",
+            frames.len(),
+            e
+        )));
+    }
+    frames
+        .into_iter()
+        .enumerate()
+        .map(|(index, decoded)| match decoded {
+            Ok(results) => Ok((
+                index,
+                drop_itf_check_failures(py, results, &hints, |decoded| &decoded.result)?
+                    .into_iter()
+                    .map(|result| PyRXingResult::from(result).with_decode_hints(&hints))
+                    .collect(),
+            )),
+            Err(e) => Err(decode_error(
+                py,
+                &format!("RXing decoding failed on frame {}", index),
+                e,
+                pure_barcode,
+            )),
+        })
+        .collect()
+}
 
-    if !path.exists() {
-        return Err(PyErr::new::<pyo3::exceptions::PyFileNotFoundError, _>(
-            format!("File not found: {}", file_path_str),
-        ));
+// 按已知的版面逐格解码: 每个 (x, y, w, h) 单元格单独裁剪后解码, 结果与 cells 一一对应,
+// 找不到条码的格为 None. result_points 换算回整页的坐标
+#[cfg(feature = "image")]
+#[pyfunction]
+#[pyo3(signature = (image_bytes, cells, hints=None))]
+fn decode_region_grid(
+    py: Python,
+    image_bytes: &[u8],
+    cells: Vec<(u32, u32, u32, u32)>,
+    hints: Option<&Bound<PyAny>>,
+) -> PyResult<Vec<Option<PyRXingResult>>> {
+    let hints = extract_decode_hints(hints)?;
+    let pure_barcode = hints.PureBarcode == Some(true);
+    let dynamic_image = py
+        .allow_threads(|| load_image_bytes(image_bytes, true))
+        .map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
+                "Failed to load image from bytes: {:?}",
+                e
+            ))
+        })?;
+    let (width, height) = (dynamic_image.width(), dynamic_image.height());
+    for (index, &(x, y, w, h)) in cells.iter().enumerate() {
+        if w == 0 || h == 0 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Cell {} {:?} must have a positive width and height.",
+                index,
+                (x, y, w, h)
+            )));
+        }
+        let fits = |start: u32, size: u32, limit: u32| {
+            start.checked_add(size).is_some_and(|end| end <= limit)
+        };
+        if !fits(x, w, width) || !fits(y, h, height) {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Cell {} {:?} does not fit in the {}x{} image.",
+                index,
+                (x, y, w, h),
+                width,
+                height
+            )));
+        }
     }
 
-    let hints = py_dict_to_decode_hints(py, hints_dict)?;
+    let decoded: Vec<_> = py.allow_threads(|| {
+        cells
+            .iter()
+            .map(|&(x, y, w, h)| {
+                let luma_source =
+                    BufferedImageLuminanceSource::new(dynamic_image.crop_imm(x, y, w, h));
+                let mut decoded = BinarizerKind::Hybrid.decode(luma_source, &hints);
+                if let Ok(decoded) = decoded.as_mut() {
+                    for point in decoded.result.getPointsMut() {
+                        point.x += x as f32;
+                        point.y += y as f32;
+                    }
+                }
+                decoded
+            })
+            .collect()
+    });
+    decoded
+        .into_iter()
+        .enumerate()
+        .map(|(index, decoded)| match decoded {
+            Ok(decoded) => Ok(Some(decoded.into_py_result(true).with_decode_hints(&hints))),
+            Err(rxing::Exceptions::NotFoundException(_)) => Ok(None),
+            Err(e) => Err(decode_error(
+                py,
+                &format!("RXing decoding failed in cell {}", index),
+                e,
+                pure_barcode,
+            )),
+        })
+        .collect()
+}
 
-    match image::open(&path) {
-        Ok(dynamic_image) => {
+// 调试与演示用: 用多码解码找出所有条码, 在原图上按格式着色画出轮廓并标注 text, 返回 PNG
+#[cfg(feature = "image")]
+#[pyfunction]
+#[pyo3(signature = (image_bytes, hints=None))]
+fn scan_and_annotate<'py>(
+    py: Python<'py>,
+    image_bytes: &[u8],
+    hints: Option<&Bound<PyAny>>,
+) -> PyResult<Bound<'py, PyBytes>> {
+    let hints = extract_decode_hints(hints)?;
+    let annotated = py.allow_threads(|| {
+        load_image_bytes(image_bytes, true).map(|dynamic_image| {
+            let canvas = dynamic_image.to_rgba8();
             let luma_source = BufferedImageLuminanceSource::new(dynamic_image);
-            let binarizer = HybridBinarizer::new(luma_source);
-            let mut binary_bitmap = BinaryBitmap::new(binarizer);
-            let mut reader = MultiFormatReader::default();
-
-            match reader.decode_with_hints(&mut binary_bitmap, &hints) {
-                Ok(result) => Ok(PyRXingResult::from(result)),
-                Err(e) => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-                    "RXing decoding failed for file {}: {:?}",
-                    file_path_str, e
-                ))),
+            let mut binary_bitmap = BinaryBitmap::new(HybridBinarizer::new(luma_source));
+            let results = decode_multiple_bitmap(&mut binary_bitmap, &hints)?;
+            Ok::<_, rxing::Exceptions>((canvas, results))
+        })
+    });
+    let canvas = match annotated {
+        Ok(Ok((mut canvas, results))) => {
+            for decoded in drop_itf_check_failures(py, results, &hints, |decoded| &decoded.result)?
+            {
+                let result = &decoded.result;
+                annotate::annotate(
+                    &mut canvas,
+                    result.getBarcodeFormat(),
+                    result.getText(),
+                    result.getPoints(),
+                );
             }
+            canvas
         }
-        Err(e) => Err(PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
-            "Failed to open or decode image file {}: {:?}",
-            file_path_str, e
-        ))),
-    }
+        Ok(Err(e)) => return Err(decode_error(py, "RXing decoding failed", e, false)),
+        Err(e) => {
+            return Err(PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
+                "Failed to load image from bytes: {:?}",
+                e
+            )))
+        }
+    };
+    Ok(PyBytes::new(
+        py,
+        &render::encode_png(&canvas, render::PixelFormat::Rgba)?,
+    ))
 }
 
 // --- ENCODING FUNCTION ---
 #[pyfunction]
+#[pyo3(signature = (data, format, width, height, hints_dict, *, extended=None, qr_mask=None))]
+#[allow(clippy::too_many_arguments)]
 fn encode(
     py: Python,
     data: &str,
     format: &str,
     width: i32,
     height: i32,
-    hints_dict: Option<&Bound<PyDict>>,
+    hints_dict: Option<&Bound<PyAny>>,
+    extended: Option<bool>,
+    qr_mask: Option<i64>,
 ) -> PyResult<PyBitMatrix> {
     let barcode_format = BarcodeFormat::from(format.to_uppercase());
 
-    let hints = py_dict_to_encode_hints(py, hints_dict)?;
-    let writer = MultiFormatWriter;
+    validate_encode_dimension("width", width)?;
+    validate_encode_dimension("height", height)?;
+    let mut hints = extract_encode_hints(hints_dict)?;
+    set_qr_mask(&mut hints, &barcode_format, qr_mask)?;
+    let contents = code_39::contents(data, &barcode_format, extended)
+        .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
 
-    match writer.encode_with_hints(data, &barcode_format, width, height, &hints) {
-        Ok(bit_matrix) => Ok(PyBitMatrix::from(bit_matrix)),
-        Err(e) => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-            "RXing encoding failed: {:?}",
-            e
-        ))),
+    py.allow_threads(|| write_bit_matrix(&contents, &barcode_format, width, height, &hints))
+        .map(PyBitMatrix::from)
+}
+
+// encode_many 中编码失败的条目; 为假值, 可用 `[r for r in results if r]` 过滤
+#[pyclass(name = "EncodeFailure")]
+#[derive(Clone)]
+struct PyEncodeFailure {
+    #[pyo3(get)]
+    index: usize,
+    #[pyo3(get)]
+    data: String,
+    #[pyo3(get)]
+    format: String,
+    #[pyo3(get)]
+    message: String,
+}
+
+#[pymethods]
+impl PyEncodeFailure {
+    fn __bool__(&self) -> bool {
+        false
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "EncodeFailure(index={}, format={:?}, message={:?})",
+            self.index, self.format, self.message
+        )
+    }
+}
+
+// encode_many 的一项: 在持有 GIL 时从 dict 中取出, 在线程池中编码
+struct EncodeJob {
+    data: String,
+    format: String,
+    width: i32,
+    height: i32,
+    hints: RxingEncodeHints,
+    extended: Option<bool>,
+    qr_mask: Option<i64>,
+    png: bool,
+}
+
+enum EncodedItem {
+    Matrix(rxing::common::BitMatrix),
+    #[cfg(feature = "image")]
+    Png(Vec<u8>),
+}
+
+impl EncodeJob {
+    const KEYS: [&'static str; 8] = [
+        "data", "format", "width", "height", "hints", "extended", "qr_mask", "png",
+    ];
+
+    fn extract(item: &Bound<PyAny>) -> PyResult<Self> {
+        let dict = item.downcast::<PyDict>()?;
+        if let Some(key) = dict.keys().iter().find(|key| {
+            !Self::KEYS
+                .iter()
+                .any(|known| key.eq(known).unwrap_or(false))
+        }) {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "unknown key {}; expected one of {:?}",
+                key.repr()?,
+                Self::KEYS
+            )));
+        }
+        let required = |key: &str| {
+            dict.get_item(key)?.ok_or_else(|| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("missing {:?}", key))
+            })
+        };
+        let optional = |key: &str| dict.get_item(key).map(|v| v.filter(|v| !v.is_none()));
+        let png = optional("png")?
+            .map(|v| v.extract())
+            .transpose()?
+            .unwrap_or(false);
+        let width = optional("width")?.map(|v| v.extract()).transpose()?;
+        let height = optional("height")?.map(|v| v.extract()).transpose()?;
+        if png && (width.is_some() || height.is_some()) {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "width and height do not apply to png output, which uses the encode_to_png() defaults",
+            ));
+        }
+        let mut hints = extract_encode_hints(optional("hints")?.as_ref())?;
+        if png {
+            if hints.Margin.is_some() {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    "png output uses a 4-module border; do not pass the MARGIN hint",
+                ));
+            }
+            hints.Margin = Some("0".to_string());
+        }
+        Ok(EncodeJob {
+            data: required("data")?.extract()?,
+            format: required("format")?.extract()?,
+            width: width.unwrap_or(29),
+            height: height.unwrap_or(29),
+            hints,
+            extended: optional("extended")?.map(|v| v.extract()).transpose()?,
+            qr_mask: optional("qr_mask")?.map(|v| v.extract()).transpose()?,
+            png,
+        })
+    }
+
+    // 不需要 GIL: 错误为惰性构造的 PyErr, 回到 Python 线程后再取出消息
+    fn run(&self) -> PyResult<EncodedItem> {
+        let barcode_format = BarcodeFormat::from(self.format.to_uppercase());
+        let mut hints = self.hints.clone();
+        set_qr_mask(&mut hints, &barcode_format, self.qr_mask)?;
+        let contents = code_39::contents(&self.data, &barcode_format, self.extended)
+            .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
+        if self.png {
+            #[cfg(feature = "image")]
+            {
+                let bit_matrix = write_bit_matrix(&contents, &barcode_format, 0, 0, &hints)?;
+                let image = render::render_rgba(&bit_matrix, &render::RenderOptions::default())?;
+                return render::encode_png(&image, render::PixelFormat::Rgba).map(EncodedItem::Png);
+            }
+            #[cfg(not(feature = "image"))]
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "png output requires the image feature",
+            ));
+        }
+        validate_encode_dimension("width", self.width)?;
+        validate_encode_dimension("height", self.height)?;
+        write_bit_matrix(&contents, &barcode_format, self.width, self.height, &hints)
+            .map(EncodedItem::Matrix)
+    }
+}
+
+// 批量编码, 结果与 items 一一对应. 线程池见 run_in_pool
+#[pyfunction]
+#[pyo3(signature = (items, *, workers=None))]
+fn encode_many(
+    py: Python,
+    items: Vec<Bound<PyAny>>,
+    workers: Option<usize>,
+) -> PyResult<Vec<PyObject>> {
+    if workers == Some(0) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "workers must be at least 1, or None for one thread per CPU.",
+        ));
+    }
+    let jobs = items
+        .iter()
+        .enumerate()
+        .map(|(index, item)| {
+            EncodeJob::extract(item).map_err(|e| {
+                let err = PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Invalid item {}: {}",
+                    index, e
+                ));
+                err.set_cause(py, Some(e));
+                err
+            })
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+
+    let encode_all = || jobs.par_iter().map(EncodeJob::run).collect::<Vec<_>>();
+    let outputs = py
+        .allow_threads(|| run_in_pool(workers, encode_all))
+        .map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
+                "Failed to start {} encode workers: {}",
+                workers.unwrap_or_default(),
+                e
+            ))
+        })?;
+
+    jobs.iter()
+        .zip(outputs)
+        .enumerate()
+        .map(|(index, (job, output))| match output {
+            Ok(EncodedItem::Matrix(bit_matrix)) => {
+                Ok(Py::new(py, PyBitMatrix::from(bit_matrix))?.into_any())
+            }
+            #[cfg(feature = "image")]
+            Ok(EncodedItem::Png(png)) => Ok(PyBytes::new(py, &png).into_any().unbind()),
+            Err(e) => {
+                let failure = PyEncodeFailure {
+                    index,
+                    data: job.data.clone(),
+                    format: job.format.clone(),
+                    message: e.value(py).to_string(),
+                };
+                Ok(Py::new(py, failure)?.into_any())
+            }
+        })
+        .collect()
+}
+
+// 编码前校验数据: 先按格式规则指出具体的问题字符, 长度或校验位, 再试编码一次,
+// 由编码器检查长度上限与二维码容量 (容量取决于 ERROR_CORRECTION 等提示)
+#[pyfunction]
+#[pyo3(signature = (data, format, hints_dict=None))]
+fn validate_encodable(
+    py: Python,
+    data: &str,
+    format: &str,
+    hints_dict: Option<&Bound<PyAny>>,
+) -> PyResult<()> {
+    let barcode_format = BarcodeFormat::from(format.to_uppercase());
+    if !SUPPORTED_WRITE_FORMATS.contains(&barcode_format) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "{:?} cannot be encoded; see supported_write_formats().",
+            format
+        )));
+    }
+    let hints = extract_encode_hints(hints_dict)?;
+    encodable::check(data, &barcode_format)
+        .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
+    py.allow_threads(|| write_bit_matrix(data, &barcode_format, 0, 0, &hints))
+        .map(|_| ())
+}
+
+#[pyfunction]
+fn upce_to_upca(text: &str) -> PyResult<String> {
+    encodable::upc_e_to_upc_a(text).map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)
+}
+
+// 单一模式下一个符号的最大容量; ecc 只适用于 QR
+#[pyfunction]
+#[pyo3(signature = (format, *, version=None, ecc=None, mode="byte"))]
+fn capacity(format: &str, version: Option<u32>, ecc: Option<&str>, mode: &str) -> PyResult<u32> {
+    let barcode_format = BarcodeFormat::from(format.to_uppercase());
+    symbol_capacity::capacity(&barcode_format, version, ecc, &mode.to_lowercase())
+        .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)
+}
+
+// 按 AI 表校验 ai_map 并拼成 GS1 元素串后编码; dict 的顺序即元素顺序.
+// Code 128 用 ESCAPE_FNC_1 (U+00F1) 表示 FNC1 (包括起始的 FNC1),
+// Data Matrix / QR 以 GS (0x1D) 作分隔符, 起始的 FNC1 由编码器加入
+#[pyfunction]
+#[pyo3(signature = (ai_map, format="CODE_128", width=29, height=29, hints_dict=None))]
+fn encode_gs1(
+    py: Python,
+    ai_map: &Bound<PyDict>,
+    format: &str,
+    width: i32,
+    height: i32,
+    hints_dict: Option<&Bound<PyAny>>,
+) -> PyResult<PyBitMatrix> {
+    let barcode_format = BarcodeFormat::from(format.to_uppercase());
+    let separator = match barcode_format {
+        BarcodeFormat::CODE_128 => '\u{00f1}',
+        BarcodeFormat::DATA_MATRIX | BarcodeFormat::QR_CODE => '\u{001d}',
+        _ => {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "GS1 encoding supports CODE_128, DATA_MATRIX and QR_CODE, got {}.",
+                format
+            )))
+        }
+    };
+
+    validate_encode_dimension("width", width)?;
+    validate_encode_dimension("height", height)?;
+    let elements = ai_map
+        .iter()
+        .map(|(ai, value)| Ok((ai.extract::<String>()?, value.extract::<String>()?)))
+        .collect::<PyResult<Vec<(String, String)>>>()?;
+    let mut contents = gs1::element_string(&elements, separator)?;
+    let mut hints = extract_encode_hints(hints_dict)?;
+    hints.Gs1Format = Some(true);
+    let bit_matrix = py.allow_threads(|| match barcode_format {
+        BarcodeFormat::CODE_128 => {
+            contents.insert(0, separator);
+            write_bit_matrix(&contents, &barcode_format, width, height, &hints)
+        }
+        BarcodeFormat::DATA_MATRIX => {
+            check_fits(&barcode_format, &contents, &hints)?;
+            catch_encode_errors(|| {
+                gs1::write_data_matrix(&contents, width as u32, height as u32, &hints)
+            })
+        }
+        _ => write_bit_matrix(&contents, &barcode_format, width, height, &hints),
+    })?;
+    Ok(PyBitMatrix::from(bit_matrix))
+}
+
+// encode_gs1 的逆操作: 把 GS1 元素串拆分为 {AI: 数据}, 按出现顺序排列. 参数为 text 或
+// RXingResult; Code 128 只有用 ASSUME_GS1 解码时才保留变长字段之间的分隔符
+#[pyfunction]
+fn parse_gs1<'py>(py: Python<'py>, data: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyDict>> {
+    let text = match data.downcast::<PyRXingResult>() {
+        Ok(result) => {
+            let result = result.borrow();
+            if let Some(id) = result.symbology_identifier.as_deref() {
+                if !gs1::SYMBOLOGY_IDENTIFIERS.contains(&id) {
+                    return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                        "Symbology identifier {} does not mark GS1 data.",
+                        id
+                    )));
+                }
+            }
+            if result.barcode_format == BarcodeFormat::CODE_128.to_string()
+                && !result.text.starts_with("]C1")
+            {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    "Code 128 results keep GS1 field separators only when decoded with the ASSUME_GS1 hint.",
+                ));
+            }
+            result.text.clone()
+        }
+        Err(_) => data.extract::<String>()?,
+    };
+    let dict = PyDict::new(py);
+    for (ai, value) in gs1::parse_element_string(&text)? {
+        dict.set_item(ai, value)?;
+    }
+    Ok(dict)
+}
+
+// 渲染尺寸上限; 部分编码器会按请求尺寸直接分配内存
+const MAX_ENCODE_DIMENSION: i32 = 16384;
+
+fn validate_encode_dimension(name: &str, value: i32) -> PyResult<()> {
+    if value <= 0 {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "{} must be a positive number of pixels, got {}.",
+            name, value
+        )));
+    }
+    if value > MAX_ENCODE_DIMENSION {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "{} must be at most {} pixels, got {}.",
+            name, MAX_ENCODE_DIMENSION, value
+        )));
+    }
+    Ok(())
+}
+
+// 固定 QR 的掩模图形 (0-7). rxing 会静默忽略超出范围的 QR_MASK_PATTERN 并自动选择,
+// 所以在这里校验
+fn set_qr_mask(
+    hints: &mut RxingEncodeHints,
+    barcode_format: &BarcodeFormat,
+    qr_mask: Option<i64>,
+) -> PyResult<()> {
+    let Some(qr_mask) = qr_mask else {
+        return Ok(());
+    };
+    if *barcode_format != BarcodeFormat::QR_CODE {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "qr_mask only applies to QR_CODE, got {:?}",
+            barcode_format
+        )));
+    }
+    if !(0..=7).contains(&qr_mask) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "qr_mask must be between 0 and 7, got {}.",
+            qr_mask
+        )));
+    }
+    hints.QrMaskPattern = Some(qr_mask.to_string());
+    Ok(())
+}
+
+// 调用 MultiFormatWriter
+fn write_bit_matrix(
+    data: &str,
+    barcode_format: &BarcodeFormat,
+    width: i32,
+    height: i32,
+    hints: &RxingEncodeHints,
+) -> PyResult<rxing::common::BitMatrix> {
+    check_error_correction(barcode_format, hints)?;
+    if *barcode_format == BarcodeFormat::CODE_128 {
+        let contents = encodable::code_128_contents(data, hints)
+            .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
+        return catch_encode_errors(|| {
+            MultiFormatWriter.encode_with_hints(&contents, barcode_format, width, height, hints)
+        });
+    }
+    check_fits(barcode_format, data, hints)?;
+    catch_encode_errors(|| {
+        MultiFormatWriter.encode_with_hints(data, barcode_format, width, height, hints)
+    })
+}
+
+// ERROR_CORRECTION 的含义随格式而变; rxing 对不适用的值有时静默改用默认值 (如给 PDF417 传 "H")
+fn check_error_correction(
+    barcode_format: &BarcodeFormat,
+    hints: &RxingEncodeHints,
+) -> PyResult<()> {
+    let Some(level) = &hints.ErrorCorrection else {
+        return Ok(());
+    };
+    let expected = match barcode_format {
+        BarcodeFormat::QR_CODE if !matches!(level.as_str(), "L" | "M" | "Q" | "H") => {
+            "L, M, Q or H"
+        }
+        BarcodeFormat::PDF_417 if !level.parse::<u32>().is_ok_and(|level| level <= 8) => {
+            "a level between 0 and 8"
+        }
+        BarcodeFormat::AZTEC if !level.parse::<u32>().is_ok_and(|percent| percent <= 100) => {
+            "a percentage between 0 and 100"
+        }
+        _ => return Ok(()),
+    };
+    Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+        "ERROR_CORRECTION for {:?} must be {}, got {:?}",
+        barcode_format, expected, level
+    )))
+}
+
+// 先按容量表检查 QR 与 Data Matrix 放不放得下, 这样数据过长时不必等待编码器
+// (Data Matrix 编码器对过长的数据很慢, 最后还会 panic)
+fn check_fits(
+    barcode_format: &BarcodeFormat,
+    data: &str,
+    hints: &RxingEncodeHints,
+) -> PyResult<()> {
+    symbol_capacity::check_fits(barcode_format, data, hints).map_err(|message| {
+        errors::WriterException::new_err(format!(
+            "Data too large to encode as {:?}: {}",
+            barcode_format, message
+        ))
+    })
+}
+
+thread_local! {
+    static QUIET_PANICS: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+static QUIET_PANIC_HOOK: std::sync::Once = std::sync::Once::new();
+
+// 个别编码器 (如 Data Matrix 找不到合适的符号时) 会 panic, 这里转换为 WriterException.
+// 被捕获的 panic 不经过默认的 panic hook 打印到 stderr; 其它线程的 panic 照常打印
+fn catch_encode_errors<F>(write: F) -> PyResult<rxing::common::BitMatrix>
+where
+    F: FnOnce() -> Result<rxing::common::BitMatrix, rxing::Exceptions>,
+{
+    QUIET_PANIC_HOOK.call_once(|| {
+        let default_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            if !QUIET_PANICS.with(|quiet| quiet.get()) {
+                default_hook(info)
+            }
+        }));
+    });
+    QUIET_PANICS.with(|quiet| quiet.set(true));
+    let written = std::panic::catch_unwind(std::panic::AssertUnwindSafe(write));
+    QUIET_PANICS.with(|quiet| quiet.set(false));
+    let message = match written {
+        Ok(Ok(bit_matrix)) => return Ok(bit_matrix),
+        Ok(Err(e)) => e.to_string(),
+        Err(panic) => panic
+            .downcast_ref::<String>()
+            .cloned()
+            .or_else(|| panic.downcast_ref::<&str>().map(|s| s.to_string()))
+            .map(|message| format!("encoder panicked: {}", message))
+            .unwrap_or_else(|| "encoder panicked".to_string()),
+    };
+    Err(errors::WriterException::new_err(format!(
+        "RXing encoding failed: {}",
+        message
+    )))
+}
+
+// 渲染输出 (PNG, SVG) 使用的编码提示: 留白由 border 控制, 因此不接受 MARGIN 提示
+fn render_encode_hints(hints: Option<&Bound<PyAny>>) -> PyResult<RxingEncodeHints> {
+    let mut encode_hints = extract_encode_hints(hints)?;
+    if encode_hints.Margin.is_some() {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "Rendered output uses `border` for the quiet zone; do not pass the MARGIN hint.",
+        ));
+    }
+    encode_hints.Margin = Some("0".to_string());
+    Ok(encode_hints)
+}
+
+// 不需要 GIL, 提示由 render_encode_hints 预先转换
+fn encode_for_render(
+    data: &str,
+    format: &str,
+    hints: &RxingEncodeHints,
+) -> PyResult<rxing::common::BitMatrix> {
+    let barcode_format = BarcodeFormat::from(format.to_uppercase());
+    write_bit_matrix(data, &barcode_format, 0, 0, hints)
+}
+
+// 编码并直接栅格化为 PNG
+#[cfg(feature = "image")]
+#[pyfunction]
+#[pyo3(signature = (data, format, *, width=0, height=0, module_size=4, border=4, fg=(0, 0, 0, 255), bg=(255, 255, 255, 255), bar_height=50, hints=None, pixel_format="rgba"))]
+#[allow(clippy::too_many_arguments)]
+fn encode_to_png(
+    py: Python,
+    data: &str,
+    format: &str,
+    width: u32,
+    height: u32,
+    module_size: u32,
+    border: u32,
+    fg: (u8, u8, u8, u8),
+    bg: (u8, u8, u8, u8),
+    bar_height: u32,
+    hints: Option<&Bound<PyAny>>,
+    pixel_format: &str,
+) -> PyResult<Vec<u8>> {
+    let pixel_format = render::PixelFormat::parse(pixel_format)?;
+    let mut options = render::RenderOptions {
+        module_size,
+        border,
+        bar_height,
+        foreground: image::Rgba([fg.0, fg.1, fg.2, fg.3]),
+        background: image::Rgba([bg.0, bg.1, bg.2, bg.3]),
+    };
+    pixel_format.check_colors(options.foreground, options.background)?;
+    let hints = render_encode_hints(hints)?;
+    py.allow_threads(|| {
+        let bit_matrix = encode_for_render(data, format, &hints)?;
+        options.grow_to_fit(&bit_matrix, width, height);
+        render::encode_png(&render::render_rgba(&bit_matrix, &options)?, pixel_format)
+    })
+}
+
+// 编码为矢量 SVG 字符串, 不需要 image 特性
+#[pyfunction]
+#[pyo3(signature = (data, format, *, module_size=4, border=4, fg=(0, 0, 0, 255), bg=(255, 255, 255, 255), bar_height=50, hints=None, shape="path"))]
+#[allow(clippy::too_many_arguments)]
+fn encode_to_svg(
+    py: Python,
+    data: &str,
+    format: &str,
+    module_size: u32,
+    border: u32,
+    fg: (u8, u8, u8, u8),
+    bg: (u8, u8, u8, u8),
+    bar_height: u32,
+    hints: Option<&Bound<PyAny>>,
+    shape: &str,
+) -> PyResult<String> {
+    let options = svg::SvgOptions {
+        module_size,
+        border,
+        bar_height,
+        foreground: fg,
+        background: bg,
+        shape: svg::SvgShape::parse(shape)?,
+    };
+    let hints = render_encode_hints(hints)?;
+    py.allow_threads(|| {
+        let bit_matrix = encode_for_render(data, format, &hints)?;
+        svg::render_svg(&bit_matrix, &options)
+    })
+}
+
+// 可复用的编码器: 格式与编码提示在构造时解析一次, 批量生成标签时各次调用共用同一份配置
+#[pyclass(name = "BarcodeWriter")]
+struct PyBarcodeWriter {
+    barcode_format: BarcodeFormat,
+    hints: RxingEncodeHints,
+}
+
+impl PyBarcodeWriter {
+    // PNG 与 SVG 输出的留白由 border 控制, 与 render_encode_hints 相同
+    fn render_hints(&self) -> PyResult<RxingEncodeHints> {
+        if self.hints.Margin.is_some() {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "Rendered output uses `border` for the quiet zone; this writer was created with the MARGIN hint.",
+            ));
+        }
+        let mut hints = self.hints.clone();
+        hints.Margin = Some("0".to_string());
+        Ok(hints)
+    }
+}
+
+#[pymethods]
+impl PyBarcodeWriter {
+    #[new]
+    #[pyo3(signature = (format, hints=None))]
+    fn new(format: &str, hints: Option<&Bound<PyAny>>) -> PyResult<Self> {
+        let barcode_format = BarcodeFormat::from(format.to_uppercase());
+        if !SUPPORTED_WRITE_FORMATS.contains(&barcode_format) {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "{:?} cannot be encoded; see supported_write_formats().",
+                format
+            )));
+        }
+        Ok(PyBarcodeWriter {
+            barcode_format,
+            hints: extract_encode_hints(hints)?,
+        })
     }
+
+    #[getter]
+    fn format(&self) -> String {
+        self.barcode_format.to_string()
+    }
+
+    // 与模块级的 encode 相同; 只有给出 qr_mask 时才复制提示
+    #[pyo3(signature = (data, width, height, *, extended=None, qr_mask=None))]
+    fn encode(
+        &self,
+        py: Python,
+        data: &str,
+        width: i32,
+        height: i32,
+        extended: Option<bool>,
+        qr_mask: Option<i64>,
+    ) -> PyResult<PyBitMatrix> {
+        validate_encode_dimension("width", width)?;
+        validate_encode_dimension("height", height)?;
+        let mut hints = std::borrow::Cow::Borrowed(&self.hints);
+        if qr_mask.is_some() {
+            set_qr_mask(hints.to_mut(), &self.barcode_format, qr_mask)?;
+        }
+        let contents = code_39::contents(data, &self.barcode_format, extended)
+            .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
+        py.allow_threads(|| {
+            write_bit_matrix(&contents, &self.barcode_format, width, height, &hints)
+        })
+        .map(PyBitMatrix::from)
+    }
+
+    // 与模块级的 encode_to_png 相同
+    #[cfg(feature = "image")]
+    #[pyo3(signature = (data, *, width=0, height=0, module_size=4, border=4, fg=(0, 0, 0, 255), bg=(255, 255, 255, 255), bar_height=50, pixel_format="rgba"))]
+    #[allow(clippy::too_many_arguments)]
+    fn encode_to_png(
+        &self,
+        py: Python,
+        data: &str,
+        width: u32,
+        height: u32,
+        module_size: u32,
+        border: u32,
+        fg: (u8, u8, u8, u8),
+        bg: (u8, u8, u8, u8),
+        bar_height: u32,
+        pixel_format: &str,
+    ) -> PyResult<Vec<u8>> {
+        let pixel_format = render::PixelFormat::parse(pixel_format)?;
+        let mut options = render::RenderOptions {
+            module_size,
+            border,
+            bar_height,
+            foreground: image::Rgba([fg.0, fg.1, fg.2, fg.3]),
+            background: image::Rgba([bg.0, bg.1, bg.2, bg.3]),
+        };
+        pixel_format.check_colors(options.foreground, options.background)?;
+        let hints = self.render_hints()?;
+        py.allow_threads(|| {
+            let bit_matrix = write_bit_matrix(data, &self.barcode_format, 0, 0, &hints)?;
+            options.grow_to_fit(&bit_matrix, width, height);
+            render::encode_png(&render::render_rgba(&bit_matrix, &options)?, pixel_format)
+        })
+    }
+
+    // 与模块级的 encode_to_svg 相同
+    #[pyo3(signature = (data, *, module_size=4, border=4, fg=(0, 0, 0, 255), bg=(255, 255, 255, 255), bar_height=50, shape="path"))]
+    #[allow(clippy::too_many_arguments)]
+    fn encode_to_svg(
+        &self,
+        py: Python,
+        data: &str,
+        module_size: u32,
+        border: u32,
+        fg: (u8, u8, u8, u8),
+        bg: (u8, u8, u8, u8),
+        bar_height: u32,
+        shape: &str,
+    ) -> PyResult<String> {
+        let options = svg::SvgOptions {
+            module_size,
+            border,
+            bar_height,
+            foreground: fg,
+            background: bg,
+            shape: svg::SvgShape::parse(shape)?,
+        };
+        let hints = self.render_hints()?;
+        py.allow_threads(|| {
+            let bit_matrix = write_bit_matrix(data, &self.barcode_format, 0, 0, &hints)?;
+            svg::render_svg(&bit_matrix, &options)
+        })
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "BarcodeWriter(format={:?})",
+            self.barcode_format.to_string()
+        )
+    }
+}
+
+// 把多个条码编码后按网格排进一张 PNG (标签页打印)
+#[cfg(feature = "image")]
+#[pyfunction]
+#[pyo3(signature = (items, *, columns=4, module_size=4, spacing=16, border=4, bar_height=50, hints=None, pixel_format="rgba"))]
+#[allow(clippy::too_many_arguments)]
+fn encode_sheet(
+    py: Python,
+    items: Vec<(String, String)>,
+    columns: u32,
+    module_size: u32,
+    spacing: u32,
+    border: u32,
+    bar_height: u32,
+    hints: Option<&Bound<PyAny>>,
+    pixel_format: &str,
+) -> PyResult<Vec<u8>> {
+    let pixel_format = render::PixelFormat::parse(pixel_format)?;
+    if items.is_empty() {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "items must contain at least one (data, format) pair.",
+        ));
+    }
+    let options = render::RenderOptions {
+        module_size,
+        border,
+        bar_height,
+        foreground: image::Rgba([0, 0, 0, 255]),
+        background: image::Rgba([255, 255, 255, 255]),
+    };
+    let hints = render_encode_hints(hints)?;
+    let cells = py.allow_threads(|| {
+        items
+            .iter()
+            .enumerate()
+            .map(|(index, (data, format))| {
+                encode_for_render(data, format, &hints)
+                    .and_then(|bit_matrix| render::render_rgba(&bit_matrix, &options))
+                    .map_err(|e| (index, e))
+            })
+            .collect::<Result<Vec<_>, _>>()
+    });
+    let cells = cells.map_err(|(index, e)| {
+        let (data, format) = &items[index];
+        let err = PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Failed to encode item {} ({:?}, {:?}): {}",
+            index, data, format, e
+        ));
+        err.set_cause(py, Some(e));
+        err
+    })?;
+    py.allow_threads(|| {
+        let sheet = render::compose_sheet(&cells, columns, spacing, options.background)?;
+        render::encode_png(&sheet, pixel_format)
+    })
+}
+
+// 编码并写入文件, 图像格式由扩展名决定; width/height 为最小像素尺寸
+#[cfg(feature = "image")]
+#[pyfunction]
+#[pyo3(signature = (data, format, path, *, width=0, height=0, module_size=4, border=4, fg=(0, 0, 0, 255), bg=(255, 255, 255, 255), bar_height=50, hints=None, create_dirs=false, pixel_format="rgba"))]
+#[allow(clippy::too_many_arguments)]
+fn encode_to_file(
+    py: Python,
+    data: &str,
+    format: &str,
+    path: &str,
+    width: u32,
+    height: u32,
+    module_size: u32,
+    border: u32,
+    fg: (u8, u8, u8, u8),
+    bg: (u8, u8, u8, u8),
+    bar_height: u32,
+    hints: Option<&Bound<PyAny>>,
+    create_dirs: bool,
+    pixel_format: &str,
+) -> PyResult<()> {
+    let pixel_format = render::PixelFormat::parse(pixel_format)?;
+    let path = PathBuf::from(path);
+    let Ok(image_format) = image::ImageFormat::from_path(&path) else {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Cannot infer an image format from the extension of {}",
+            path.display()
+        )));
+    };
+
+    if pixel_format == render::PixelFormat::OneBit && image_format != image::ImageFormat::Png {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "pixel_format \"1bit\" can only be written as PNG, not {:?}.",
+            image_format
+        )));
+    }
+
+    let mut options = render::RenderOptions {
+        module_size,
+        border,
+        bar_height,
+        foreground: image::Rgba([fg.0, fg.1, fg.2, fg.3]),
+        background: image::Rgba([bg.0, bg.1, bg.2, bg.3]),
+    };
+    pixel_format.check_colors(options.foreground, options.background)?;
+    let hints = render_encode_hints(hints)?;
+    let rendered = py.allow_threads(|| {
+        let bit_matrix = encode_for_render(data, format, &hints)?;
+        options.grow_to_fit(&bit_matrix, width, height);
+        render::render_rgba(&bit_matrix, &options)
+    })?;
+
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        if create_dirs {
+            std::fs::create_dir_all(parent).map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
+                    "Failed to create directory {}: {}",
+                    parent.display(),
+                    e
+                ))
+            })?;
+        } else if !parent.is_dir() {
+            return Err(PyErr::new::<pyo3::exceptions::PyFileNotFoundError, _>(
+                format!(
+                    "Directory not found: {} (pass create_dirs=True to create it)",
+                    parent.display()
+                ),
+            ));
+        }
+    }
+    py.allow_threads(|| render::save_image(rendered, &path, image_format, pixel_format))
+}
+
+#[pyfunction]
+fn supported_read_formats() -> Vec<String> {
+    SUPPORTED_READ_FORMATS
+        .iter()
+        .map(|format| format.to_string())
+        .collect()
+}
+
+#[pyfunction]
+fn supported_write_formats() -> Vec<String> {
+    SUPPORTED_WRITE_FORMATS
+        .iter()
+        .map(|format| format.to_string())
+        .collect()
 }
 
 #[pymodule]
 #[pyo3(name = "rxing_lib")]
 fn rxing_py_module(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add(
+        "ReaderException",
+        m.py().get_type::<errors::ReaderException>(),
+    )?;
+    m.add(
+        "NotFoundException",
+        m.py().get_type::<errors::NotFoundException>(),
+    )?;
+    m.add(
+        "ChecksumException",
+        m.py().get_type::<errors::ChecksumException>(),
+    )?;
+    m.add(
+        "FormatException",
+        m.py().get_type::<errors::FormatException>(),
+    )?;
+    m.add(
+        "WriterException",
+        m.py().get_type::<errors::WriterException>(),
+    )?;
     m.add_class::<PyRXingResult>()?;
+    m.add_class::<PyParsedResult>()?;
     m.add_class::<PyPoint>()?;
+    m.add_class::<PyQRPoints>()?;
     m.add_class::<PyBitMatrix>()?;
+    m.add_class::<PyFormatAttempt>()?;
+    m.add_class::<PyEncodeFailure>()?;
+    m.add_class::<PyVideoScanner>()?;
+    m.add_class::<PyBarcodeReader>()?;
+    m.add_class::<PyBarcodeWriter>()?;
+    m.add_class::<PyAppendCollector>()?;
+    m.add_class::<PyDecodeHints>()?;
+    m.add_class::<PyEncodeHints>()?;
 
     m.add_function(wrap_pyfunction!(decode_luma_pixels, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_luma16_pixels, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_pil_image, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_numpy, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_rgb_pixels, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_yuv, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_from_bit_matrix, m)?)?;
+    m.add_function(wrap_pyfunction!(diagnose_luma_pixels, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_luma_candidates, m)?)?;
+    m.add_function(wrap_pyfunction!(binarize_luma_pixels, m)?)?;
+    m.add_class::<PyLuminanceSource>()?;
+    m.add_class::<PyBinaryBitmap>()?;
+    m.add_function(wrap_pyfunction!(detect_luma_pixels, m)?)?;
+    m.add_class::<PyDetection>()?;
+    m.add_function(wrap_pyfunction!(to_luma, m)?)?;
     #[cfg(feature = "image")]
     m.add_function(wrap_pyfunction!(decode_image_bytes, m)?)?;
     #[cfg(feature = "image")]
     m.add_function(wrap_pyfunction!(decode_from_file_path, m)?)?;
+    #[cfg(feature = "image")]
+    m.add_function(wrap_pyfunction!(decode_timed, m)?)?;
+    #[cfg(feature = "image")]
+    m.add_class::<PyDecodeTiming>()?;
+    #[cfg(feature = "image")]
+    m.add_function(wrap_pyfunction!(decode_batch, m)?)?;
+    #[cfg(feature = "image")]
+    m.add_class::<PyDecodeFailure>()?;
+    #[cfg(feature = "image")]
+    m.add_function(wrap_pyfunction!(scan_directory, m)?)?;
+    #[cfg(feature = "image")]
+    m.add_class::<PyDirectoryScanner>()?;
+    #[cfg(feature = "image")]
+    m.add_function(wrap_pyfunction!(decode_multiple_image_bytes, m)?)?;
+    #[cfg(feature = "image")]
+    m.add_function(wrap_pyfunction!(decode_multiple_from_file_path, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_multiple_luma_pixels, m)?)?;
+    #[cfg(feature = "image")]
+    m.add_function(wrap_pyfunction!(decode_all_frames, m)?)?;
+    #[cfg(feature = "image")]
+    m.add_function(wrap_pyfunction!(decode_region_grid, m)?)?;
+    #[cfg(feature = "image")]
+    m.add_function(wrap_pyfunction!(scan_and_annotate, m)?)?;
     m.add_function(wrap_pyfunction!(encode, m)?)?;
+    m.add_function(wrap_pyfunction!(encode_many, m)?)?;
+    m.add_function(wrap_pyfunction!(encode_gs1, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_gs1, m)?)?;
+    m.add_function(wrap_pyfunction!(validate_encodable, m)?)?;
+    m.add_function(wrap_pyfunction!(capacity, m)?)?;
+    m.add_function(wrap_pyfunction!(upce_to_upca, m)?)?;
+    m.add_function(wrap_pyfunction!(encode_to_svg, m)?)?;
+    #[cfg(feature = "image")]
+    m.add_function(wrap_pyfunction!(encode_to_png, m)?)?;
+    #[cfg(feature = "image")]
+    m.add_function(wrap_pyfunction!(encode_sheet, m)?)?;
+    #[cfg(feature = "image")]
+    m.add_function(wrap_pyfunction!(encode_to_file, m)?)?;
+    m.add_function(wrap_pyfunction!(set_timestamp_override, m)?)?;
+    m.add_function(wrap_pyfunction!(timestamp_override, m)?)?;
+    m.add_function(wrap_pyfunction!(supported_read_formats, m)?)?;
+    m.add_function(wrap_pyfunction!(supported_write_formats, m)?)?;
 
-    let py_barcode_format_module = PyModule::new(_py, "BarcodeFormat")?;
-    py_barcode_format_module.add("AZTEC", BarcodeFormat::AZTEC.to_string())?;
-    py_barcode_format_module.add("CODABAR", BarcodeFormat::CODABAR.to_string())?;
-    py_barcode_format_module.add("CODE_39", BarcodeFormat::CODE_39.to_string())?;
-    py_barcode_format_module.add("CODE_93", BarcodeFormat::CODE_93.to_string())?;
-    py_barcode_format_module.add("CODE_128", BarcodeFormat::CODE_128.to_string())?;
-    py_barcode_format_module.add("DATA_MATRIX", BarcodeFormat::DATA_MATRIX.to_string())?;
-    py_barcode_format_module.add("EAN_8", BarcodeFormat::EAN_8.to_string())?;
-    py_barcode_format_module.add("EAN_13", BarcodeFormat::EAN_13.to_string())?;
-    py_barcode_format_module.add("ITF", BarcodeFormat::ITF.to_string())?;
-    py_barcode_format_module.add("MAXICODE", BarcodeFormat::MAXICODE.to_string())?;
-    py_barcode_format_module.add("PDF_417", BarcodeFormat::PDF_417.to_string())?;
-    py_barcode_format_module.add("QR_CODE", BarcodeFormat::QR_CODE.to_string())?;
-    py_barcode_format_module.add("RSS_14", BarcodeFormat::RSS_14.to_string())?;
-    py_barcode_format_module.add("RSS_EXPANDED", BarcodeFormat::RSS_EXPANDED.to_string())?;
-    py_barcode_format_module.add("UPC_A", BarcodeFormat::UPC_A.to_string())?;
-    py_barcode_format_module.add("UPC_E", BarcodeFormat::UPC_E.to_string())?;
-    py_barcode_format_module.add(
-        "UPC_EAN_EXTENSION",
-        BarcodeFormat::UPC_EAN_EXTENSION.to_string(),
-    )?;
-    m.add_submodule(&py_barcode_format_module)?;
+    // (名称, 值) 对, Python 端据此生成 BarcodeFormat 枚举; 值与 RXingResult.barcode_format 相同
+    let barcode_formats: Vec<(String, String)> = BARCODE_FORMATS
+        .iter()
+        .map(|format| (format!("{:?}", format), format.to_string()))
+        .collect();
+    m.add("BARCODE_FORMATS", barcode_formats)?;
+    m.add("METADATA_KEYS", metadata::METADATA_KEYS.to_vec())?;
 
     Ok(())
 }
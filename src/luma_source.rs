@@ -0,0 +1,109 @@
+use rxing::common::Result;
+use rxing::{Exceptions, LuminanceSource};
+use std::borrow::Cow;
+
+// 借用调用方缓冲区的 8 位灰度源, VideoScanner 用它逐帧复用同一块缓冲区.
+// 只有裁剪与旋转 (如 TRY_HARDER 下一维码的旋转重试) 才复制像素
+pub(crate) struct BorrowedLumaSource<'a> {
+    data: Cow<'a, [u8]>,
+    width: usize,
+    height: usize,
+    inverted: bool,
+}
+
+impl<'a> BorrowedLumaSource<'a> {
+    // data 须恰好为 width * height 字节
+    pub(crate) fn new(data: &'a [u8], width: usize, height: usize) -> Self {
+        debug_assert_eq!(data.len(), width * height);
+        BorrowedLumaSource {
+            data: Cow::Borrowed(data),
+            width,
+            height,
+            inverted: false,
+        }
+    }
+
+    fn luma(&self, value: u8) -> u8 {
+        if self.inverted {
+            u8::MAX - value
+        } else {
+            value
+        }
+    }
+}
+
+impl LuminanceSource for BorrowedLumaSource<'_> {
+    const SUPPORTS_CROP: bool = true;
+    const SUPPORTS_ROTATION: bool = true;
+
+    fn get_row(&self, y: usize) -> Vec<u8> {
+        self.data[y * self.width..(y + 1) * self.width]
+            .iter()
+            .map(|&value| self.luma(value))
+            .collect()
+    }
+
+    fn get_column(&self, x: usize) -> Vec<u8> {
+        self.data
+            .chunks_exact(self.width)
+            .map(|row| self.luma(row[x]))
+            .collect()
+    }
+
+    fn get_matrix(&self) -> Vec<u8> {
+        self.data.iter().map(|&value| self.luma(value)).collect()
+    }
+
+    fn get_width(&self) -> usize {
+        self.width
+    }
+
+    fn get_height(&self) -> usize {
+        self.height
+    }
+
+    fn invert(&mut self) {
+        self.inverted = !self.inverted;
+    }
+
+    fn crop(&self, left: usize, top: usize, width: usize, height: usize) -> Result<Self> {
+        if left + width > self.width || top + height > self.height {
+            return Err(Exceptions::illegal_argument_with(
+                "Crop rectangle does not fit within image data.",
+            ));
+        }
+        let data = self
+            .data
+            .chunks_exact(self.width)
+            .skip(top)
+            .take(height)
+            .flat_map(|row| &row[left..left + width])
+            .copied()
+            .collect::<Vec<u8>>();
+        Ok(BorrowedLumaSource {
+            data: Cow::Owned(data),
+            width,
+            height,
+            inverted: self.inverted,
+        })
+    }
+
+    // 逆时针旋转 90°: 新图 (x, y) 取原图 (width - 1 - y, x)
+    fn rotate_counter_clockwise(&self) -> Result<Self> {
+        let data = (0..self.width)
+            .flat_map(|y| {
+                (0..self.height).map(move |x| self.data[x * self.width + self.width - 1 - y])
+            })
+            .collect::<Vec<u8>>();
+        Ok(BorrowedLumaSource {
+            data: Cow::Owned(data),
+            width: self.height,
+            height: self.width,
+            inverted: self.inverted,
+        })
+    }
+
+    fn get_luma8_point(&self, x: usize, y: usize) -> u8 {
+        self.luma(self.data[y * self.width + x])
+    }
+}
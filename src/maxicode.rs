@@ -0,0 +1,71 @@
+// MaxiCode 结构化载波报文 (模式 2 / 3) 的主信息: 邮政编码, ISO 3166 国家代码, 服务类别.
+// rxing 只把它们拼进 text, 这里按 ISO/IEC 16023 的位位置从 raw_bytes (94 个 6 位码字) 中直接读取
+pub(crate) struct MaxiCodeFields {
+    pub mode: u8,
+    pub postal_code: Option<String>,
+    pub country_code: Option<u32>,
+    pub service_class: Option<u32>,
+}
+
+const COUNTRY_BITS: [u8; 10] = [53, 54, 43, 44, 45, 46, 47, 48, 37, 38];
+const SERVICE_CLASS_BITS: [u8; 10] = [55, 56, 57, 58, 59, 60, 49, 50, 51, 52];
+const POSTCODE_2_LENGTH_BITS: [u8; 6] = [39, 40, 41, 42, 31, 32];
+const POSTCODE_2_BITS: [u8; 30] = [
+    33, 34, 35, 36, 25, 26, 27, 28, 29, 30, 19, 20, 21, 22, 23, 24, 13, 14, 15, 16, 17, 18, 7, 8,
+    9, 10, 11, 12, 1, 2,
+];
+const POSTCODE_3_BITS: [[u8; 6]; 6] = [
+    [39, 40, 41, 42, 31, 32],
+    [33, 34, 35, 36, 25, 26],
+    [27, 28, 29, 30, 19, 20],
+    [21, 22, 23, 24, 13, 14],
+    [15, 16, 17, 18, 7, 8],
+    [9, 10, 11, 12, 1, 2],
+];
+
+// 码集 A; 控制字符 (ECI, FS, GS, RS, NS, PAD, 移位与锁定) 不会出现在邮政编码中, 用 0 占位
+const CODE_SET_A: &[u8; 64] =
+    b"\rABCDEFGHIJKLMNOPQRSTUVWXYZ\0\0\0\0\0 \0\"#$%&'()*+,-./0123456789:\0\0\0\0\0";
+
+// 位编号从 1 开始, 每个码字的最高位在前
+fn bit(bytes: &[u8], bit: u8) -> u32 {
+    let bit = usize::from(bit) - 1;
+    u32::from(bytes[bit / 6] & (1 << (5 - bit % 6)) != 0)
+}
+
+fn int(bytes: &[u8], bits: &[u8]) -> u32 {
+    bits.iter()
+        .fold(0, |value, &b| (value << 1) | bit(bytes, b))
+}
+
+pub(crate) fn fields(raw_bytes: &[u8]) -> Option<MaxiCodeFields> {
+    let mode = raw_bytes.first()? & 0x0F;
+    if !matches!(mode, 2 | 3) || raw_bytes.len() < 10 {
+        return Some(MaxiCodeFields {
+            mode,
+            postal_code: None,
+            country_code: None,
+            service_class: None,
+        });
+    }
+    let postal_code = if mode == 2 {
+        let length = int(raw_bytes, &POSTCODE_2_LENGTH_BITS) as usize;
+        (length <= 10)
+            .then(|| format!("{:0>length$}", int(raw_bytes, &POSTCODE_2_BITS)))
+            .filter(|code| code.len() == length)
+    } else {
+        // 模式 3 为 6 个码集 A 字符, 不足 6 位的邮编以空格补齐
+        POSTCODE_3_BITS
+            .iter()
+            .map(|bits| CODE_SET_A[int(raw_bytes, bits) as usize])
+            .map(|c| (c != 0 && c != b'\r').then_some(c as char))
+            .collect::<Option<String>>()
+            .map(|code| code.trim_end().to_string())
+    };
+    Some(MaxiCodeFields {
+        mode,
+        postal_code,
+        country_code: Some(int(raw_bytes, &COUNTRY_BITS)),
+        service_class: Some(int(raw_bytes, &SERVICE_CLASS_BITS)),
+    })
+}
@@ -0,0 +1,73 @@
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+use rxing::RXingResultMetadataValue;
+
+// 全部元数据键, 即 rxing 的 RXingResultMetadataType 名称. Python 中的 MetadataKey 枚举由此生成
+pub(crate) const METADATA_KEYS: [&str; 17] = [
+    "OTHER",
+    "ORIENTATION",
+    "BYTE_SEGMENTS",
+    "ERROR_CORRECTION_LEVEL",
+    "ISSUE_NUMBER",
+    "SUGGESTED_PRICE",
+    "POSSIBLE_COUNTRY",
+    "UPC_EAN_EXTENSION",
+    "PDF417_EXTRA_METADATA",
+    "STRUCTURED_APPEND_SEQUENCE",
+    "STRUCTURED_APPEND_PARITY",
+    "SYMBOLOGY_IDENTIFIER",
+    "IS_MIRRORED",
+    "CONTENT_TYPE",
+    "IS_INVERTED",
+    "FILTERED_CLOSED",
+    "FILTERED_RESOLUTION",
+];
+
+// 字符串值为 str, 整数为 int, 标志为 bool, BYTE_SEGMENTS 为 bytes 列表, FILTERED_RESOLUTION 为
+// (宽, 高), PDF417_EXTRA_METADATA 为宏 PDF417 字段的 dict (未出现的字段为 None)
+pub(crate) fn to_object(py: Python, value: &RXingResultMetadataValue) -> PyResult<PyObject> {
+    use RXingResultMetadataValue::*;
+    let value = match value {
+        OTHER(s)
+        | ErrorCorrectionLevel(s)
+        | SuggestedPrice(s)
+        | PossibleCountry(s)
+        | UpcEanExtension(s)
+        | SymbologyIdentifier(s)
+        | ContentType(s) => s.into_pyobject(py)?.into_any(),
+        Orientation(n)
+        | IssueNumber(n)
+        | StructuredAppendSequence(n)
+        | StructuredAppendParity(n) => n.into_pyobject(py)?.into_any(),
+        IsMirrored(b) | IsInverted(b) | FilteredClosed(b) => {
+            b.into_pyobject(py)?.to_owned().into_any()
+        }
+        ByteSegments(segments) => segments
+            .iter()
+            .map(|segment| PyBytes::new(py, segment))
+            .collect::<Vec<_>>()
+            .into_pyobject(py)?
+            .into_any(),
+        FilteredResolution(size) => size.into_pyobject(py)?.into_any(),
+        Pdf417ExtraMetadata(macro_pdf417) => {
+            let text = |s: &str| (!s.is_empty()).then(|| s.to_string());
+            let dict = PyDict::new(py);
+            dict.set_item("segment_index", macro_pdf417.getSegmentIndex())?;
+            dict.set_item("file_id", text(macro_pdf417.getFileId()))?;
+            dict.set_item("last_segment", macro_pdf417.isLastSegment())?;
+            let count = macro_pdf417.getSegmentCount();
+            dict.set_item("segment_count", (count >= 0).then_some(count))?;
+            dict.set_item("sender", text(macro_pdf417.getSender()))?;
+            dict.set_item("addressee", text(macro_pdf417.getAddressee()))?;
+            dict.set_item("file_name", text(macro_pdf417.getFileName()))?;
+            let size = macro_pdf417.getFileSize();
+            dict.set_item("file_size", (size >= 0).then_some(size))?;
+            let checksum = macro_pdf417.getChecksum();
+            dict.set_item("checksum", (checksum >= 0).then_some(checksum))?;
+            let timestamp = macro_pdf417.getTimestamp();
+            dict.set_item("timestamp", (timestamp >= 0).then_some(timestamp))?;
+            dict.into_any()
+        }
+    };
+    Ok(value.unbind())
+}
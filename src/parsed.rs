@@ -0,0 +1,148 @@
+use pyo3::prelude::*;
+use pyo3::types::{timezone_utc, PyDateTime, PyDict};
+use rxing::client::result::{ParsedClientResult, ParsedRXingResult};
+
+// rxing 的 ResultParser 解析结果转换为 (类型名, 显示文本, 字段). 字段名为 Python 风格的小写名称;
+// rxing 以空字符串表示的缺失值为 None
+pub(crate) fn parsed_fields<'py>(
+    py: Python<'py>,
+    parsed: &ParsedClientResult,
+) -> PyResult<(String, String, Bound<'py, PyDict>)> {
+    let fields = PyDict::new(py);
+    let text = |s: &str| (!s.is_empty()).then(|| s.to_string());
+    match parsed {
+        ParsedClientResult::TextResult(r) => {
+            fields.set_item("text", r.getText())?;
+            fields.set_item("language", text(r.getLanguage()))?;
+        }
+        ParsedClientResult::URIResult(r) => {
+            fields.set_item("uri", r.getURI())?;
+            fields.set_item("title", text(r.getTitle()))?;
+        }
+        ParsedClientResult::WiFiResult(r) => {
+            fields.set_item("ssid", r.getSsid())?;
+            fields.set_item("encryption", text(r.getNetworkEncryption()))?;
+            fields.set_item("password", text(r.getPassword()))?;
+            fields.set_item("hidden", r.isHidden())?;
+            fields.set_item("identity", text(r.getIdentity()))?;
+            fields.set_item("anonymous_identity", text(r.getAnonymousIdentity()))?;
+            fields.set_item("eap_method", text(r.getEapMethod()))?;
+        }
+        ParsedClientResult::AddressBookResult(r) => {
+            fields.set_item("names", r.getNames())?;
+            fields.set_item("nicknames", r.getNicknames())?;
+            fields.set_item("pronunciation", text(r.getPronunciation()))?;
+            fields.set_item("phone_numbers", r.getPhoneNumbers())?;
+            fields.set_item("phone_types", r.getPhoneTypes())?;
+            fields.set_item("emails", r.getEmails())?;
+            fields.set_item("email_types", r.getEmailTypes())?;
+            fields.set_item("instant_messenger", text(r.getInstantMessenger()))?;
+            fields.set_item("note", text(r.getNote()))?;
+            fields.set_item("addresses", r.getAddresses())?;
+            fields.set_item("address_types", r.getAddressTypes())?;
+            fields.set_item("title", text(r.getTitle()))?;
+            fields.set_item("org", text(r.getOrg()))?;
+            fields.set_item("urls", r.getURLs())?;
+            fields.set_item("birthday", text(r.getBirthday()))?;
+            fields.set_item("geo", r.getGeo())?;
+        }
+        ParsedClientResult::GeoResult(r) => {
+            fields.set_item("latitude", r.getLatitude())?;
+            fields.set_item("longitude", r.getLongitude())?;
+            // 未给出时为 0.0
+            fields.set_item("altitude", r.getAltitude())?;
+            fields.set_item("query", text(r.getQuery()))?;
+            fields.set_item("geo_uri", r.getGeoURI())?;
+        }
+        ParsedClientResult::SMSResult(r) => {
+            fields.set_item("numbers", r.getNumbers())?;
+            fields.set_item("vias", r.getVias())?;
+            fields.set_item("subject", text(r.getSubject()))?;
+            fields.set_item("body", text(r.getBody()))?;
+            fields.set_item("sms_uri", r.getSMSURI())?;
+        }
+        ParsedClientResult::EmailResult(r) => {
+            fields.set_item("tos", r.getTos())?;
+            fields.set_item("ccs", r.getCCs())?;
+            fields.set_item("bccs", r.getBCCs())?;
+            fields.set_item("subject", text(r.getSubject()))?;
+            fields.set_item("body", text(r.getBody()))?;
+        }
+        ParsedClientResult::TelResult(r) => {
+            fields.set_item("number", r.getNumber())?;
+            fields.set_item("tel_uri", r.getTelURI())?;
+            fields.set_item("title", text(r.getTitle()))?;
+        }
+        ParsedClientResult::CalendarEventResult(r) => {
+            let utc = timezone_utc(py);
+            fields.set_item("summary", text(r.getSummary()))?;
+            // rxing 的时间戳为 Unix 纪元秒, 没有结束时间时为 -1
+            let start = PyDateTime::from_timestamp(py, r.getStartTimestamp() as f64, Some(&utc))?;
+            fields.set_item("start", start)?;
+            fields.set_item("start_all_day", r.isStartAllDay())?;
+            let end = match r.getEndTimestamp() {
+                -1 => None,
+                end => Some(PyDateTime::from_timestamp(py, end as f64, Some(&utc))?),
+            };
+            fields.set_item("end", end)?;
+            fields.set_item("end_all_day", r.isEndAllDay())?;
+            fields.set_item("location", text(r.getLocation()))?;
+            fields.set_item("organizer", text(r.getOrganizer()))?;
+            fields.set_item("attendees", r.getAttendees())?;
+            fields.set_item("description", text(r.getDescription()))?;
+            // 没有 GEO 属性时为 NaN
+            let geo = |v: f64| (!v.is_nan()).then_some(v);
+            fields.set_item("latitude", geo(r.getLatitude()))?;
+            fields.set_item("longitude", geo(r.getLongitude()))?;
+        }
+        ParsedClientResult::ProductResult(r) => {
+            fields.set_item("product_id", r.getProductID())?;
+            // UPC-E 展开为 UPC-A 后的号码, 其它格式与 product_id 相同
+            fields.set_item("normalized_product_id", r.getNormalizedProductID())?;
+        }
+        ParsedClientResult::ISBNResult(r) => {
+            fields.set_item("isbn", r.getISBN())?;
+        }
+        ParsedClientResult::VINResult(r) => {
+            fields.set_item("vin", r.getVIN())?;
+            fields.set_item("world_manufacturer_id", r.getWorldManufacturerID())?;
+            fields.set_item(
+                "vehicle_descriptor_section",
+                r.getVehicleDescriptorSection(),
+            )?;
+            fields.set_item(
+                "vehicle_identifier_section",
+                r.getVehicleIdentifierSection(),
+            )?;
+            fields.set_item("country_code", text(r.getCountryCode()))?;
+            fields.set_item("vehicle_attributes", r.getVehicleAttributes())?;
+            fields.set_item("model_year", r.getModelYear())?;
+            fields.set_item("plant_code", r.getPlantCode().to_string())?;
+            fields.set_item("sequential_number", r.getSequentialNumber())?;
+        }
+        // GS1 DataBar Expanded 的应用标识符; 类型同为 PRODUCT
+        ParsedClientResult::ExpandedProductResult(r) => {
+            fields.set_item("raw_text", r.getRawText())?;
+            fields.set_item("product_id", text(r.getProductID()))?;
+            fields.set_item("sscc", text(r.getSscc()))?;
+            fields.set_item("lot_number", text(r.getLotNumber()))?;
+            fields.set_item("production_date", text(r.getProductionDate()))?;
+            fields.set_item("packaging_date", text(r.getPackagingDate()))?;
+            fields.set_item("best_before_date", text(r.getBestBeforeDate()))?;
+            fields.set_item("expiration_date", text(r.getExpirationDate()))?;
+            fields.set_item("weight", text(r.getWeight()))?;
+            fields.set_item("weight_type", text(r.getWeightType()))?;
+            fields.set_item("weight_increment", text(r.getWeightIncrement()))?;
+            fields.set_item("price", text(r.getPrice()))?;
+            fields.set_item("price_increment", text(r.getPriceIncrement()))?;
+            fields.set_item("price_currency", text(r.getPriceCurrency()))?;
+            fields.set_item("uncommon_ais", r.getUncommonAIs())?;
+        }
+        ParsedClientResult::Other(_) => {}
+    }
+    Ok((
+        format!("{:?}", parsed.getType()),
+        parsed.getDisplayRXingResult(),
+        fields,
+    ))
+}
@@ -0,0 +1,289 @@
+use image::buffer::ConvertBuffer;
+use image::{DynamicImage, GrayImage, ImageError, ImageFormat, RgbImage, Rgba, RgbaImage};
+use pyo3::prelude::*;
+use rxing::common::BitMatrix;
+use std::io::{Cursor, Write};
+use std::path::Path;
+
+// 栅格化参数: 模块像素大小, 四周留白 (模块数), 一维码条高 (模块数) 以及前景/背景颜色
+pub(crate) struct RenderOptions {
+    pub module_size: u32,
+    pub border: u32,
+    pub bar_height: u32,
+    pub foreground: Rgba<u8>,
+    pub background: Rgba<u8>,
+}
+
+// 与 encode_to_png 的默认参数相同
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            module_size: 4,
+            border: 4,
+            bar_height: 50,
+            foreground: Rgba([0, 0, 0, 255]),
+            background: Rgba([255, 255, 255, 255]),
+        }
+    }
+}
+
+// 输出图像的颜色类型. 1bit 只有黑白两色, gray 只有灰度, 只有 rgba 保留透明度
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum PixelFormat {
+    OneBit,
+    Gray,
+    Rgb,
+    Rgba,
+}
+
+impl PixelFormat {
+    pub fn parse(pixel_format: &str) -> PyResult<Self> {
+        match pixel_format {
+            "1bit" => Ok(PixelFormat::OneBit),
+            "gray" => Ok(PixelFormat::Gray),
+            "rgb" => Ok(PixelFormat::Rgb),
+            "rgba" => Ok(PixelFormat::Rgba),
+            _ => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Unknown pixel_format {:?}; expected \"1bit\", \"gray\", \"rgb\" or \"rgba\".",
+                pixel_format
+            ))),
+        }
+    }
+
+    // 前景与背景颜色必须能用这种颜色类型原样表示, 否则报错而不是悄悄换算
+    pub fn check_colors(self, foreground: Rgba<u8>, background: Rgba<u8>) -> PyResult<()> {
+        let (name, needs) = match self {
+            PixelFormat::Rgba => return Ok(()),
+            PixelFormat::Rgb => ("rgb", "opaque colors"),
+            PixelFormat::Gray => ("gray", "opaque grays (equal r, g and b)"),
+            PixelFormat::OneBit => ("1bit", "opaque black or white"),
+        };
+        for (option, color) in [("fg", foreground), ("bg", background)] {
+            if !self.can_represent(color) {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "pixel_format {:?} needs {} for fg and bg, got {}={:?}; use pixel_format \"rgba\".",
+                    name,
+                    needs,
+                    option,
+                    (color[0], color[1], color[2], color[3])
+                )));
+            }
+        }
+        if self == PixelFormat::OneBit && foreground == background {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "pixel_format \"1bit\" needs one of fg and bg black and the other white.",
+            ));
+        }
+        Ok(())
+    }
+
+    fn can_represent(self, Rgba([r, g, b, a]): Rgba<u8>) -> bool {
+        match self {
+            PixelFormat::Rgba => true,
+            PixelFormat::Rgb => a == u8::MAX,
+            PixelFormat::Gray => a == u8::MAX && r == g && g == b,
+            PixelFormat::OneBit => a == u8::MAX && r == g && g == b && matches!(r, 0 | u8::MAX),
+        }
+    }
+}
+
+impl RenderOptions {
+    // 一维码的 BitMatrix 只有一行, 渲染时重复为 bar_height 行
+    fn module_rows(&self, matrix: &BitMatrix) -> u32 {
+        if matrix.getHeight() == 1 {
+            self.bar_height
+        } else {
+            matrix.getHeight()
+        }
+    }
+
+    // 按需增大 module_size (一维码还有 bar_height), 使图像至少为 width x height 像素
+    pub fn grow_to_fit(&mut self, matrix: &BitMatrix, width: u32, height: u32) {
+        let quiet_zone = self.border.saturating_mul(2);
+        let columns = matrix.getWidth().saturating_add(quiet_zone);
+        self.module_size = self.module_size.max(width.div_ceil(columns));
+        if matrix.getHeight() == 1 {
+            let rows = height.div_ceil(self.module_size.max(1));
+            self.bar_height = self.bar_height.max(rows.saturating_sub(quiet_zone));
+        } else {
+            let rows = matrix.getHeight().saturating_add(quiet_zone);
+            self.module_size = self.module_size.max(height.div_ceil(rows));
+        }
+    }
+
+    // 校验参数并返回输出图像的像素尺寸
+    fn image_size(&self, matrix: &BitMatrix) -> PyResult<(u32, u32)> {
+        if self.module_size == 0 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "module_size must be at least 1 pixel.",
+            ));
+        }
+        if self.bar_height == 0 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "bar_height must be at least 1 module.",
+            ));
+        }
+        let to_pixels = |modules: u32| {
+            self.border
+                .checked_mul(2)
+                .and_then(|quiet_zone| quiet_zone.checked_add(modules))
+                .and_then(|total| total.checked_mul(self.module_size))
+        };
+        match (
+            to_pixels(matrix.getWidth()),
+            to_pixels(self.module_rows(matrix)),
+        ) {
+            (Some(width), Some(height))
+                if width <= i32::MAX as u32 && height <= i32::MAX as u32 =>
+            {
+                Ok((width, height))
+            }
+            _ => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "A {}x{} symbol with module_size {} and border {} does not fit in an image.",
+                matrix.getWidth(),
+                matrix.getHeight(),
+                self.module_size,
+                self.border
+            ))),
+        }
+    }
+}
+
+pub(crate) fn render_rgba(matrix: &BitMatrix, options: &RenderOptions) -> PyResult<RgbaImage> {
+    let (width, height) = options.image_size(matrix)?;
+    let mut image = RgbaImage::from_pixel(width, height, options.background);
+    let single_row = matrix.getHeight() == 1;
+    for row in 0..options.module_rows(matrix) {
+        let y = if single_row { 0 } else { row };
+        for x in 0..matrix.getWidth() {
+            if !matrix.get(x, y) {
+                continue;
+            }
+            let left = (options.border + x) * options.module_size;
+            let top = (options.border + row) * options.module_size;
+            for py in top..top + options.module_size {
+                for px in left..left + options.module_size {
+                    image.put_pixel(px, py, options.foreground);
+                }
+            }
+        }
+    }
+    Ok(image)
+}
+
+// 把多张条码图排成 columns 列的网格. 各单元格使用统一的间距 (最大宽度/高度),
+// 尺寸较小的条码在单元格内居中; 单元格之间留 spacing 像素的背景色
+pub(crate) fn compose_sheet(
+    cells: &[RgbaImage],
+    columns: u32,
+    spacing: u32,
+    background: Rgba<u8>,
+) -> PyResult<RgbaImage> {
+    if columns == 0 {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "columns must be at least 1.",
+        ));
+    }
+    let cell_width = cells.iter().map(|cell| cell.width()).max().unwrap_or(0);
+    let cell_height = cells.iter().map(|cell| cell.height()).max().unwrap_or(0);
+    let count = u32::try_from(cells.len()).unwrap_or(u32::MAX);
+    let columns = columns.min(count.max(1));
+    let rows = count.div_ceil(columns);
+    let span = |cells: u32, pitch: u32| {
+        pitch
+            .checked_mul(cells)?
+            .checked_add(spacing.checked_mul(cells.saturating_sub(1))?)
+            .filter(|&total| total <= i32::MAX as u32)
+    };
+    let (Some(width), Some(height)) = (span(columns, cell_width), span(rows, cell_height)) else {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "A {}x{} sheet of {}x{} cells with spacing {} does not fit in an image.",
+            columns, rows, cell_width, cell_height, spacing
+        )));
+    };
+    let mut sheet = RgbaImage::from_pixel(width, height, background);
+    for (index, cell) in (0u32..).zip(cells) {
+        let left = (index % columns) * (cell_width + spacing) + (cell_width - cell.width()) / 2;
+        let top = (index / columns) * (cell_height + spacing) + (cell_height - cell.height()) / 2;
+        image::imageops::replace(&mut sheet, cell, i64::from(left), i64::from(top));
+    }
+    Ok(sheet)
+}
+
+pub(crate) fn encode_png(image: &RgbaImage, pixel_format: PixelFormat) -> PyResult<Vec<u8>> {
+    let mut buffer = Cursor::new(Vec::new());
+    let result = match pixel_format {
+        PixelFormat::OneBit => write_one_bit_png(image, &mut buffer).map_err(|e| e.to_string()),
+        _ => convert(image, pixel_format)
+            .write_to(&mut buffer, ImageFormat::Png)
+            .map_err(|e| format!("{:?}", e)),
+    };
+    match result {
+        Ok(()) => Ok(buffer.into_inner()),
+        Err(e) => Err(PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
+            "Failed to write PNG: {}",
+            e
+        ))),
+    }
+}
+
+// gray / rgb 由 check_colors 保证颜色可以原样表示, 转换不会改变像素值
+fn convert(image: &RgbaImage, pixel_format: PixelFormat) -> DynamicImage {
+    match pixel_format {
+        PixelFormat::OneBit | PixelFormat::Gray => {
+            DynamicImage::ImageLuma8(ConvertBuffer::<GrayImage>::convert(image))
+        }
+        PixelFormat::Rgb => DynamicImage::ImageRgb8(ConvertBuffer::<RgbImage>::convert(image)),
+        PixelFormat::Rgba => DynamicImage::ImageRgba8(image.clone()),
+    }
+}
+
+// image 不能写出 1 位 PNG, 这里直接用 png: 每像素一位, 0 为黑, 1 为白, 每行补齐到整字节
+fn write_one_bit_png<W: Write>(image: &RgbaImage, writer: W) -> Result<(), png::EncodingError> {
+    let mut encoder = png::Encoder::new(writer, image.width(), image.height());
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::One);
+    encoder.set_filter(png::FilterType::NoFilter);
+    encoder.set_compression(png::Compression::Best);
+    let row_bytes = (image.width() as usize).div_ceil(8);
+    let mut data = vec![0u8; row_bytes * image.height() as usize];
+    for (x, y, pixel) in image.enumerate_pixels() {
+        if pixel[0] > 127 {
+            data[y as usize * row_bytes + x as usize / 8] |= 0x80 >> (x % 8);
+        }
+    }
+    encoder.write_header()?.write_image_data(&data)
+}
+
+// rgba 时 JPEG 等格式不支持透明通道, 降为 RGB 再保存
+pub(crate) fn save_image(
+    image: RgbaImage,
+    path: &Path,
+    format: ImageFormat,
+    pixel_format: PixelFormat,
+) -> PyResult<()> {
+    let result = match pixel_format {
+        // encode_to_file 已确认 1bit 的输出为 PNG
+        PixelFormat::OneBit => {
+            let png = encode_png(&image, pixel_format)?;
+            std::fs::write(path, png).map_err(ImageError::IoError)
+        }
+        PixelFormat::Rgba => {
+            let rgba = DynamicImage::ImageRgba8(image);
+            match rgba.save_with_format(path, format) {
+                Err(ImageError::Unsupported(_)) => {
+                    DynamicImage::ImageRgb8(rgba.to_rgb8()).save_with_format(path, format)
+                }
+                other => other,
+            }
+        }
+        _ => convert(&image, pixel_format).save_with_format(path, format),
+    };
+    result.map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
+            "Failed to write image file {}: {}",
+            path.display(),
+            e
+        ))
+    })
+}
@@ -0,0 +1,125 @@
+use pyo3::prelude::*;
+use rxing::common::BitMatrix;
+use std::fmt::Write;
+
+// SVG 输出参数, 含义与栅格化的 RenderOptions 相同; 颜色为 RGBA
+pub(crate) struct SvgOptions {
+    pub module_size: u32,
+    pub border: u32,
+    pub bar_height: u32,
+    pub foreground: (u8, u8, u8, u8),
+    pub background: (u8, u8, u8, u8),
+    pub shape: SvgShape,
+}
+
+// path: 所有深色模块合并为一个 <path> (同一行相邻的模块合并为一段), 文件最小;
+// rects: 每段一个 <rect>, 便于用 CSS 或脚本逐个处理
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum SvgShape {
+    Path,
+    Rects,
+}
+
+impl SvgShape {
+    pub fn parse(shape: &str) -> PyResult<Self> {
+        match shape.to_lowercase().as_str() {
+            "path" => Ok(SvgShape::Path),
+            "rects" => Ok(SvgShape::Rects),
+            _ => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "shape must be \"path\" or \"rects\", got {:?}",
+                shape
+            ))),
+        }
+    }
+}
+
+pub(crate) fn render_svg(matrix: &BitMatrix, options: &SvgOptions) -> PyResult<String> {
+    if options.module_size == 0 {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "module_size must be at least 1.",
+        ));
+    }
+    if options.bar_height == 0 {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "bar_height must be at least 1 module.",
+        ));
+    }
+    // 坐标以模块为单位, 由 viewBox 缩放到 width/height
+    let single_row = matrix.getHeight() == 1;
+    let rows = if single_row {
+        options.bar_height
+    } else {
+        matrix.getHeight()
+    };
+    let columns = u64::from(matrix.getWidth()) + 2 * u64::from(options.border);
+    let total_rows = u64::from(rows) + 2 * u64::from(options.border);
+    let module_size = u64::from(options.module_size);
+
+    let mut svg = String::new();
+    let _ = write!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" version=\"1.1\" width=\"{}\" height=\"{}\" \
+         viewBox=\"0 0 {} {}\" shape-rendering=\"crispEdges\">",
+        columns * module_size,
+        total_rows * module_size,
+        columns,
+        total_rows
+    );
+    if options.background.3 > 0 {
+        let _ = write!(
+            svg,
+            "<rect width=\"100%\" height=\"100%\"{}/>",
+            fill(options.background)
+        );
+    }
+
+    // 一维码只有一行, 每段直接画成 bar_height 高
+    let (run_rows, run_height) = if single_row { (1, rows) } else { (rows, 1) };
+    let mut runs = Vec::new();
+    for y in 0..run_rows {
+        let mut x = 0;
+        while x < matrix.getWidth() {
+            if !matrix.get(x, y) {
+                x += 1;
+                continue;
+            }
+            let start = x;
+            while x < matrix.getWidth() && matrix.get(x, y) {
+                x += 1;
+            }
+            runs.push((start + options.border, y + options.border, x - start));
+        }
+    }
+
+    match options.shape {
+        SvgShape::Path => {
+            let _ = write!(svg, "<path{} d=\"", fill(options.foreground));
+            for (x, y, width) in runs {
+                let _ = write!(svg, "M{} {}h{}v{}h-{}z", x, y, width, run_height, width);
+            }
+            svg.push_str("\"/>");
+        }
+        SvgShape::Rects => {
+            let _ = write!(svg, "<g{}>", fill(options.foreground));
+            for (x, y, width) in runs {
+                let _ = write!(
+                    svg,
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/>",
+                    x, y, width, run_height
+                );
+            }
+            svg.push_str("</g>");
+        }
+    }
+    svg.push_str("</svg>");
+    Ok(svg)
+}
+
+// 不透明的颜色只写 fill, 半透明时另加 fill-opacity
+fn fill((r, g, b, a): (u8, u8, u8, u8)) -> String {
+    let mut attribute = format!(" fill=\"#{:02x}{:02x}{:02x}\"", r, g, b);
+    if a < u8::MAX {
+        let _ = write!(attribute, " fill-opacity=\"{:.3}\"", f32::from(a) / 255.0);
+    }
+    attribute
+}
@@ -0,0 +1,258 @@
+use rxing::common::{BitMatrix, DetectorRXingResult, PerspectiveTransform, Quadrilateral};
+use rxing::datamatrix::decoder::{Decoder as DataMatrixDecoder, Version as DataMatrixVersion};
+use rxing::datamatrix::detector::{zxing_cpp_detector, Detector as DataMatrixDetector};
+use rxing::qrcode::cpp_port::{decoder as qr_decoder, detector as qr_detector};
+use rxing::qrcode::decoder::qrcode_decoder as qr_code_decoder;
+use rxing::{
+    point_f, BarcodeFormat, Point, RXingResult, RXingResultMetadataType, RXingResultMetadataValue,
+};
+use std::sync::{Arc, OnceLock};
+
+// rxing 的结果中没有的符号信息, 从采样网格读出
+#[derive(Clone, Default)]
+pub(crate) struct SymbolDetails {
+    // 模块数 (宽, 高)
+    pub size: Option<(u32, u32)>,
+    pub structured_append: Option<StructuredAppend>,
+    // QR 系列与结果一致的采样网格, 供 LazySymbol::byte_segments 再解码一次
+    qr_bits: Option<BitMatrix>,
+}
+
+// 同一图像的多个结果共用一份二值化矩阵的副本, 在第一个需要它的结果处复制
+pub(crate) struct MatrixCopy<'a> {
+    matrix: &'a BitMatrix,
+    copy: Option<Arc<BitMatrix>>,
+}
+
+impl<'a> MatrixCopy<'a> {
+    pub(crate) fn new(matrix: &'a BitMatrix) -> Self {
+        MatrixCopy { matrix, copy: None }
+    }
+
+    fn get(&mut self) -> Arc<BitMatrix> {
+        self.copy
+            .get_or_insert_with(|| Arc::new(self.matrix.clone()))
+            .clone()
+    }
+}
+
+// 读取符号信息要重新定位并解码符号, 代价与一次解码相当, 因此只保留解码时的矩阵,
+// 第一次读取时才计算并缓存. 只有 QR 系列与 Data Matrix 有这些信息.
+// 字节段还要在采样网格上多解码一次, 单独缓存, 只在读取字节段时计算
+#[derive(Clone)]
+pub(crate) struct LazySymbol {
+    matrix: Arc<BitMatrix>,
+    result: Arc<RXingResult>,
+    details: Arc<OnceLock<SymbolDetails>>,
+    byte_segments: Arc<OnceLock<Option<Vec<Vec<u8>>>>>,
+}
+
+impl LazySymbol {
+    pub(crate) fn new(result: &RXingResult, matrix: &mut MatrixCopy) -> Option<Self> {
+        match result.getBarcodeFormat() {
+            BarcodeFormat::QR_CODE
+            | BarcodeFormat::MICRO_QR_CODE
+            | BarcodeFormat::RECTANGULAR_MICRO_QR_CODE
+            | BarcodeFormat::DATA_MATRIX => Some(LazySymbol {
+                matrix: matrix.get(),
+                result: Arc::new(result.clone()),
+                details: Arc::new(OnceLock::new()),
+                byte_segments: Arc::new(OnceLock::new()),
+            }),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn format(&self) -> &BarcodeFormat {
+        self.result.getBarcodeFormat()
+    }
+
+    pub(crate) fn details(&self) -> &SymbolDetails {
+        self.details
+            .get_or_init(|| symbol_details(&self.matrix, &self.result))
+    }
+
+    // rxing 没有给出 BYTE_SEGMENTS 时恢复的字节段; rxing 给出时为 None, 以元数据中的为准
+    pub(crate) fn byte_segments(&self) -> Option<&Vec<Vec<u8>>> {
+        self.byte_segments
+            .get_or_init(|| {
+                let reported = self
+                    .result
+                    .getRXingResultMetadata()
+                    .contains_key(&RXingResultMetadataType::BYTE_SEGMENTS);
+                if reported || *self.result.getBarcodeFormat() != BarcodeFormat::QR_CODE {
+                    return None;
+                }
+                qr_byte_segments(self.details().qr_bits.as_ref()?)
+            })
+            .as_ref()
+    }
+}
+
+// 在二值化矩阵上重新定位符号, 取解码文本与结果一致的采样网格 (QR 系列只采样一次);
+// 无法确定的信息为 None
+fn symbol_details(matrix: &BitMatrix, result: &RXingResult) -> SymbolDetails {
+    match result.getBarcodeFormat() {
+        BarcodeFormat::QR_CODE
+        | BarcodeFormat::MICRO_QR_CODE
+        | BarcodeFormat::RECTANGULAR_MICRO_QR_CODE => {
+            let Some(bits) = qr_symbol_bits(matrix, result) else {
+                return SymbolDetails::default();
+            };
+            SymbolDetails {
+                size: Some((bits.width(), bits.height())),
+                structured_append: structured_append(&bits, result),
+                qr_bits: Some(bits),
+            }
+        }
+        BarcodeFormat::DATA_MATRIX => SymbolDetails {
+            size: data_matrix_symbol_size(matrix, result),
+            ..SymbolDetails::default()
+        },
+        _ => SymbolDetails::default(),
+    }
+}
+
+// QR 为版本 1-40, Micro QR 为 M1-M4 中的数字, Data Matrix 为 ECC 200 的版本号 1-30
+pub(crate) fn symbol_version(format: &BarcodeFormat, size: (u32, u32)) -> Option<u32> {
+    let (width, height) = size;
+    match format {
+        BarcodeFormat::QR_CODE if width >= 21 => Some((width - 17) / 4),
+        BarcodeFormat::MICRO_QR_CODE if width >= 11 => Some((width - 9) / 2),
+        BarcodeFormat::DATA_MATRIX => DataMatrixVersion::getVersionForDimensions(height, width)
+            .ok()
+            .map(|version| version.getVersionNumber()),
+        _ => None,
+    }
+}
+
+// QR 结构化追加 (structured append) 的位置与校验: 第 index 个 (从 0 开始), 共 total 个,
+// parity 为整条消息所有字节的异或值, 同一消息的各个符号相同
+#[derive(Clone, Copy)]
+pub(crate) struct StructuredAppend {
+    pub index: u32,
+    pub total: u32,
+    pub parity: u8,
+}
+
+// rxing 的两个 QR 读码器写入元数据的含义不同, 且 C++ 移植版不保留 parity,
+// 因此在元数据表明是结构化追加符号时, 重新采样网格并读取符号头
+fn structured_append(bits: &BitMatrix, result: &RXingResult) -> Option<StructuredAppend> {
+    if *result.getBarcodeFormat() != BarcodeFormat::QR_CODE {
+        return None;
+    }
+    match result
+        .getRXingResultMetadata()
+        .get(&RXingResultMetadataType::STRUCTURED_APPEND_SEQUENCE)
+    {
+        Some(RXingResultMetadataValue::StructuredAppendSequence(sequence)) if *sequence >= 0 => {}
+        _ => return None,
+    }
+    let decoded = qr_decoder::Decode(bits).ok()?;
+    let info = decoded.structuredAppend();
+    Some(StructuredAppend {
+        index: u32::try_from(info.index).ok()?,
+        total: u32::try_from(info.count).ok()?,
+        parity: info.id.parse().ok()?,
+    })
+}
+
+// rxing 优先使用的 C++ 移植版 QR 读码器不记录字节段, 用 ZXing 移植版的解码器在同一网格上
+// 重新解码; 后者不支持 Micro QR 与 rMQR
+fn qr_byte_segments(bits: &BitMatrix) -> Option<Vec<Vec<u8>>> {
+    let decoded = qr_code_decoder::decode_bitmatrix(bits).ok()?;
+    let segments = decoded.getByteSegments();
+    (!segments.is_empty()).then(|| segments.clone())
+}
+
+// 重新定位符号, 返回解码文本与结果一致的采样网格
+fn qr_symbol_bits(matrix: &BitMatrix, result: &RXingResult) -> Option<BitMatrix> {
+    let matches = |bits: &BitMatrix| {
+        qr_decoder::Decode(bits).is_ok_and(|decoded| decoded.text() == result.getText())
+    };
+    let pure = [
+        qr_detector::DetectPureQR,
+        qr_detector::DetectPureMQR,
+        qr_detector::DetectPureRMQR,
+    ];
+    if let Some(bits) = pure
+        .iter()
+        .filter_map(|detect| detect(matrix).ok())
+        .map(|detected| detected.getBits().clone())
+        .find(|bits| matches(bits))
+    {
+        return Some(bits);
+    }
+
+    let mut patterns = qr_detector::FindFinderPatterns(matrix, true);
+    if *result.getBarcodeFormat() == BarcodeFormat::QR_CODE {
+        qr_detector::GenerateFinderPatternSets(&mut patterns)
+            .iter()
+            .filter_map(|set| qr_detector::SampleQR(matrix, set).ok())
+            .map(|detected| detected.getBits().clone())
+            .find(|bits| matches(bits))
+    } else {
+        patterns
+            .iter()
+            .flat_map(|pattern| {
+                [
+                    qr_detector::SampleMQR(matrix, *pattern),
+                    qr_detector::SampleRMQR(matrix, *pattern),
+                ]
+            })
+            .filter_map(|detected| detected.ok())
+            .map(|detected| detected.getBits().clone())
+            .find(|bits| matches(bits))
+    }
+}
+
+fn data_matrix_symbol_size(matrix: &BitMatrix, result: &RXingResult) -> Option<(u32, u32)> {
+    let decoder = DataMatrixDecoder::new();
+    let matches = |bits: &BitMatrix| {
+        decoder
+            .decode(bits)
+            .is_ok_and(|decoded| decoded.getText() == result.getText())
+    };
+    let mut candidates = zxing_cpp_detector::detect(matrix, true, true)
+        .map(|detected| {
+            detected
+                .iter()
+                .map(|symbol| symbol.getBits().clone())
+                .collect()
+        })
+        .unwrap_or_else(|_| Vec::new());
+    if let Ok(detected) = DataMatrixDetector::new(matrix).and_then(|detector| detector.detect()) {
+        candidates.push(detected.getBits().clone());
+    }
+    let bits = candidates.into_iter().find(|bits| matches(bits))?;
+    Some((bits.width(), bits.height()))
+}
+
+// QR 码的定位点位置. rxing 只给出符号的四个外角 (按符号方向的左上, 右上, 右下, 左下),
+// 这里把模块坐标经透视变换映射到图像中
+pub(crate) struct QrLandmarks {
+    // 左上, 右上, 左下三个位置探测图形的中心
+    pub finder_patterns: [Point; 3],
+    // 右下角的校正图形中心; 版本 1 没有校正图形
+    pub alignment_pattern: Option<Point>,
+    pub bottom_right: Point,
+}
+
+pub(crate) fn qr_landmarks(corners: [Point; 4], modules: u32) -> Option<QrLandmarks> {
+    let n = modules as f32;
+    let module_square = Quadrilateral([
+        point_f(0.0, 0.0),
+        point_f(n, 0.0),
+        point_f(n, n),
+        point_f(0.0, n),
+    ]);
+    let transform =
+        PerspectiveTransform::quadrilateralToQuadrilateral(module_square, Quadrilateral(corners))
+            .ok()?;
+    let at = |x: f32, y: f32| transform.transform_point(point_f(x, y));
+    Some(QrLandmarks {
+        finder_patterns: [at(3.5, 3.5), at(n - 3.5, 3.5), at(3.5, n - 3.5)],
+        alignment_pattern: (modules >= 25).then(|| at(n - 6.5, n - 6.5)),
+        bottom_right: corners[2],
+    })
+}
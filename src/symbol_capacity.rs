@@ -0,0 +1,180 @@
+use rxing::datamatrix::decoder::Version as DataMatrixVersion;
+use rxing::qrcode::decoder::{ErrorCorrectionLevel, Mode, Version as QrVersion};
+use rxing::{BarcodeFormat, EncodeHints};
+use std::str::FromStr;
+
+// 单一模式下一个符号最多能容纳的字符数 (byte 模式为字节数), 由 rxing 的版本表算出.
+// version 为 symbol_version 报告的版本号, 省略时取最大的符号
+pub(crate) fn capacity(
+    format: &BarcodeFormat,
+    version: Option<u32>,
+    ecc: Option<&str>,
+    mode: &str,
+) -> Result<u32, String> {
+    match format {
+        BarcodeFormat::QR_CODE => qr_capacity(version.unwrap_or(40), ecc.unwrap_or("L"), mode),
+        BarcodeFormat::DATA_MATRIX => {
+            if let Some(ecc) = ecc {
+                return Err(format!(
+                    "DATA_MATRIX has a fixed error correction level, got ecc {:?}",
+                    ecc
+                ));
+            }
+            data_matrix_capacity(version.unwrap_or(24), mode)
+        }
+        _ => Err(format!(
+            "capacity is only known for QR_CODE and DATA_MATRIX, got {:?}",
+            format
+        )),
+    }
+}
+
+// 编码前检查数据是否放得下最大的符号, 放不下时返回说明. 这里只按下界估算,
+// 放得下的数据不会被拒绝; 边界附近的情况仍由编码器判断
+pub(crate) fn check_fits(
+    format: &BarcodeFormat,
+    data: &str,
+    hints: &EncodeHints,
+) -> Result<(), String> {
+    match format {
+        BarcodeFormat::QR_CODE => check_qr_fits(data, hints),
+        BarcodeFormat::DATA_MATRIX => check_data_matrix_fits(data),
+        _ => Ok(()),
+    }
+}
+
+fn check_qr_fits(data: &str, hints: &EncodeHints) -> Result<(), String> {
+    // QR_COMPACT 会混合多种模式, 单一模式的容量不再是上限
+    if hints
+        .QrCompact
+        .as_deref()
+        .is_some_and(|compact| compact.parse() == Ok(true))
+    {
+        return Ok(());
+    }
+    let version = match hints.QrVersion.as_deref().map(str::parse::<u32>) {
+        None => 40,
+        Some(Ok(version @ 1..=40)) => version,
+        // 无效的版本号由编码器报错
+        Some(_) => return Ok(()),
+    };
+    let ecc = hints.ErrorCorrection.as_deref().unwrap_or("L");
+    // 与编码器选择模式的方式一致; byte 模式每个字符至少一个字节
+    let mode = if !data.is_empty() && data.chars().all(|c| c.is_ascii_digit()) {
+        "numeric"
+    } else if !data.is_empty() && data.chars().all(|c| QR_ALPHANUMERIC.contains(c)) {
+        "alphanumeric"
+    } else {
+        "byte"
+    };
+    let characters = data.chars().count();
+    let capacity = qr_capacity(version, ecc, mode)?;
+    if characters > capacity as usize {
+        return Err(format!(
+            "{} characters in {} mode, but version {} with ecc {} holds {}",
+            characters,
+            mode,
+            version,
+            ecc.to_uppercase(),
+            capacity
+        ));
+    }
+    Ok(())
+}
+
+const QR_ALPHANUMERIC: &str = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:";
+
+fn check_data_matrix_fits(data: &str) -> Result<(), String> {
+    // 以 1/6 码字为单位: 数字在 ASCII 下每个 1/2 码字, 其它 ASCII 字符在 C40/Text/X12 下
+    // 至少 2/3 码字, 其余字符在 Base 256 下至少 1 码字
+    let sixths: usize = data
+        .chars()
+        .map(|c| match c {
+            '0'..='9' => 3,
+            c if c.is_ascii() => 4,
+            _ => 6,
+        })
+        .sum();
+    let needed = sixths.div_ceil(6);
+    let capacity = data_matrix_codewords(24)?;
+    if needed > capacity as usize {
+        return Err(format!(
+            "needs at least {} codewords, but the largest symbol holds {}",
+            needed, capacity
+        ));
+    }
+    Ok(())
+}
+
+fn qr_capacity(version: u32, ecc: &str, mode: &str) -> Result<u32, String> {
+    let symbol = match version {
+        1..=40 => QrVersion::getVersionForNumber(version).map_err(|e| e.to_string())?,
+        _ => return Err(format!("QR_CODE version must be 1-40, got {}", version)),
+    };
+    let level = match ecc.to_uppercase().as_str() {
+        "L" | "M" | "Q" | "H" => ErrorCorrectionLevel::from_str(ecc).map_err(|e| e.to_string())?,
+        _ => return Err(format!("QR_CODE ecc must be L, M, Q or H, got {:?}", ecc)),
+    };
+    let mode = match mode {
+        "numeric" => Mode::NUMERIC,
+        "alphanumeric" => Mode::ALPHANUMERIC,
+        "byte" => Mode::BYTE,
+        "kanji" => Mode::KANJI,
+        _ => {
+            return Err(format!(
+                "QR_CODE mode must be numeric, alphanumeric, byte or kanji, got {:?}",
+                mode
+            ))
+        }
+    };
+    let data_codewords =
+        symbol.getTotalCodewords() - symbol.getECBlocksForLevel(level).getTotalECCodewords();
+    // 扣除 4 位模式指示符与字符计数
+    let count_bits = u32::from(mode.getCharacterCountBits(symbol));
+    let bits = data_codewords * 8 - 4 - count_bits;
+    let characters = match mode {
+        // 每 3 位数字 10 位, 余下 1 或 2 位数字占 4 或 7 位
+        Mode::NUMERIC => bits / 10 * 3 + [0, 0, 0, 0, 1, 1, 1, 2, 2, 2][(bits % 10) as usize],
+        // 每 2 个字符 11 位, 余下 1 个字符占 6 位
+        Mode::ALPHANUMERIC => bits / 11 * 2 + u32::from(bits % 11 >= 6),
+        Mode::BYTE => bits / 8,
+        _ => bits / 13,
+    };
+    Ok(characters.min((1 << count_bits) - 1))
+}
+
+fn data_matrix_capacity(version: u32, mode: &str) -> Result<u32, String> {
+    let data_codewords = data_matrix_codewords(version)?;
+    // ISO/IEC 16022: ASCII 每码字 2 位数字; C40 每 2 码字 3 个字符, 需 1 个码字锁定;
+    // Base 256 需锁定码字与长度码字
+    match mode {
+        "numeric" => Ok(data_codewords * 2),
+        "alphanumeric" => {
+            let c40 = data_codewords - 1;
+            Ok(c40 / 2 * 3 + c40 % 2)
+        }
+        "byte" => Ok(data_codewords.saturating_sub(2)),
+        _ => Err(format!(
+            "DATA_MATRIX mode must be numeric, alphanumeric or byte, got {:?}",
+            mode
+        )),
+    }
+}
+
+fn data_matrix_codewords(version: u32) -> Result<u32, String> {
+    // rxing 不公开版本表, 按尺寸逐个查找
+    let symbol = (8..=144)
+        .step_by(2)
+        .flat_map(|rows| (8..=144).step_by(2).map(move |columns| (rows, columns)))
+        .filter_map(|(rows, columns)| {
+            DataMatrixVersion::getVersionForDimensions(rows, columns).ok()
+        })
+        .find(|symbol| symbol.getVersionNumber() == version)
+        .ok_or_else(|| format!("DATA_MATRIX version must be 1-48, got {}", version))?;
+    Ok(symbol
+        .getECBlocks()
+        .getECBlocks()
+        .iter()
+        .map(|block| block.getCount() * block.getDataCodewords())
+        .sum())
+}
@@ -0,0 +1,129 @@
+// Aztec 数据字符的编码表, 与 rxing 的 Aztec 解码器相同
+#[derive(Clone, Copy, PartialEq)]
+enum Table {
+    Upper,
+    Lower,
+    Mixed,
+    Digit,
+    Punct,
+    Binary,
+}
+
+// ISO/IEC 24778 的 Aztec 符号标识符修饰符: 0 为普通数据, 1 为首位有 FNC1 (GS1), 2 为 FNC1
+// 跟在一个字母或两个数字之后 (AIM), 含 ECI 时再加 3. rxing 的 Aztec 读码器总是报告 ]z0,
+// 这里在纠错后的数据位 (raw_bytes 的前 num_bits 位) 上重新扫描 FLG(n) 字符
+pub(crate) fn aztec_modifier(raw_bytes: &[u8], num_bits: usize) -> Option<u32> {
+    if num_bits > raw_bytes.len() * 8 {
+        return None;
+    }
+    let read = |index: usize, size: usize| -> Option<usize> {
+        (index + size <= num_bits).then(|| {
+            (index..index + size).fold(0, |code, i| {
+                code << 1 | (raw_bytes[i / 8] >> (7 - i % 8) & 1) as usize
+            })
+        })
+    };
+
+    let (mut latch_table, mut shift_table) = (Table::Upper, Table::Upper);
+    let mut index = 0;
+    // 第一个 FNC1 之前的数据字符; 最多记录三个, 足以判断是否为一个字母或两个数字
+    let mut leading = String::new();
+    let mut fnc1_after: Option<String> = None;
+    let mut has_eci = false;
+    let push_data = |leading: &mut String, c: char| {
+        if leading.len() < 3 {
+            leading.push(c);
+        }
+    };
+    loop {
+        if shift_table == Table::Binary {
+            let Some(mut length) = read(index, 5) else {
+                break;
+            };
+            index += 5;
+            if length == 0 {
+                let Some(long_length) = read(index, 11) else {
+                    break;
+                };
+                length = long_length + 31;
+                index += 11;
+            }
+            for _ in 0..length {
+                let Some(byte) = read(index, 8) else {
+                    break;
+                };
+                push_data(&mut leading, byte as u8 as char);
+                index += 8;
+            }
+            shift_table = latch_table;
+            continue;
+        }
+        let size = if shift_table == Table::Digit { 4 } else { 5 };
+        let Some(code) = read(index, size) else {
+            break;
+        };
+        index += size;
+        // 切换编码表的控制字符: (目标表, 是否锁定)
+        let switch = match (shift_table, code) {
+            (Table::Punct, 0) => {
+                let Some(n) = read(index, 3) else {
+                    break;
+                };
+                index += 3;
+                if n == 0 {
+                    fnc1_after.get_or_insert_with(|| leading.clone());
+                } else {
+                    // ECI 号为 n 个数字表中的数字
+                    has_eci = true;
+                    index += 4 * n;
+                }
+                shift_table = latch_table;
+                continue;
+            }
+            (Table::Upper | Table::Lower | Table::Mixed | Table::Digit, 0) => {
+                Some((Table::Punct, false))
+            }
+            (Table::Upper | Table::Lower | Table::Mixed, 31) => Some((Table::Binary, false)),
+            (Table::Upper | Table::Mixed, 28) => Some((Table::Lower, true)),
+            (Table::Upper | Table::Lower, 29) => Some((Table::Mixed, true)),
+            (Table::Upper | Table::Lower, 30) => Some((Table::Digit, true)),
+            (Table::Lower, 28) | (Table::Digit, 15) => Some((Table::Upper, false)),
+            (Table::Mixed, 29) | (Table::Punct, 31) | (Table::Digit, 14) => {
+                Some((Table::Upper, true))
+            }
+            (Table::Mixed, 30) => Some((Table::Punct, true)),
+            _ => None,
+        };
+        match switch {
+            Some((table, lock)) => {
+                latch_table = if lock { table } else { shift_table };
+                shift_table = table;
+            }
+            None => {
+                let c = match (shift_table, code) {
+                    (Table::Upper, 2..=27) => (b'A' + code as u8 - 2) as char,
+                    (Table::Lower, 2..=27) => (b'a' + code as u8 - 2) as char,
+                    (Table::Digit, 2..=11) => (b'0' + code as u8 - 2) as char,
+                    // 其余字符只需知道它不是字母或数字
+                    _ => ' ',
+                };
+                push_data(&mut leading, c);
+                shift_table = latch_table;
+            }
+        }
+    }
+
+    let fnc1 = match fnc1_after.as_deref() {
+        Some("") => 1,
+        Some(prefix) => {
+            let chars: Vec<char> = prefix.chars().collect();
+            match chars[..] {
+                [c] if c.is_ascii_alphabetic() => 2,
+                [a, b] if a.is_ascii_digit() && b.is_ascii_digit() => 2,
+                _ => 0,
+            }
+        }
+        None => 0,
+    };
+    Some(fnc1 + if has_eci { 3 } else { 0 })
+}
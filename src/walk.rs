@@ -0,0 +1,90 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// scan_directory 的文件遍历: 每个目录读取一次并按名称排序, 结果顺序稳定. 子目录在遍历到时
+// 才展开 (深度优先), 不会先列出整个目录树. 指向目录的符号链接不展开, 避免循环
+pub(crate) struct DirectoryWalk {
+    pattern: Vec<char>,
+    recursive: bool,
+    pending: Vec<std::vec::IntoIter<PathBuf>>,
+}
+
+impl DirectoryWalk {
+    pub fn new(root: &Path, pattern: &str, recursive: bool) -> io::Result<Self> {
+        Ok(DirectoryWalk {
+            pattern: pattern.chars().collect(),
+            recursive,
+            pending: vec![sorted_entries(root)?],
+        })
+    }
+}
+
+impl Iterator for DirectoryWalk {
+    // 无法读取的子目录作为错误返回, 之后继续遍历其余的条目
+    type Item = Result<PathBuf, (PathBuf, io::Error)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(entries) = self.pending.last_mut() {
+            let Some(path) = entries.next() else {
+                self.pending.pop();
+                continue;
+            };
+            let is_dir = fs::symlink_metadata(&path).is_ok_and(|metadata| metadata.is_dir());
+            if is_dir {
+                if self.recursive {
+                    match sorted_entries(&path) {
+                        Ok(entries) => self.pending.push(entries),
+                        Err(e) => return Some(Err((path, e))),
+                    }
+                }
+                continue;
+            }
+            let name: Vec<char> = match path.file_name() {
+                Some(name) => name.to_string_lossy().chars().collect(),
+                None => continue,
+            };
+            if matches(&self.pattern, &name) && path.is_file() {
+                return Some(Ok(path));
+            }
+        }
+        None
+    }
+}
+
+fn sorted_entries(dir: &Path) -> io::Result<std::vec::IntoIter<PathBuf>> {
+    let mut entries = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    entries.sort();
+    Ok(entries.into_iter())
+}
+
+// shell 风格的通配符, 与文件名 (不含目录) 比较: * 匹配任意个字符, ? 匹配一个字符,
+// 其余字符按原样比较, 区分大小写
+fn matches(pattern: &[char], name: &[char]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    // 最近一个 * 的位置, 以及它当前匹配到的文件名位置, 失配时从这里回溯
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    star = Some((star_p, star_n + 1));
+                    p = star_p + 1;
+                    n = star_n + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
@@ -1,3 +1,10 @@
+import asyncio
+import base64
+import datetime
+import io
+import json
+import pathlib
+import tempfile
 import unittest
 import os
 import sys
@@ -148,6 +155,1549 @@ class TestDecode(unittest.TestCase):
         self.assertEqual(result.text, QR_CODE_EXAMPLE_TEXT)
         self.assertEqual(result.barcode_format, "qrcode")
 
+    def test_decode_qr_exposes_error_correction_level(self):
+        result = rxing.decode(self.__class__.qr_image_path)
+        self.assertIn(result.error_correction_level, ("L", "M", "Q", "H"))
+        self.assertEqual(result.symbology_identifier, "]Q1")
+
+    def _encode_and_decode_qr(self, text, charset):
+        matrix = rxing.encode(
+            text, "QR_CODE", 200, 200, hints_dict={"CHARACTER_SET": charset}
+        )
+        return rxing.decode(matrix.to_pil_image().convert("L"))
+
+    def test_decode_utf8_eci(self):
+        result = self._encode_and_decode_qr("Grüße aus Köln", "UTF-8")
+        self.assertEqual(result.text, "Grüße aus Köln")
+        self.assertEqual(result.eci, 26)
+        self.assertEqual(result.character_set, "utf-8")
+        self.assertEqual(result.raw_bytes, "Grüße aus Köln".encode("utf-8"))
+
+    def test_decode_shift_jis_eci(self):
+        result = self._encode_and_decode_qr("日本語 text", "Shift_JIS")
+        self.assertEqual(result.text, "日本語 text")
+        self.assertEqual(result.eci, 20)
+        self.assertEqual(result.character_set, "shift_jis")
+        self.assertEqual(
+            result.raw_bytes.decode(result.character_set), "日本語 text"
+        )
+
+    def test_decode_ean13_has_no_raw_bytes(self):
+        matrix = rxing.encode("5901234123457", "EAN_13", 300, 100)
+        result = rxing.decode(matrix.to_pil_image().convert("L"))
+        self.assertEqual(result.text, "5901234123457")
+        self.assertIsNone(result.raw_bytes)
+        self.assertEqual(result.num_bits, 0)
+        self.assertTrue(result.result_points)
+
+    def test_decode_symbol_version(self):
+        matrix = rxing.encode("Version 5", "QR_CODE", 200, 200, {"QR_VERSION": "5"})
+        result = rxing.decode(matrix.to_pil_image().convert("L"))
+        self.assertEqual(result.symbol_version, 5)
+        self.assertEqual(result.symbol_size, (37, 37))
+
+        result = rxing.decode(rxing.encode_to_png("Data Matrix", "DATA_MATRIX"))
+        self.assertEqual(result.symbol_size, (16, 16))
+        self.assertEqual(result.symbol_version, 4)
+
+    def test_decode_1d_has_no_symbol_version(self):
+        matrix = rxing.encode("5901234123457", "EAN_13", 300, 100)
+        result = rxing.decode(matrix.to_pil_image().convert("L"))
+        self.assertIsNone(result.symbol_version)
+        self.assertIsNone(result.symbol_size)
+
+    def test_decode_qr_bounding_box_and_corners(self):
+        image = rxing.encode("Overlay", "QR_CODE", 200, 200).to_pil_image()
+        for angle in (0, 90):
+            result = rxing.decode(image.convert("L").rotate(angle))
+            x, y, w, h = result.bounding_box
+            corners = result.corners
+            self.assertEqual(len(corners), 4)
+            xs = [c[0] for c in corners]
+            ys = [c[1] for c in corners]
+            self.assertEqual((x, y, w, h), (min(xs), min(ys), max(xs) - x, max(ys) - y))
+            # clockwise from the top-left corner
+            top_left, top_right, bottom_right, bottom_left = corners
+            self.assertLess(top_left[0], top_right[0])
+            self.assertLess(top_right[1], bottom_right[1])
+            self.assertGreater(bottom_right[0], bottom_left[0])
+            self.assertGreater(bottom_left[1], top_left[1])
+
+    def test_decode_1d_bounding_box_is_thin(self):
+        matrix = rxing.encode("5901234123457", "EAN_13", 300, 100)
+        result = rxing.decode(matrix.to_pil_image().convert("L"))
+        x, y, w, h = result.bounding_box
+        self.assertGreater(w, 100)
+        self.assertEqual(h, 1.0)
+        self.assertEqual(len(result.result_points), 2)
+        self.assertIsNone(result.corners)
+
+    def test_decode_without_eci(self):
+        result = rxing.decode(self.__class__.qr_image_path)
+        self.assertIsNone(result.eci)
+        self.assertEqual(result.character_set, "us-ascii")
+
+    def test_decode_timestamp_is_epoch_millis(self):
+        before = datetime.datetime.now(datetime.timezone.utc)
+        result = rxing.decode(self.__class__.qr_image_path)
+        after = datetime.datetime.now(datetime.timezone.utc)
+        self.assertIsInstance(result.timestamp, int)
+        self.assertEqual(result.timestamp_datetime.tzinfo, datetime.timezone.utc)
+        self.assertEqual(
+            result.timestamp, round(result.timestamp_datetime.timestamp() * 1000)
+        )
+        slack = datetime.timedelta(seconds=1)
+        self.assertTrue(before - slack <= result.timestamp_datetime <= after + slack)
+
+    def test_decode_qr_with_max_dimension(self):
+        full = rxing.decode(self.__class__.qr_image_path)
+        scaled = rxing.decode(self.__class__.qr_image_path, max_dimension=120)
+        self.assertEqual(scaled.text, QR_CODE_EXAMPLE_TEXT)
+        # Points are mapped back to the full-resolution coordinate space.
+        for p_full, p_scaled in zip(full.result_points, scaled.result_points):
+            self.assertAlmostEqual(p_full.x, p_scaled.x, delta=3.0)
+            self.assertAlmostEqual(p_full.y, p_scaled.y, delta=3.0)
+
+    def test_decode_retry_full_resolution(self):
+        png = rxing.encode_to_png("x" * 200, "QR_CODE", module_size=3)
+        with self.assertRaises(rxing.NotFoundException):
+            rxing.decode(png, max_dimension=80, retry_full_resolution=False)
+        full = rxing.decode(png)
+        retried = rxing.decode(png, max_dimension=80)
+        self.assertEqual(retried.text, "x" * 200)
+        self.assertEqual(
+            [(p.x, p.y) for p in retried.result_points],
+            [(p.x, p.y) for p in full.result_points],
+        )
+        raw = rxing.rxing_lib.decode_image_bytes(png, max_dimension=80)
+        self.assertEqual(raw.text, "x" * 200)
+        self.assertEqual(rxing.decode(Image.open(io.BytesIO(png))).text, "x" * 200)
+        with self.assertRaises(TypeError):
+            rxing.decode(Image.open(io.BytesIO(png)), max_dimension=80)
+
+    def test_decode_qr_with_try_scales(self):
+        with open(self.__class__.qr_image_path, "rb") as f:
+            image_bytes = f.read()
+        result = rxing.decode(image_bytes, try_scales=[0.5, 0.25])
+        self.assertEqual(result.text, QR_CODE_EXAMPLE_TEXT)
+
+    def test_decode_qr_with_rotations(self):
+        pil_img = Image.open(self.__class__.qr_image_path).convert("L")
+        skewed = pil_img.rotate(-30, expand=True, fillcolor=255)
+        result = rxing.decode(skewed, rotations=[0, 30])
+        self.assertEqual(result.text, QR_CODE_EXAMPLE_TEXT)
+        self.assertIn(result.rotation, (0, 30))
+        self.assertNotIn("ROTATION", result.result_metadata)
+
+    def test_decode_code128_with_rotations(self):
+        matrix = rxing.encode("ROTATE-123", "CODE_128", 300, 80)
+        pil_img = matrix.to_pil_image().convert("L").rotate(90, expand=True)
+        result = rxing.decode(pil_img, rotations=[0, 90, 180, 270])
+        self.assertEqual(result.text, "ROTATE-123")
+        self.assertIn(result.rotation, (90, 270))
+
+    def test_decode_code128_symbology_identifier(self):
+        matrix = rxing.encode("AIM-128", "CODE_128", 300, 80)
+        result = rxing.decode(matrix.to_pil_image().convert("L"))
+        self.assertEqual(result.symbology_identifier, "]C0")
+        self.assertIsNone(result.error_correction_level)
+
+    def test_symbology_identifier_modifiers(self):
+        aztec = {"MARGIN": "4"}
+        aztec_utf8 = {"MARGIN": "4", "CHARACTER_SET": "UTF-8"}
+        cases = [
+            ("AB+C", "CODE_39", None, None, "]A0"),
+            ("ABCX", "CODE_39", {"ASSUME_CODE_39_CHECK_DIGIT": True}, None, "]A3"),
+            ("AB+C", "CODE_39", {"CODE_39_EXTENDED_MODE": True}, None, "]A4"),
+            ("12345670", "ITF", None, None, "]I0"),
+            ("12345670", "ITF", {"ASSUME_ITF_CHECK_DIGIT": True}, None, "]I1"),
+            ("4006381333931", "EAN_13", None, None, "]E0"),
+            ("Привет", "QR_CODE", None, None, "]Q2"),
+            ("HELLO", "AZTEC", None, aztec, "]z0"),
+            # rxing reports ]z0 for every Aztec code; the ECI is found in the bit stream
+            ("Привет", "AZTEC", None, aztec_utf8, "]z3"),
+        ]
+        for text, fmt, hints, encode_hints, expected in cases:
+            size = (400, 100) if fmt in ("CODE_39", "ITF", "EAN_13") else (200, 200)
+            matrix = rxing.encode(text, fmt, *size, encode_hints)
+            result = rxing.decode(matrix.to_pil_image().convert("L"), hints)
+            self.assertEqual(result.symbology_identifier, expected, (text, fmt, hints))
+            self.assertEqual(result.get_metadata("SYMBOLOGY_IDENTIFIER"), expected)
+
+    def test_decode_scaling_options_validated(self):
+        with self.assertRaises(ValueError):
+            rxing.decode(self.__class__.no_barcode_image_path, max_dimension=0)
+        with self.assertRaises(ValueError):
+            rxing.decode(self.__class__.no_barcode_image_path, try_scales=[1.5])
+        with self.assertRaises(ValueError):
+            rxing.decode(self.__class__.no_barcode_image_path, rotations=[])
+
+    def _decode_code128_extended(self, text, hints=None):
+        # rxing's Code 128 writer only takes ASCII; "\u00f4" inserts an FNC4 so the
+        # next character is shifted into the 128..255 byte range.
+        escaped = "".join(
+            "\u00f4" + chr(ord(c) - 128) if ord(c) > 127 else c for c in text
+        )
+        matrix = rxing.encode(escaped, "CODE_128", 400, 80)
+        return rxing.decode(matrix.to_pil_image().convert("L"), hints)
+
+    def test_decode_code128_latin1_character_set(self):
+        result = self._decode_code128_extended("café", {"CHARACTER_SET": "ISO-8859-1"})
+        self.assertEqual(result.text, "café")
+        self.assertEqual(result.character_set, "iso-8859-1")
+
+    def test_decode_code128_pinned_character_set(self):
+        # The same byte values read as Windows-1252 and as UTF-8.
+        result = self._decode_code128_extended(
+            "\x80 5", {"CHARACTER_SET": "windows-1252"}
+        )
+        self.assertEqual(result.text, "€ 5")
+        utf8_bytes = "été".encode("utf-8").decode("latin-1")
+        self.assertEqual(self._decode_code128_extended(utf8_bytes).text, utf8_bytes)
+        result = self._decode_code128_extended(utf8_bytes, {"CHARACTER_SET": "UTF-8"})
+        self.assertEqual(result.text, "été")
+
+    def test_decode_allowed_lengths(self):
+        matrix = rxing.encode("PART1234", "CODE_39", 300, 80)
+        img = matrix.to_pil_image().convert("L")
+        result = rxing.decode(img, {"ALLOWED_LENGTHS": [8]})
+        self.assertEqual(result.text, "PART1234")
+        with self.assertRaisesRegex(ValueError, "NotFoundException"):
+            rxing.decode(img, {"ALLOWED_LENGTHS": [6, 10]})
+
+    def test_decode_itf_check_digit(self):
+        check = {"ASSUME_ITF_CHECK_DIGIT": True}
+        good = rxing.encode("15400141288763", "ITF", 400, 80).to_pil_image()
+        result = rxing.decode(good, dict(check, ALLOWED_LENGTHS=[14]))
+        self.assertEqual(result.text, "15400141288763")
+        hints = rxing.DecodeHints(assume_itf_check_digit=True, allowed_lengths=[14])
+        self.assertEqual(rxing.decode(good, hints).text, "15400141288763")
+        with self.assertRaisesRegex(ValueError, "NotFoundException"):
+            rxing.decode(good, dict(check, ALLOWED_LENGTHS=[10]))
+
+        bad = rxing.encode("15400141288764", "ITF", 400, 80).to_pil_image()
+        self.assertEqual(rxing.decode(bad).text, "15400141288764")
+        with self.assertRaisesRegex(ValueError, "ChecksumException"):
+            rxing.decode(bad, check)
+        # multi-decode drops the misread but reports it
+        for decode_all in (rxing.decode_multiple, rxing.decode_candidates):
+            with self.assertWarnsRegex(UserWarning, '"15400141288764"'):
+                self.assertEqual(decode_all(bad, check), [])
+
+    def test_exception_hierarchy(self):
+        for cls in (
+            rxing.NotFoundException,
+            rxing.ChecksumException,
+            rxing.FormatException,
+        ):
+            self.assertTrue(issubclass(cls, rxing.ReaderException))
+        self.assertTrue(issubclass(rxing.ReaderException, ValueError))
+        self.assertTrue(issubclass(rxing.WriterException, ValueError))
+
+        with self.assertRaises(rxing.NotFoundException) as ctx:
+            rxing.decode(self.__class__.no_barcode_image_path)
+        self.assertEqual(ctx.exception.kind, "NotFoundException")
+
+        bad = rxing.encode("15400141288764", "ITF", 400, 80).to_pil_image()
+        with self.assertRaises(rxing.ChecksumException) as ctx:
+            rxing.decode(bad, {"ASSUME_ITF_CHECK_DIGIT": True})
+        self.assertEqual(ctx.exception.kind, "ChecksumException")
+        self.assertIn("check digit", ctx.exception.detail)
+
+        with self.assertRaises(rxing.WriterException):
+            rxing.encode("abc", "EAN_13", 100, 50)
+
+    def test_decode_code_39_check_digit(self):
+        check = {"ASSUME_CODE_39_CHECK_DIGIT": True}
+        # W is the mod-43 check character of "CODE39".
+        good = rxing.encode("CODE39W", "CODE_39", 300, 80).to_pil_image()
+        self.assertEqual(rxing.decode(good).text, "CODE39W")
+        self.assertEqual(rxing.decode(good, check).text, "CODE39")
+        hints = rxing.DecodeHints(assume_code_39_check_digit=True)
+        self.assertEqual(rxing.decode(good, hints).text, "CODE39")
+
+        bad = rxing.encode("CODE39A", "CODE_39", 300, 80).to_pil_image()
+        self.assertEqual(rxing.decode(bad).text, "CODE39A")
+        with self.assertRaisesRegex(ValueError, "ChecksumException"):
+            rxing.decode(bad, check)
+        self.assertEqual(rxing.decode_multiple(bad, check), [])
+
+    def test_decode_allowed_ean_extensions(self):
+        matrix = rxing.encode("5901234123457", "EAN_13", 300, 80)
+        img = matrix.to_pil_image().convert("L")
+        self.assertEqual(rxing.decode(img).text, "5901234123457")
+        # No supplemental extension is present, so requiring one rejects the read.
+        with self.assertRaisesRegex(ValueError, "NotFoundException"):
+            rxing.decode(img, {"ALLOWED_EAN_EXTENSIONS": [2, 5]})
+
+    def _ean13_with_extension(self, text, extension):
+        # Appends an EAN-5 supplement (start guard 1011, digits in L/G parity
+        # chosen by the supplement checksum, "01" separators) after a 9-module gap.
+        l_codes = ["0001101", "0011001", "0010011", "0111101", "0100011"]
+        l_codes += ["0110001", "0101111", "0111011", "0110111", "0001011"]
+        g_codes = [code.translate(str.maketrans("01", "10"))[::-1] for code in l_codes]
+        parities = ["GGLLL", "GLGLL", "GLLGL", "GLLLG", "LGGLL"]
+        parities += ["LLGGL", "LLLGG", "LGLGL", "LGLLG", "LLGLG"]
+        digits = [int(d) for d in extension]
+        checksum = (3 * sum(digits[0::2]) + 9 * sum(digits[1::2])) % 10
+        modules = "1011" + "01".join(
+            (l_codes if parity == "L" else g_codes)[d]
+            for d, parity in zip(digits, parities[checksum])
+        )
+        main = rxing.encode(text, "EAN_13", 1, 1).data[0]
+        row = main + [False] * 9 + [m == "1" for m in modules] + [False] * 9
+        matrix = rxing.BitMatrix.from_rows([row])
+        image = matrix.to_pil_image().convert("L")
+        return image.resize((matrix.width * 3, 80), Image.NEAREST)
+
+    def test_decode_ean13_with_extension(self):
+        img = self._ean13_with_extension("5901234123457", "52495")
+        result = rxing.decode(img)
+        self.assertEqual(result.text, "5901234123457")
+        self.assertEqual(result.upc_ean_extension, "52495")
+        self.assertEqual(result.suggested_price, "$24.95")
+        self.assertIsNone(result.issue_number)
+        result = rxing.decode(img, {"ALLOWED_EAN_EXTENSIONS": [5]})
+        self.assertEqual(result.upc_ean_extension, "52495")
+
+    def test_decode_ean13_without_extension(self):
+        matrix = rxing.encode("5901234123457", "EAN_13", 300, 80)
+        result = rxing.decode(matrix.to_pil_image().convert("L"))
+        self.assertIsNone(result.upc_ean_extension)
+        self.assertIsNone(result.suggested_price)
+        self.assertIsNone(result.issue_number)
+
+    # Mode 2 MaxiCode: postcode 152382802, country 840, service class 001,
+    # message "HELLO MAXICODE"
+    MAXICODE_MODE_2_ROWS = [
+        "000000000001000010000000000000",
+        "011011111100110001010011101010",
+        "001000001100101000101111001001",
+        "010101010101010101010101010100",
+        "000000000000000000000000000011",
+        "101010101010101010101010101010",
+        "010101010101010101010101010110",
+        "000000000000000000000000000010",
+        "101010101010101010101010101011",
+        "010101010000010001011001010100",
+        "000000000010110001101100000000",
+        "101010101000000001011110101000",
+        "010101000000000000010101010110",
+        "000000100000000000011000000000",
+        "101010010000000000000110101000",
+        "010101000000000000011101010110",
+        "000000010000000000000100000000",
+        "101010101000000000000010101010",
+        "010101111100000000001101010101",
+        "000000101100000000001000000010",
+        "101010111010000000011110101001",
+        "010101010000000000110101010110",
+        "000000001101100100100100000011",
+        "101010100000101010100010101000",
+        "010101010101010101011011111100",
+        "000000000000000000001101101110",
+        "101010101010101010100001000011",
+        "110101100000111010111011110110",
+        "011010010010000010011101011101",
+        "010101001010101100010001010110",
+        "101111111101000111111010010011",
+        "110111010100100101011111110100",
+        "011011010010100110100110010011",
+    ]
+
+    def _maxicode_image(self, rows, cell=6, border=12):
+        # Odd rows are shifted half a module right to approximate the hexagons
+        height = len(rows) * cell + 2 * border
+        width = len(rows[0]) * cell + cell // 2 + 2 * border
+        pixels = np.full((height, width), 255, dtype=np.uint8)
+        for r, row in enumerate(rows):
+            top = border + r * cell
+            for c, module in enumerate(row):
+                if module == "1":
+                    left = border + c * cell + (cell // 2 if r % 2 else 0)
+                    pixels[top : top + cell, left : left + cell] = 0
+        return Image.fromarray(pixels)
+
+    def test_decode_maxicode_structured_fields(self):
+        img = self._maxicode_image(self.MAXICODE_MODE_2_ROWS)
+        result = rxing.decode(img, {"POSSIBLE_FORMATS": ["MAXICODE"]})
+        self.assertEqual(result.barcode_format, "maxicode")
+        self.assertTrue(result.text.endswith("HELLO MAXICODE"))
+        self.assertEqual(result.maxicode_mode, 2)
+        self.assertEqual(result.postal_code, "152382802")
+        self.assertEqual(result.country_code, 840)
+        self.assertEqual(result.service_class, 1)
+
+        qr = rxing.decode(rxing.encode("Hello", "QR_CODE", 100, 100).to_pil_image())
+        self.assertIsNone(qr.maxicode_mode)
+        self.assertIsNone(qr.postal_code)
+
+    # Version 1 QR symbols with a structured append header, built module by
+    # module: "Hello, " and "world!" are parts 0 and 1 of 2 with parity 13
+    # (the XOR of b"Hello, world!"); "message" is part 1 of 2 with parity 15
+    QR_APPEND_HELLO = [
+        "111111100100101111111",
+        "100000101001001000001",
+        "101110100100001011101",
+        "101110101001001011101",
+        "101110100011101011101",
+        "100000101110101000001",
+        "111111101010101111111",
+        "000000000011100000000",
+        "111110111100110101010",
+        "110000010000100010100",
+        "011101110101001010110",
+        "001010000110011111110",
+        "110000110001000001011",
+        "000000001101100101100",
+        "111111101000101010100",
+        "100000100111100110010",
+        "101110101110100000100",
+        "101110101110100101100",
+        "101110101001000010000",
+        "100000101010000111111",
+        "111111101111000001100",
+    ]
+    QR_APPEND_WORLD = [
+        "111111100011101111111",
+        "100000101110101000001",
+        "101110100011101011101",
+        "101110101100101011101",
+        "101110100100101011101",
+        "100000101001001000001",
+        "111111101010101111111",
+        "000000000100000000000",
+        "111110111001010101010",
+        "110100011111100100100",
+        "010010110100101000110",
+        "011101000001100111110",
+        "110101101110110000011",
+        "000000001000100101100",
+        "111111101001000011000",
+        "100000100100000111110",
+        "101110101101000001000",
+        "101110101001111111110",
+        "101110101000101100000",
+        "100000101011111011011",
+        "111111101000100001000",
+    ]
+    QR_APPEND_OTHER = [
+        "111111100100101111111",
+        "100000101001001000001",
+        "101110100100001011101",
+        "101110101001001011101",
+        "101110100011101011101",
+        "100000101110101000001",
+        "111111101010101111111",
+        "000000000011100000000",
+        "111110111100110101010",
+        "001010000010110000100",
+        "001100110001001100110",
+        "111010010110010111111",
+        "111011101101011001011",
+        "000000001001110011100",
+        "111111101100101100000",
+        "100000100111110011010",
+        "101110101000110001100",
+        "101110101000100011110",
+        "101110101101000100000",
+        "100000101000000011111",
+        "111111101011000001100",
+    ]
+
+    def test_append_collector(self):
+        symbols = (self.QR_APPEND_HELLO, self.QR_APPEND_WORLD, self.QR_APPEND_OTHER)
+        # The fixtures mark dark modules with "1" where _render_rows expects "X"
+        images = (
+            self._render_rows([r.replace("1", "X") for r in rows], scale=4, border=4)
+            for rows in symbols
+        )
+        hello, world, other = (rxing.decode(image) for image in images)
+        self.assertEqual(
+            (
+                world.structured_append_index,
+                world.structured_append_total,
+                world.structured_append_parity,
+            ),
+            (1, 2, 13),
+        )
+        collector = rxing.AppendCollector()
+        self.assertIsNone(collector.add(world))
+        self.assertEqual(collector.missing, [0])
+        with self.assertRaisesRegex(ValueError, "different message"):
+            collector.add(other)
+        self.assertEqual(collector.add(hello), b"Hello, world!")
+        self.assertEqual(collector.missing, [])
+
+        plain = rxing.decode(rxing.encode("Hello", "QR_CODE", 100, 100).to_pil_image())
+        self.assertIsNone(plain.structured_append_index)
+        with self.assertRaisesRegex(ValueError, "structured append"):
+            collector.add(plain)
+
+    # Version 1 QR symbol holding the Windows-1251 bytes of "Привет" in byte
+    # mode without an ECI header, which is not valid UTF-8
+    QR_CP1251_PRIVET = [
+        "111111100100101111111",
+        "100000101001001000001",
+        "101110100100001011101",
+        "101110101001001011101",
+        "101110100011101011101",
+        "100000101110101000001",
+        "111111101010101111111",
+        "000000000011100000000",
+        "111110111100110101010",
+        "101010010010100100111",
+        "001100100101010010111",
+        "100010001010000111011",
+        "010100111101010010011",
+        "000000001101111100000",
+        "111111101000101100111",
+        "100000100011111111001",
+        "101110101110100100010",
+        "101110101000100010100",
+        "101110101001010010000",
+        "100000101100000001000",
+        "111111101101010000010",
+    ]
+
+    def test_text_bytes_round_trip(self):
+        rows = [row.replace("1", "X") for row in self.QR_CP1251_PRIVET]
+        result = rxing.decode(self._render_rows(rows, scale=4, border=4))
+        expected = "Привет".encode("cp1251")
+        self.assertEqual(result.text_bytes, expected)
+        self.assertNotEqual(result.text, "Привет")
+        self.assertEqual(result.text_bytes.decode("cp1251"), "Привет")
+
+        matrix = rxing.encode("Hello", "CODE_128", 200, 60)
+        result = rxing.decode(matrix.to_pil_image().convert("L"))
+        self.assertEqual(result.text_bytes, b"Hello")
+
+    def test_decode_codabar_start_end(self):
+        matrix = rxing.encode("A40156B", "CODABAR", 300, 80)
+        img = matrix.to_pil_image().convert("L")
+        self.assertEqual(rxing.decode(img).text, "40156")
+        result = rxing.decode(img, {"RETURN_CODABAR_START_END": True})
+        self.assertEqual(result.text, "A40156B")
+
+    def test_decode_length_hints_validated(self):
+        path = self.__class__.no_barcode_image_path
+        with self.assertRaises(TypeError):
+            rxing.decode(path, {"ALLOWED_LENGTHS": 8})
+        with self.assertRaises(TypeError):
+            rxing.decode(path, {"ALLOWED_LENGTHS": [8.0]})
+        with self.assertRaises(ValueError):
+            rxing.decode(path, {"ALLOWED_LENGTHS": [0]})
+        with self.assertRaises(ValueError):
+            rxing.decode(path, {"ALLOWED_EAN_EXTENSIONS": [3]})
+
+    def test_decode_numpy(self):
+        pil_img = Image.open(self.__class__.qr_image_path)
+        gray = np.asarray(pil_img.convert("L"))
+        rgb = np.asarray(pil_img.convert("RGB"))
+        self.assertEqual(rxing.decode_numpy(gray).text, QR_CODE_EXAMPLE_TEXT)
+        self.assertEqual(rxing.decode_numpy(rgb).text, QR_CODE_EXAMPLE_TEXT)
+        # A non-contiguous BGR view, as OpenCV would hand over.
+        bgr = rgb[..., ::-1]
+        result = rxing.decode_numpy(bgr, channel_order="BGR")
+        self.assertEqual(result.text, QR_CODE_EXAMPLE_TEXT)
+        self.assertEqual(rxing.decode_numpy(gray[..., None]).text, QR_CODE_EXAMPLE_TEXT)
+
+        with self.assertRaisesRegex(TypeError, "uint8"):
+            rxing.decode_numpy(gray.astype(np.float32))
+        with self.assertRaisesRegex(ValueError, "shape"):
+            rxing.decode_numpy(gray.ravel())
+        with self.assertRaisesRegex(ValueError, "channel_order"):
+            rxing.decode_numpy(rgb, channel_order="GBR")
+
+    def test_decode_rgb_pixels(self):
+        pil_img = Image.open(self.__class__.qr_image_path)
+        rgb = pil_img.convert("RGB")
+        width, height = rgb.size
+        data = rgb.tobytes()
+        result = rxing.decode_rgb_pixels(data, width, height)
+        self.assertEqual(result.text, QR_CODE_EXAMPLE_TEXT)
+        bgra = np.asarray(pil_img.convert("RGBA"))[..., [2, 1, 0, 3]]
+        result = rxing.decode_rgb_pixels(
+            np.ascontiguousarray(bgra), width, height, channel_order="BGRA"
+        )
+        self.assertEqual(result.text, QR_CODE_EXAMPLE_TEXT)
+        # rows padded to a multiple of 64 bytes
+        stride = (width * 3 + 63) // 64 * 64
+        padded = bytearray(stride * height)
+        for y in range(height):
+            padded[y * stride : y * stride + width * 3] = data[
+                y * width * 3 : (y + 1) * width * 3
+            ]
+        result = rxing.decode_rgb_pixels(padded, width, height, stride=stride)
+        self.assertEqual(result.text, QR_CODE_EXAMPLE_TEXT)
+
+        with self.assertRaisesRegex(ValueError, "channel_order"):
+            rxing.decode_rgb_pixels(data, width, height, channel_order="GBR")
+        with self.assertRaisesRegex(ValueError, "expected"):
+            rxing.decode_rgb_pixels(data, width, height, channel_order="RGBA")
+        with self.assertRaisesRegex(ValueError, "must be positive"):
+            rxing.decode_rgb_pixels(b"", 0, 0)
+
+    def test_decode_yuv(self):
+        gray = Image.open(self.__class__.qr_image_path).convert("L")
+        width, height = gray.size
+        luma = gray.tobytes()
+        chroma = bytes([128]) * (2 * ((width + 1) // 2) * ((height + 1) // 2))
+        for yuv_format in ("NV12", "NV21", "I420", "YV12"):
+            result = rxing.decode_yuv(luma + chroma, width, height, yuv_format)
+            self.assertEqual(result.text, QR_CODE_EXAMPLE_TEXT)
+
+        y = np.frombuffer(luma, dtype=np.uint8)
+        uv = np.full_like(y, 128)
+        if width % 2 == 0:
+            yuyv = np.stack([y, uv], axis=-1).tobytes()
+            result = rxing.decode_yuv(yuyv, width, height, "YUYV")
+            self.assertEqual(result.text, QR_CODE_EXAMPLE_TEXT)
+            uyvy = np.stack([uv, y], axis=-1).tobytes()
+            result = rxing.decode_yuv(uyvy, width, height, "UYVY")
+            self.assertEqual(result.text, QR_CODE_EXAMPLE_TEXT)
+
+        # NV12 with rows padded to 64 bytes, as V4L2 drivers often deliver
+        stride = (width + 63) // 64 * 64
+        planes = np.zeros((height + (height + 1) // 2, stride), dtype=np.uint8)
+        planes[:height, :width] = np.asarray(gray)
+        result = rxing.decode_yuv(planes, width, height, stride=stride)
+        self.assertEqual(result.text, QR_CODE_EXAMPLE_TEXT)
+
+        with self.assertRaisesRegex(ValueError, "expected"):
+            rxing.decode_yuv(luma, width, height)
+        with self.assertRaisesRegex(ValueError, "Unknown YUV format"):
+            rxing.decode_yuv(luma + chroma, width, height, "RGB")
+        with self.assertRaisesRegex(ValueError, "must be positive"):
+            rxing.decode_yuv(b"", 0, 0)
+
+    def test_decode_unknown_character_set(self):
+        with self.assertRaisesRegex(ValueError, "Unknown CHARACTER_SET"):
+            rxing.decode(
+                self.__class__.no_barcode_image_path, {"CHARACTER_SET": "klingon"}
+            )
+
+    def test_decode_pil_image_modes(self):
+        pil_img = Image.open(self.__class__.qr_image_path)
+        for mode in ("L", "RGB", "RGBA"):
+            result = rxing.decode_pil_image(pil_img.convert(mode))
+            self.assertEqual(result.text, QR_CODE_EXAMPLE_TEXT, mode)
+
+    def test_decode_pil_image_rejects_unsupported_mode(self):
+        pil_img = Image.open(self.__class__.qr_image_path).convert("CMYK")
+        with self.assertRaisesRegex(ValueError, "Unsupported PIL image mode"):
+            rxing.decode_pil_image(pil_img)
+        # decode() converts other modes to "L" first.
+        self.assertEqual(rxing.decode(pil_img).text, QR_CODE_EXAMPLE_TEXT)
+
+    def test_decode_luma_pixels_with_stride(self):
+        matrix = rxing.encode("Stride", "QR_CODE", 100, 100)
+        luma = np.where(np.array(matrix.data, dtype=bool), 0, 255).astype(np.uint8)
+        height, width = luma.shape
+        padded = np.zeros((height, width + 12), dtype=np.uint8)
+        padded[:, :width] = luma
+        buffer = padded.tobytes()[:-12]
+        raw = rxing.rxing_lib.decode_luma_pixels
+        self.assertEqual(raw(buffer, width, height, stride=width + 12).text, "Stride")
+
+        with self.assertRaisesRegex(ValueError, "width \\* height"):
+            raw(buffer, width, height)
+        with self.assertRaisesRegex(ValueError, "stride must be at least width"):
+            raw(buffer, width, height, stride=width - 1)
+        with self.assertRaisesRegex(ValueError, "too short"):
+            raw(buffer[:-1], width, height, stride=width + 12)
+        with self.assertRaisesRegex(ValueError, "must be positive"):
+            raw(b"", 0, 0, stride=0)
+
+    def test_decode_roi(self):
+        symbol = rxing.encode("ROI", "QR_CODE", 80, 80).to_pil_image().convert("L")
+        frame = Image.new("L", (400, 300), 255)
+        frame.paste(symbol, (250, 150))
+        full = rxing.decode(frame)
+        cropped = rxing.decode(frame, roi=(230, 130, 120, 120))
+        self.assertEqual(cropped.text, "ROI")
+        self.assertEqual(
+            [(p.x, p.y) for p in cropped.result_points],
+            [(p.x, p.y) for p in full.result_points],
+        )
+        png = io.BytesIO()
+        frame.save(png, format="PNG")
+        for source in (png.getvalue(), np.asarray(frame)):
+            result = rxing.decode(source, roi=(230, 130, 120, 120), padding=4)
+            self.assertEqual(result.text, "ROI")
+        with self.assertRaises(rxing.NotFoundException):
+            rxing.decode(frame, roi=(0, 0, 200, 150))
+
+        with self.assertRaisesRegex(ValueError, "does not fit"):
+            rxing.decode(frame, roi=(300, 0, 200, 100))
+        with self.assertRaisesRegex(ValueError, "does not fit"):
+            rxing.decode(png.getvalue(), roi=(300, 0, 200, 100))
+        with self.assertRaisesRegex(ValueError, "positive"):
+            rxing.decode(frame, roi=(0, 0, 0, 100))
+
+    def test_roi_on_other_entry_points(self):
+        symbol = rxing.encode("ROI", "QR_CODE", 80, 80).to_pil_image().convert("L")
+        bars = rxing.encode("C128", "CODE_128", 200, 60).to_pil_image().convert("L")
+        frame = Image.new("L", (400, 300), 255)
+        frame.paste(symbol, (250, 150))
+        frame.paste(bars, (20, 20))
+        png = io.BytesIO()
+        frame.save(png, format="PNG")
+        png = png.getvalue()
+        roi = (230, 130, 120, 120)
+        points = [(p.x, p.y) for p in rxing.decode(frame, roi=roi).result_points]
+
+        def assert_roi_result(result):
+            self.assertEqual(result.text, "ROI")
+            self.assertEqual([(p.x, p.y) for p in result.result_points], points)
+
+        for source in (frame, png):
+            (result,) = rxing.decode_multiple(source, roi=roi)
+            assert_roi_result(result)
+        self.assertEqual(rxing.decode_pure(frame, roi=(250, 150, 80, 80)).text, "ROI")
+        with self.assertRaises(TypeError):
+            rxing.decode_pure(rxing.encode("ROI", "QR_CODE"), roi=roi)
+        assert_roi_result(asyncio.run(rxing.decode_async(png, roi=roi)))
+
+        reader = rxing.BarcodeReader()
+        assert_roi_result(reader.decode(png, roi=roi))
+        assert_roi_result(reader.decode_pixels(frame.tobytes(), 400, 300, roi=roi))
+
+        scanner = rxing.VideoScanner(400, 300, roi=roi)
+        self.assertEqual(scanner.roi, roi)
+        assert_roi_result(scanner.push_frame(frame.tobytes()))
+        (result,) = rxing.decode_stream([frame.tobytes()], 400, 300, roi=roi)
+        assert_roi_result(result)
+        with self.assertRaisesRegex(ValueError, "does not fit"):
+            rxing.VideoScanner(400, 300, roi=(300, 0, 200, 100))
+
+        small = io.BytesIO()
+        symbol.save(small, format="PNG")
+        result, failure = rxing.decode_batch([png, small.getvalue()], roi=roi)
+        assert_roi_result(result)
+        self.assertFalse(failure)
+        self.assertIn("does not fit", failure.message)
+
+        candidates = rxing.decode_candidates(frame, roi=(0, 0, 240, 100))
+        self.assertEqual(candidates[0].text, "C128")
+        self.assertTrue(all(p.x >= 20 for p in candidates[0].result_points))
+        self.assertEqual(rxing.decode_candidates(frame, roi=roi), [])
+
+    def test_stride_on_other_raw_decoders(self):
+        matrix = rxing.encode("Stride", "QR_CODE", 100, 100)
+        luma = np.where(np.array(matrix.data, dtype=bool), 0, 255).astype(np.uint8)
+        height, width = luma.shape
+        padded = np.zeros((height, width + 12), dtype=np.uint8)
+        padded[:, :width] = luma
+        stride = width + 12
+        attempts = rxing.rxing_lib.diagnose_luma_pixels(
+            padded, width, height, {"POSSIBLE_FORMATS": ["QR_CODE"]}, stride=stride
+        )
+        self.assertEqual([a.result.text for a in attempts if a.success], ["Stride"])
+        # luma16 strides count 16-bit values, not bytes
+        result = rxing.decode_luma16_pixels(
+            padded.astype(np.uint16) * 257, width, height, stride=stride
+        )
+        self.assertEqual(result.text, "Stride")
+
+        code = rxing.encode("STRIDE-128", "CODE_128", 200, 40)
+        luma = np.where(np.array(code.data, dtype=bool), 0, 255).astype(np.uint8)
+        height, width = luma.shape
+        padded = np.zeros((height, width + 5), dtype=np.uint8)
+        padded[:, :width] = luma
+        results = rxing.rxing_lib.decode_luma_candidates(
+            padded, width, height, stride=width + 5
+        )
+        self.assertEqual(results[0].text, "STRIDE-128")
+
+    def test_decode_luma_pixels_buffer_protocol(self):
+        matrix = rxing.encode("Buffer", "QR_CODE", 100, 100)
+        luma = np.where(np.array(matrix.data, dtype=bool), 0, 255).astype(np.uint8)
+        height, width = luma.shape
+        raw = rxing.rxing_lib.decode_luma_pixels
+        for source in (luma, bytearray(luma.tobytes()), memoryview(luma.tobytes())):
+            self.assertEqual(raw(source, width, height).text, "Buffer")
+        # The buffer is only borrowed for the call and may be reused afterwards.
+        frame = bytearray(luma.tobytes())
+        self.assertEqual(raw(frame, width, height).text, "Buffer")
+        frame[:] = bytes(len(frame))
+        with self.assertRaises(ValueError):
+            raw(frame, width, height)
+        results = rxing.rxing_lib.decode_multiple_luma_pixels(
+            memoryview(luma), width, height
+        )
+        self.assertEqual([r.text for r in results], ["Buffer"])
+
+    def test_decode_luma16_pixels(self):
+        matrix = rxing.encode("Sixteen", "QR_CODE", 148, 148)
+        modules = np.array(matrix.data, dtype=bool)
+        full_range = np.where(modules, 0, 65535).astype(np.uint16)
+        self.assertEqual(rxing.decode(full_range).text, "Sixteen")
+
+        # A faint 0..200 signal collapses to a single level with the linear >> 8
+        # mapping, while gamma stretches the dark end enough to decode.
+        faint = np.where(modules, 0, 200).astype(np.uint16)
+        height, width = faint.shape
+        with self.assertRaises(ValueError):
+            rxing.decode_luma16_pixels(faint, width, height)
+        result = rxing.decode_luma16_pixels(faint, width, height, gamma=2.2)
+        self.assertEqual(result.text, "Sixteen")
+
+    def test_decode_luma16_pixels_validates_input(self):
+        with self.assertRaisesRegex(ValueError, "width \\* height"):
+            rxing.decode_luma16_pixels(np.zeros(5, dtype=np.uint16), 2, 2)
+        with self.assertRaises(ValueError):
+            rxing.decode_luma16_pixels(np.zeros(4, dtype=np.uint16), 2, 2, gamma=0)
+
+    def test_decode_async_qr(self):
+        with open(self.__class__.qr_image_path, "rb") as f:
+            image_bytes = f.read()
+
+        async def decode_concurrently():
+            return await asyncio.gather(
+                *(rxing.decode_image_bytes_async(image_bytes) for _ in range(4)),
+                rxing.decode_async(self.__class__.qr_image_path),
+            )
+
+        results = asyncio.run(decode_concurrently())
+        self.assertEqual([r.text for r in results], [QR_CODE_EXAMPLE_TEXT] * 5)
+
+    def test_decode_multiple_and_batch_async(self):
+        with open(self.__class__.qr_image_path, "rb") as f:
+            image_bytes = f.read()
+
+        async def run():
+            return await asyncio.gather(
+                rxing.decode_multiple_async(image_bytes),
+                rxing.decode_batch_async(
+                    [image_bytes, self.__class__.no_barcode_image_path], threads=2
+                ),
+            )
+
+        multiple, batch = asyncio.run(run())
+        self.assertEqual([r.text for r in multiple], [QR_CODE_EXAMPLE_TEXT])
+        self.assertEqual(batch[0].text, QR_CODE_EXAMPLE_TEXT)
+        self.assertIsInstance(batch[1], rxing.DecodeFailure)
+        self.assertIsInstance(batch[1].error, rxing.NotFoundException)
+
+    def test_decode_async_no_barcode_and_cancel(self):
+        async def run():
+            with self.assertRaisesRegex(ValueError, "NotFoundException"):
+                await rxing.decode_async(self.__class__.no_barcode_image_path)
+            task = asyncio.ensure_future(
+                rxing.decode_async(self.__class__.no_barcode_image_path)
+            )
+            task.cancel()
+            with self.assertRaises(asyncio.CancelledError):
+                await task
+            # The loop keeps working after a cancelled decode.
+            await asyncio.sleep(0)
+
+        asyncio.run(run())
+
+    def _qr_sheet(self, positions):
+        sheet = Image.new("L", (600, 500), 255)
+        for text, (x, y) in positions.items():
+            symbol = rxing.encode(text, "QR_CODE").to_pil_image()
+            size = (symbol.width * 5, symbol.height * 5)
+            sheet.paste(symbol.resize(size, Image.NEAREST), (x + 20, y + 20))
+        return sheet
+
+    def test_decode_multiple_reading_order(self):
+        # The top row is offset by 15 px, as on a slightly skewed form.
+        positions = {"A1": (0, 10), "A2": (310, 25), "B1": (20, 260), "B2": (290, 250)}
+        sheet = self._qr_sheet(positions)
+
+        results = rxing.decode_multiple(sheet)
+        self.assertEqual(sorted(r.text for r in results), sorted(positions))
+        rows = rxing.decode_multiple(sheet, sort="reading_order", row_tolerance=30)
+        self.assertEqual([r.text for r in rows], ["A1", "A2", "B1", "B2"])
+        by_height = rxing.decode_multiple(sheet, sort="reading_order", row_tolerance=0)
+        self.assertEqual([r.text for r in by_height], ["A1", "A2", "B2", "B1"])
+
+    def test_decode_multiple_sources(self):
+        positions = {"A1": (0, 10), "A2": (310, 25), "B1": (20, 260), "B2": (290, 250)}
+        sheet = self._qr_sheet(positions)
+        expected = ["A1", "A2", "B1", "B2"]
+        kwargs = {"sort": "reading_order", "row_tolerance": 30}
+
+        luma = rxing.decode_multiple_luma_pixels(
+            sheet.tobytes(), sheet.width, sheet.height, **kwargs
+        )
+        self.assertEqual([r.text for r in luma], expected)
+        buffer = io.BytesIO()
+        sheet.save(buffer, format="PNG")
+        from_bytes = rxing.decode_multiple_image_bytes(buffer.getvalue(), **kwargs)
+        self.assertEqual([r.text for r in from_bytes], expected)
+        with tempfile.TemporaryDirectory() as directory:
+            path = pathlib.Path(directory) / "sheet.png"
+            sheet.save(path)
+            from_path = rxing.decode_multiple_from_file_path(path, **kwargs)
+            self.assertEqual([r.text for r in from_path], expected)
+            from_wrapper = rxing.decode_multiple(path, **kwargs)
+            self.assertEqual([r.text for r in from_wrapper], expected)
+            with self.assertRaises(FileNotFoundError):
+                rxing.decode_multiple_from_file_path(path.with_name("missing.png"))
+        from_pil = rxing.decode_multiple(sheet, **kwargs)
+        self.assertEqual([r.text for r in from_pil], expected)
+        with self.assertRaises(ValueError):
+            rxing.decode_multiple_luma_pixels(b"\xff" * 10, sheet.width, sheet.height)
+
+    def test_decode_multiple_no_barcode_and_validation(self):
+        path = self.__class__.no_barcode_image_path
+        self.assertEqual(rxing.decode_multiple(path), [])
+        with self.assertRaises(ValueError):
+            rxing.decode_multiple(path, sort="columns")
+        with self.assertRaises(ValueError):
+            rxing.decode_multiple(path, sort="reading_order", row_tolerance=-1)
+
+    def test_decode_all_frames(self):
+        codes = [("Page one", "QR_CODE"), None, ("Page three", "CODE_128")]
+        pages = []
+        for code in codes:
+            page = Image.new("L", (700, 300), 255)
+            if code is not None:
+                png = rxing.encode_to_png(*code)
+                page.paste(Image.open(io.BytesIO(png)).convert("L"), (20, 20))
+            pages.append(page)
+        for image_format in ("TIFF", "GIF"):
+            buffer = io.BytesIO()
+            pages[0].save(
+                buffer, format=image_format, save_all=True, append_images=pages[1:]
+            )
+            frames = rxing.decode_all_frames(buffer.getvalue())
+            texts = [(index, [r.text for r in results]) for index, results in frames]
+            self.assertEqual(
+                texts, [(0, ["Page one"]), (1, []), (2, ["Page three"])], image_format
+            )
+
+        single = rxing.decode_all_frames(rxing.encode_to_png("Single", "QR_CODE"))
+        self.assertEqual(len(single), 1)
+        self.assertEqual(single[0][0], 0)
+        self.assertEqual([r.text for r in single[0][1]], ["Single"])
+        with self.assertRaises(OSError):
+            rxing.decode_all_frames(b"not an image")
+
+    def test_decode_region_grid(self):
+        page = Image.new("L", (600, 200), 255)
+        for left, data in [(0, "LEFT CELL"), (400, "RIGHT CELL")]:
+            png = rxing.encode_to_png(data, "QR_CODE", module_size=6)
+            page.paste(Image.open(io.BytesIO(png)), (left, 0))
+        buffer = io.BytesIO()
+        page.save(buffer, format="PNG")
+        page_bytes = buffer.getvalue()
+
+        cells = [(0, 0, 200, 200), (200, 0, 200, 200), (400, 0, 200, 200)]
+        left, empty, right = rxing.decode_region_grid(page_bytes, cells)
+        self.assertEqual(left.text, "LEFT CELL")
+        self.assertIsNone(empty)
+        self.assertEqual(right.text, "RIGHT CELL")
+        self.assertTrue(all(point.x >= 400 for point in right.result_points))
+
+        with self.assertRaisesRegex(ValueError, "Cell 1"):
+            rxing.decode_region_grid(page_bytes, [(0, 0, 200, 200), (500, 0, 200, 200)])
+        with self.assertRaisesRegex(ValueError, "positive width"):
+            rxing.decode_region_grid(page_bytes, [(0, 0, 0, 200)])
+
+    def test_scan_and_annotate(self):
+        items = [("A-1", "QR_CODE"), ("B-2", "DATA_MATRIX"), ("C-3", "CODE_128")]
+        sheet = rxing.encode_sheet(items, columns=3, spacing=40)
+        annotated = Image.open(io.BytesIO(rxing.scan_and_annotate(sheet)))
+        original = Image.open(io.BytesIO(sheet)).convert("RGBA")
+        self.assertEqual(annotated.size, original.size)
+        self.assertNotEqual(annotated.convert("RGBA").tobytes(), original.tobytes())
+        colors = {pixel[:3] for pixel in annotated.convert("RGBA").getdata()}
+        self.assertGreater(len(colors - {(0, 0, 0), (255, 255, 255)}), 2)
+
+        blank = io.BytesIO()
+        Image.new("L", (64, 64), 255).save(blank, format="PNG")
+        unchanged = Image.open(io.BytesIO(rxing.scan_and_annotate(blank.getvalue())))
+        self.assertEqual(set(unchanged.convert("L").getdata()), {255})
+        with self.assertRaises(OSError):
+            rxing.scan_and_annotate(b"not an image")
+
+    def test_decode_with_all_formats_tried(self):
+        image = rxing.encode("Triage", "QR_CODE", 200, 200).to_pil_image()
+        self.assertEqual(rxing.decode_with_all_formats_tried(image).text, "Triage")
+
+        report = rxing.decode_with_all_formats_tried(image, verbose=True)
+        self.assertEqual(
+            [attempt.format for attempt in report], rxing.supported_read_formats()
+        )
+        by_format = {attempt.format: attempt for attempt in report}
+        self.assertTrue(by_format["qrcode"].success)
+        self.assertEqual(by_format["qrcode"].result.text, "Triage")
+        self.assertIsNone(by_format["qrcode"].error_kind)
+        self.assertFalse(by_format["ean 13"].success)
+        self.assertEqual(by_format["ean 13"].error_kind, "NotFoundException")
+        self.assertIsNone(by_format["ean 13"].result)
+
+        limited = rxing.decode_with_all_formats_tried(
+            image, {"POSSIBLE_FORMATS": ["EAN_13", "QR_CODE"]}, verbose=True
+        )
+        self.assertEqual([attempt.format for attempt in limited], ["ean 13", "qrcode"])
+
+        # ALSO_INVERTED 反转位图后, 之后的格式仍从原图开始
+        matrix = rxing.encode("Triage", "QR_CODE", 200, 200)
+        inverted = matrix.invert().to_pil_image()
+        report = rxing.decode_with_all_formats_tried(
+            inverted, {"ALSO_INVERTED": True}, verbose=True
+        )
+        by_format = {attempt.format: attempt for attempt in report}
+        self.assertTrue(by_format["qrcode"].success)
+
+    def _render_rows(self, rows, scale=8, border=2):
+        matrix = rxing.BitMatrix.from_rows([[c == "X" for c in row] for row in rows])
+        width, height = matrix.width + 2 * border, matrix.height + 2 * border
+        image = Image.new("L", (width, height), 255)
+        image.paste(matrix.to_pil_image().convert("L"), (border, border))
+        return image.resize((width * scale, height * scale), Image.NEAREST)
+
+    def test_decode_micro_qr(self):
+        # M1 symbol encoding "123"
+        image = self._render_rows(
+            [
+                "XXXXXXX X X",
+                "X     X    ",
+                "X XXX X XXX",
+                "X XXX X  XX",
+                "X XXX X   X",
+                "X     X XX ",
+                "XXXXXXX X  ",
+                "        X  ",
+                "XX     X   ",
+                " X  XXXXX X",
+                "X  XXXXXX X",
+            ]
+        )
+        for hints in (None, {"POSSIBLE_FORMATS": ["MICRO_QR_CODE"]}):
+            result = rxing.decode(image, hints)
+            self.assertEqual(result.text, "123")
+            self.assertEqual(result.barcode_format, rxing.BarcodeFormat.MICRO_QR_CODE)
+            self.assertEqual(result.symbol_version, 1)
+
+    def test_decode_rectangular_micro_qr(self):
+        # R7x43 symbol encoding "ABCDEFG"
+        image = self._render_rows(
+            [
+                "XXXXXXX X X X X X X XXX X X X X X X X X XXX",
+                "X     X  X XXX  XXXXX XXX      X X XX   X X",
+                "X XXX X X XXX X X X XXXX XXXX X  X XXXXXXXX",
+                "X XXX X  XX    XXXXX   XXXXXX   X X   X   X",
+                "X XXX X   XX  XXX   XXXXXXX  X X  XX  X X X",
+                "X     X XXXXX XXX XXX XXXXX    XXXXXX X   X",
+                "XXXXXXX X X X X X X XXX X X X X X X X XXXXX",
+            ]
+        )
+        result = rxing.decode(
+            image,
+            {"POSSIBLE_FORMATS": ["RECTANGULAR_MICRO_QR_CODE"], "PURE_BARCODE": True},
+        )
+        self.assertEqual(result.text, "ABCDEFG")
+        self.assertEqual(
+            result.barcode_format, rxing.BarcodeFormat.RECTANGULAR_MICRO_QR_CODE
+        )
+        self.assertEqual(result.symbol_size, (43, 7))
+
+    def test_decode_unknown_possible_format(self):
+        image = Image.new("L", (100, 100), 255)
+        with self.assertRaisesRegex(ValueError, "Unsupported barcode format: NOPE"):
+            rxing.decode(image, {"POSSIBLE_FORMATS": ["NOPE"]})
+
+    def test_decode_timeout(self):
+        image = rxing.encode("Deadline", "QR_CODE", 200, 200).to_pil_image()
+        self.assertEqual(rxing.decode(image, timeout_ms=10000).text, "Deadline")
+
+        noise = np.random.default_rng(0).integers(0, 256, (3000, 3000), dtype=np.uint8)
+        with self.assertRaises(TimeoutError):
+            rxing.decode(
+                noise, {"TRY_HARDER": True}, rotations=[0, 90, 180], timeout_ms=1
+            )
+        with self.assertRaises(ValueError):
+            rxing.decode(image, timeout_ms=0)
+
+    def test_decode_global_binarizer_low_contrast(self):
+        image = rxing.encode("Faint print", "QR_CODE", 200, 200).to_pil_image()
+        # 20 levels of contrast is below HybridBinarizer's minimum dynamic range
+        faint = image.convert("L").point(lambda v: 100 if v < 128 else 120)
+        with self.assertRaises(ValueError):
+            rxing.decode(faint)
+        self.assertEqual(rxing.decode(faint, binarizer="global").text, "Faint print")
+        png = io.BytesIO()
+        faint.save(png, format="PNG")
+        self.assertEqual(
+            rxing.decode(png.getvalue(), binarizer="global").text, "Faint print"
+        )
+        with self.assertRaises(ValueError):
+            rxing.decode(faint, binarizer="sauvola")
+
+    def test_decode_fixed_threshold_binarizer(self):
+        image = rxing.encode("Thermal", "QR_CODE", 100, 100).to_pil_image()
+        # a thermal print: grey 120 on grey 135, too faint for the hybrid binarizer
+        faint = image.convert("L").point(lambda v: 120 if v < 128 else 135)
+        with self.assertRaises(ValueError):
+            rxing.decode(faint)
+        self.assertEqual(
+            rxing.decode(faint, binarizer="fixed_threshold").text, "Thermal"
+        )
+        self.assertEqual(
+            rxing.decode(faint, binarizer="fixed_threshold:128").text, "Thermal"
+        )
+        # the cutoff is below both grey levels, so the whole image is white
+        with self.assertRaises(rxing.NotFoundException):
+            rxing.decode(faint, binarizer="fixed_threshold:110")
+        reader = rxing.BarcodeReader(binarizer="fixed_threshold:130")
+        self.assertEqual(reader.binarizer, "fixed_threshold:130")
+        reader = rxing.BarcodeReader(binarizer="global_histogram")
+        self.assertEqual(reader.binarizer, "global")
+        for binarizer in ["fixed_threshold:0", "fixed_threshold:256", "hybrid:3"]:
+            with self.assertRaises(ValueError):
+                rxing.decode(faint, binarizer=binarizer)
+
+    def test_decode_otsu_and_adaptive_binarizers(self):
+        matrix = rxing.encode("Washed out", "QR_CODE", 200, 200)
+        size = matrix.width
+        bits = np.array(matrix.data, dtype=bool)
+        # washed out: grey 215 on 230, evenly lit
+        washed = np.where(bits, 215, 230).astype(np.uint8)
+        # unevenly lit: 200 on 240 under light falling off to 45% at the left edge
+        light = 0.45 + 0.55 * np.arange(size) / size
+        uneven = (np.where(bits, 200, 240) * light[np.newaxis, :]).astype(np.uint8)
+        for image in [washed, uneven]:
+            with self.assertRaises(rxing.NotFoundException):
+                rxing.decode(image)
+        self.assertEqual(rxing.decode(washed, binarizer="otsu").text, "Washed out")
+        # no single threshold separates ink from paper across the uneven image
+        with self.assertRaises(rxing.NotFoundException):
+            rxing.decode(uneven, binarizer="otsu")
+        for binarizer in ["adaptive", "adaptive:41"]:
+            for image in [washed, uneven]:
+                result = rxing.decode(image, binarizer=binarizer)
+                self.assertEqual(result.text, "Washed out")
+        reader = rxing.BarcodeReader(binarizer="adaptive:31")
+        self.assertEqual(reader.binarizer, "adaptive:31")
+        for binarizer in ["adaptive:2", "adaptive:", "otsu:3"]:
+            with self.assertRaises(ValueError):
+                rxing.decode(washed, binarizer=binarizer)
+
+    def test_decode_padding_rescues_flush_crop(self):
+        matrix = rxing.encode("590123412345", "EAN_13", 200, 60, {"MARGIN": "0"})
+        flush = matrix.to_pil_image().convert("L")
+        with self.assertRaises(ValueError):
+            rxing.decode(flush)
+        result = rxing.decode(flush, padding=10)
+        self.assertEqual(result.text, "5901234123457")
+        # points are reported in the coordinates of the unpadded image
+        for point in result.result_points:
+            self.assertTrue(0 <= point.x <= flush.width)
+            self.assertTrue(0 <= point.y <= flush.height)
+        png = io.BytesIO()
+        flush.save(png, format="PNG")
+        bytes_result = rxing.decode(png.getvalue(), padding=10)
+        self.assertEqual(bytes_result.text, "5901234123457")
+        self.assertEqual(
+            [(p.x, p.y) for p in bytes_result.result_points],
+            [(p.x, p.y) for p in result.result_points],
+        )
+        with self.assertRaisesRegex(ValueError, "non-negative"):
+            rxing.decode(flush, padding=-1)
+
+    def test_barcode_reader(self):
+        from concurrent.futures import ThreadPoolExecutor
+
+        matrix = rxing.encode("Reader", "QR_CODE", 200, 200)
+        image = matrix.to_pil_image().convert("L")
+        png = io.BytesIO()
+        image.save(png, format="PNG")
+        reader = rxing.BarcodeReader({"POSSIBLE_FORMATS": ["QR_CODE"]})
+        self.assertEqual(reader.binarizer, "hybrid")
+        self.assertEqual(reader.decode(png.getvalue()).text, "Reader")
+        self.assertEqual(reader.decode_pixels(image.tobytes(), 200, 200).text, "Reader")
+        with tempfile.TemporaryDirectory() as directory:
+            path = pathlib.Path(directory) / "reader.png"
+            image.save(path)
+            self.assertEqual(reader.decode(path).text, "Reader")
+            self.assertEqual(reader.decode(str(path)).text, "Reader")
+
+        # one reader shared across threads
+        with ThreadPoolExecutor(max_workers=4) as pool:
+            texts = list(
+                pool.map(
+                    lambda _: reader.decode_pixels(image.tobytes(), 200, 200).text,
+                    range(8),
+                )
+            )
+        self.assertEqual(texts, ["Reader"] * 8)
+
+        with self.assertRaises(rxing.NotFoundException):
+            reader.decode_pixels(bytes([255]) * 100, 10, 10)
+        with self.assertRaises(ValueError):
+            reader.decode_pixels(b"short", 10, 10)
+        for width, height in ((0, 0), (65536, 65536)):
+            with self.assertRaises(ValueError):
+                reader.decode_pixels(b"", width, height)
+        reader.reset()
+        self.assertEqual(reader.decode(png.getvalue()).text, "Reader")
+        with self.assertRaises(ValueError):
+            rxing.BarcodeReader(binarizer="sauvola")
+
+    def test_video_scanner(self):
+        image = rxing.encode("Frame", "QR_CODE", 200, 200).to_pil_image().convert("L")
+        frame = np.array(image)
+        blank = np.full_like(frame, 255)
+        scanner = rxing.VideoScanner(200, 200, hints_dict={"TRY_HARDER": True})
+        self.assertEqual(scanner.push_frame(frame).text, "Frame")
+        self.assertIsNone(scanner.push_frame(blank))
+        self.assertEqual(scanner.push_frame(image.tobytes()).text, "Frame")
+        with self.assertRaises(ValueError):
+            scanner.push_frame(frame[:100])
+        with self.assertRaises(ValueError):
+            rxing.VideoScanner(200, 200, "BGR")
+        with self.assertRaisesRegex(ValueError, "too large"):
+            rxing.VideoScanner(2**32 - 1, 2**32 - 1, "RGBA")
+
+        rgb = np.array(image.convert("RGB"))
+        results = list(rxing.decode_stream([rgb], 200, 200, "RGB"))
+        self.assertEqual([r.text for r in results], ["Frame"])
+        # a rotated view is not contiguous and is copied before decoding
+        results = list(rxing.decode_stream([frame, blank, frame[::-1, ::-1]], 200, 200))
+        self.assertEqual(results[0].text, "Frame")
+        self.assertIsNone(results[1])
+        self.assertEqual(results[2].text, "Frame")
+
+    def test_decode_hints_object(self):
+        matrix = rxing.encode("Hints", "QR_CODE", 200, 200)
+        img = matrix.to_pil_image().convert("L")
+        hints = rxing.DecodeHints(try_harder=True, possible_formats=["QR_CODE"])
+        self.assertEqual(
+            repr(hints),
+            "DecodeHints(try_harder=True, possible_formats=['%s'])"
+            % rxing.BarcodeFormat.QR_CODE,
+        )
+        for _ in range(2):
+            self.assertEqual(rxing.decode(img, hints=hints).text, "Hints")
+        with self.assertRaisesRegex(ValueError, "NotFoundException"):
+            rxing.decode(img, hints=rxing.DecodeHints(possible_formats=["EAN_13"]))
+
+        with self.assertRaisesRegex(ValueError, "Unsupported barcode format"):
+            rxing.DecodeHints(possible_formats=["NOT_A_FORMAT"])
+        with self.assertRaisesRegex(ValueError, "2 or 5"):
+            rxing.DecodeHints(allowed_ean_extensions=[3])
+        with self.assertRaises(TypeError):
+            rxing.decode(img, hints=["TRY_HARDER"])
+
+    def test_decode_hints_fields(self):
+        hints = rxing.DecodeHints(
+            try_harder=True,
+            possible_formats=[rxing.BarcodeFormat.QR_CODE, "EAN_13"],
+            allowed_lengths=[8],
+            threshold_sweep=True,
+        )
+        self.assertTrue(hints.try_harder)
+        self.assertEqual(hints.possible_formats, ["ean 13", "qrcode"])
+        self.assertEqual(hints.allowed_lengths, [8])
+        self.assertTrue(hints.threshold_sweep)
+        self.assertIsNone(hints.pure_barcode)
+        self.assertIsNone(hints.character_set)
+        self.assertIsNone(hints.code_39_extended_mode)
+        disabled = rxing.DecodeHints(assume_itf_check_digit=False)
+        self.assertIs(disabled.assume_itf_check_digit, False)
+        self.assertEqual(repr(disabled), "DecodeHints(assume_itf_check_digit=False)")
+        with self.assertRaises(AttributeError):
+            hints.try_harder = False
+
+        with self.assertRaises(TypeError):
+            rxing.DecodeHints(try_hardr=True)
+        with self.assertRaises(TypeError):
+            rxing.DecodeHints(try_harder="yes")
+
+    def test_parse_gs1(self):
+        ai_map = {"01": "09506000134352", "10": "AB12", "17": "251231", "21": "XYZ"}
+        for fmt, size in (("DATA_MATRIX", 200), ("QR_CODE", 200)):
+            matrix = rxing.encode_gs1(ai_map, fmt, size, size, {"MARGIN": "2"})
+            result = rxing.decode(matrix.to_pil_image().convert("L"))
+            self.assertEqual(rxing.parse_gs1(result), ai_map)
+        matrix = rxing.encode_gs1(ai_map, "CODE_128", 400, 100)
+        image = matrix.to_pil_image().convert("L")
+        result = rxing.decode(image, {"ASSUME_GS1": True})
+        parsed = rxing.parse_gs1(result)
+        self.assertEqual(parsed, ai_map)
+        self.assertEqual(list(parsed), ["01", "10", "17", "21"])
+        with self.assertRaises(ValueError):
+            rxing.parse_gs1(rxing.decode(image))
+        # AI 235 starts with the predefined-length prefix "23" but is variable-length
+        ai_map = {"235": "ABC", "10": "X"}
+        matrix = rxing.encode_gs1(ai_map, "DATA_MATRIX", 200, 200, {"MARGIN": "2"})
+        result = rxing.decode(matrix.to_pil_image().convert("L"))
+        self.assertEqual(result.text, "235ABC\x1d10X")
+        self.assertEqual(rxing.parse_gs1(result), ai_map)
+
+        self.assertEqual(
+            rxing.parse_gs1("(01)90012345678908(3103)001750(10)A(B)C"),
+            {"01": "90012345678908", "3103": "001750", "10": "A(B)C"},
+        )
+        self.assertEqual(
+            rxing.parse_gs1("]C10109506000134352\x1d17251231"),
+            {"01": "09506000134352", "17": "251231"},
+        )
+        # bad check digit, duplicate AI with another value, short fixed-length value
+        for text in ("0109506000134353", "10AB\x1d10CD", "12345", ""):
+            with self.assertRaises(ValueError):
+                rxing.parse_gs1(text)
+        qr = rxing.decode(rxing.encode("HELLO", "QR_CODE", 100, 100).to_pil_image())
+        with self.assertRaises(ValueError):
+            rxing.parse_gs1(qr)
+
+    def test_assume_gs1_hint(self):
+        ai_map = {"10": "AB12", "01": "09506000134352"}
+        image = rxing.encode_gs1(ai_map, "CODE_128", 400, 100).to_pil_image()
+        self.assertEqual(rxing.decode(image).text, "10AB120109506000134352")
+        for hints in ({"ASSUME_GS1": True}, rxing.DecodeHints(assume_gs1=True)):
+            result = rxing.decode(image, hints=hints)
+            self.assertEqual(result.text, "]C110AB12\x1d0109506000134352")
+
+    def test_telepen_as_numeric_hint(self):
+        image = rxing.encode("1234", "TELEPEN", 300, 80).to_pil_image()
+        self.assertEqual(rxing.decode(image).text, "1234")
+        hints = rxing.DecodeHints(telepen_as_numeric=True)
+        self.assertTrue(hints.telepen_as_numeric)
+        result = rxing.decode(image, hints={"TELEPEN_AS_NUMERIC": True})
+        self.assertEqual(result.text, "22232425")
+        self.assertEqual(rxing.decode(image, hints=hints).text, "22232425")
+
+    def test_result_point_callback_hint(self):
+        points = []
+        hints = rxing.DecodeHints(
+            need_result_point_callback=lambda x, y: points.append((x, y))
+        )
+        image = rxing.encode("hi", "QR_CODE", 100, 100).to_pil_image()
+        self.assertEqual(rxing.decode(image, hints=hints).text, "hi")
+        self.assertTrue(points)
+        self.assertTrue(all(0 <= x < 100 and 0 <= y < 100 for x, y in points))
+
+        def failing(x, y):
+            raise RuntimeError("callback failed")
+
+        hints = {"NEED_RESULT_POINT_CALLBACK": failing}
+        self.assertEqual(rxing.decode(image, hints=hints).text, "hi")
+        with self.assertRaises(TypeError):
+            rxing.decode(image, hints={"NEED_RESULT_POINT_CALLBACK": 3})
+        with self.assertRaises(TypeError):
+            rxing.DecodeHints(need_result_point_callback=3)
+
+    def test_to_luma_matches_decoder_weights(self):
+        rgb = bytes([255, 0, 0, 0, 255, 0, 0, 0, 255, 200, 100, 50])
+        expected = bytes(
+            (306 * r + 601 * g + 117 * b + 0x200) >> 10
+            for r, g, b in zip(rgb[0::3], rgb[1::3], rgb[2::3])
+        )
+        self.assertEqual(rxing.to_luma(rgb, 2, 2, 3), expected)
+        rgba = bytes([0, 0, 0, 0, 0, 0, 0, 255])
+        self.assertEqual(rxing.to_luma(rgba, 2, 1, 4), bytes([255, 0]))
+        self.assertEqual(rxing.to_luma(b"\x01\x02", 2, 1, 1), b"\x01\x02")
+        with self.assertRaises(ValueError):
+            rxing.to_luma(rgb, 2, 2, 4)
+        with self.assertRaises(ValueError):
+            rxing.to_luma(rgb, 2, 2, 2)
+
+        matrix = rxing.encode("LUMA", "CODE_128", 200, 60)
+        image = matrix.to_pil_image().convert("RGB")
+        luma = rxing.to_luma(image.tobytes(), image.width, image.height, 3)
+        result = rxing.decode(Image.frombytes("L", image.size, luma))
+        self.assertEqual(result.text, "LUMA")
+
+    def test_structured_points(self):
+        matrix = rxing.encode("x" * 60, "QR_CODE", hints_dict={"MARGIN": "0"})
+        self.assertEqual(matrix.width, 33)
+        module = 4
+        image = matrix.to_pil_image().convert("L")
+        image = image.resize((33 * module, 33 * module), Image.NEAREST)
+        canvas = Image.new("L", (image.width + 64, image.height + 64), 255)
+        canvas.paste(image, (32, 32))
+        points = rxing.decode(canvas).structured_points()
+        self.assertIsInstance(points, rxing.QRPoints)
+
+        def at(modules_x, modules_y):
+            return (32 + modules_x * module, 32 + modules_y * module)
+
+        expected = [at(3.5, 3.5), at(33 - 3.5, 3.5), at(3.5, 33 - 3.5)]
+        for point, (x, y) in zip(points.finder_patterns, expected):
+            self.assertAlmostEqual(point.x, x, delta=1.5)
+            self.assertAlmostEqual(point.y, y, delta=1.5)
+        x, y = at(33 - 6.5, 33 - 6.5)
+        self.assertAlmostEqual(points.alignment_pattern.x, x, delta=1.5)
+        self.assertAlmostEqual(points.alignment_pattern.y, y, delta=1.5)
+        self.assertAlmostEqual(points.bottom_right.x, at(33, 33)[0], delta=1.5)
+
+        # Rotated a quarter turn clockwise, the top-left finder is now top-right
+        rotated = rxing.decode(canvas.rotate(-90, expand=True)).structured_points()
+        first = rotated.finder_patterns[0]
+        self.assertGreater(first.x, canvas.width / 2)
+        self.assertLess(first.y, canvas.height / 2)
+
+        small = rxing.encode("v1", "QR_CODE", 100, 100).to_pil_image()
+        self.assertIsNone(rxing.decode(small).structured_points().alignment_pattern)
+        code128 = rxing.decode(rxing.encode("FLAT", "CODE_128", 200, 60).to_pil_image())
+        flat = code128.structured_points()
+        self.assertEqual(len(flat), len(code128.result_points))
+
+    def test_decode_pure(self):
+        matrix = rxing.encode("PURE", "CODE_128", 1, 1)
+        self.assertEqual(rxing.decode_pure(matrix).text, "PURE")
+        image = rxing.encode("PURE", "DATA_MATRIX", 80, 80).to_pil_image()
+        self.assertEqual(rxing.decode_pure(image.convert("L")).text, "PURE")
+        result = rxing.decode_pure(image, {"POSSIBLE_FORMATS": ["DATA_MATRIX"]})
+        self.assertEqual(result.text, "PURE")
+        hints = rxing.DecodeHints(possible_formats=["DATA_MATRIX"])
+        self.assertEqual(rxing.decode_pure(image, hints).text, "PURE")
+        self.assertEqual(rxing.decode_pure(matrix, rxing.DecodeHints()).text, "PURE")
+        self.assertIsNone(hints.pure_barcode)
+        with self.assertRaises(TypeError):
+            rxing.decode_pure(image, ["PURE_BARCODE"])
+
+    def test_pure_barcode_failure_mentions_hint(self):
+        # A speck outside the symbol breaks PURE_BARCODE's bounding box
+        image = rxing.encode("CAMERA", "DATA_MATRIX", 80, 80).to_pil_image()
+        image = image.convert("L")
+        image.paste(0, (1, 1, 4, 4))
+        self.assertEqual(rxing.decode(image).text, "CAMERA")
+        with self.assertRaisesRegex(ValueError, "PURE_BARCODE is set"):
+            rxing.decode(image, {"PURE_BARCODE": True})
+        with self.assertRaises(ValueError) as ctx:
+            rxing.decode(Image.new("L", (50, 50), 255))
+        self.assertNotIn("PURE_BARCODE", str(ctx.exception))
+
+    def test_decode_from_bit_matrix(self):
+        # one pixel per module, straight from the encoder
+        matrix = rxing.encode("Pre-binarized", "QR_CODE")
+        result = rxing.decode_from_bit_matrix(matrix)
+        self.assertEqual(result.text, "Pre-binarized")
+        self.assertEqual(result.barcode_format, "qrcode")
+        for point in result.result_points:
+            self.assertTrue(0 <= point.x <= matrix.width)
+
+        # 1D encodings are a single row of modules
+        bars = rxing.encode("BITS-128", "CODE_128", 1, 1)
+        self.assertEqual(bars.height, 1)
+        for hints in ({}, {"PURE_BARCODE": True}, rxing.DecodeHints(try_harder=True)):
+            self.assertEqual(rxing.decode_from_bit_matrix(bars, hints).text, "BITS-128")
+
+        rows = [[False] * 40 for _ in range(40)]
+        with self.assertRaisesRegex(ValueError, "NotFoundException"):
+            rxing.decode_from_bit_matrix(rxing.BitMatrix.from_rows(rows))
+
+    def test_decode_candidates_ranked_by_scan_lines(self):
+        def bars(text, rows):
+            matrix = rxing.encode(text, "CODE_128", 1, 1, {"MARGIN": "10"})
+            row = matrix.to_numpy_array()
+            return np.repeat(np.where(row, 0, 255).astype(np.uint8), rows, axis=0)
+
+        # a smudged code whose lower third reads as a different lot number
+        image = np.vstack([bars("LOT-1234", 40), bars("LOT-1284", 20)])
+        candidates = rxing.decode_candidates(image)
+        self.assertEqual([c.text for c in candidates], ["LOT-1234", "LOT-1284"])
+        self.assertEqual([c.scan_lines for c in candidates], [40, 20])
+        self.assertEqual(rxing.decode(image).text, "LOT-1234")
+        self.assertEqual(
+            rxing.decode_candidates(image, {"POSSIBLE_FORMATS": ["EAN_13"]}), []
+        )
+
+    def test_binarize_shows_what_the_reader_sees(self):
+        matrix = rxing.encode("Thermal", "QR_CODE", 100, 100)
+        image = matrix.to_pil_image().convert("L")
+        faint = image.point(lambda v: 120 if v < 128 else 135)
+        # the hybrid binarizer drops all 15 levels of contrast, so decode() fails
+        blank = rxing.binarize(faint)
+        self.assertEqual((blank.width, blank.height), (matrix.width, matrix.height))
+        self.assertFalse(any(blank.to_bytes()))
+        fixed = rxing.binarize(faint, binarizer="fixed_threshold", hints={})
+        self.assertEqual(fixed.to_bytes(), matrix.to_bytes())
+        flat = np.full((60, 80), 200, dtype=np.uint8)
+        with self.assertRaises(rxing.NotFoundException):
+            rxing.binarize(flat, binarizer="global_histogram")
+        luma = faint.tobytes()
+        pixels = rxing.rxing_lib.binarize_luma_pixels(
+            luma, faint.width, faint.height, "otsu"
+        )
+        self.assertEqual(pixels.to_bytes(), matrix.to_bytes())
+        with self.assertRaises(ValueError):
+            rxing.rxing_lib.binarize_luma_pixels(luma[:-1], faint.width, faint.height)
+
+    def test_luminance_source_and_binary_bitmap(self):
+        matrix = rxing.encode("Pipeline", "QR_CODE", 120, 100)
+        image = matrix.to_pil_image().convert("L")
+        source = rxing.LuminanceSource.from_image(image)
+        self.assertEqual((source.width, source.height), image.size)
+        self.assertEqual(source.to_bytes(), image.tobytes())
+        from_numpy = rxing.LuminanceSource(np.array(image), *image.size)
+        self.assertEqual(from_numpy.to_bytes(), source.to_bytes())
+
+        tiny = rxing.LuminanceSource(bytes([1, 2, 3, 4, 5, 6]), 3, 2)
+        self.assertEqual(list(tiny.rotate(90).to_bytes()), [4, 1, 5, 2, 6, 3])
+        self.assertEqual((tiny.rotate(-90).width, tiny.rotate(-90).height), (2, 3))
+        self.assertEqual(list(tiny.crop(1, 0, 2, 2).to_bytes()), [2, 3, 5, 6])
+        self.assertEqual(list(tiny.invert().to_bytes())[:2], [254, 253])
+
+        bitmap = rxing.BinaryBitmap(source, binarizer="otsu")
+        self.assertEqual(bitmap.binarizer, "otsu")
+        self.assertEqual(bitmap.get_black_matrix().to_bytes(), matrix.to_bytes())
+        # one binarization, several decode attempts
+        self.assertEqual(bitmap.decode().text, "Pipeline")
+        self.assertEqual(bitmap.rotate(90).decode().text, "Pipeline")
+        self.assertEqual(bitmap.invert().invert().decode().text, "Pipeline")
+        with self.assertRaises(rxing.NotFoundException):
+            bitmap.invert().decode()
+        rotated = rxing.BinaryBitmap(source.rotate(270), binarizer="otsu")
+        self.assertEqual(
+            rotated.get_black_matrix().to_bytes(),
+            bitmap.rotate(270).get_black_matrix().to_bytes(),
+        )
+        cropped = bitmap.crop(5, 5, 100, 80)
+        self.assertEqual((cropped.width, cropped.height), (100, 80))
+
+        for call in [
+            lambda: source.crop(0, 0, 0, 5),
+            lambda: source.crop(10, 0, source.width, 5),
+            lambda: bitmap.rotate(45),
+            lambda: rxing.LuminanceSource(b"", 0, 0),
+            lambda: rxing.LuminanceSource(b"", 65536, 65536),
+        ]:
+            with self.assertRaises(ValueError):
+                call()
+        flat = rxing.LuminanceSource(bytes(100), 10, 10)
+        with self.assertRaises(rxing.NotFoundException):
+            rxing.BinaryBitmap(flat, binarizer="global_histogram")
+
+    def test_detect_finds_symbols_without_decoding(self):
+        sheet = np.full((330, 330), 255, dtype=np.uint8)
+        for x, y in [(0, 0), (150, 0), (0, 150), (150, 150)]:
+            qr = rxing.encode(f"Label {x} {y}", "QR_CODE", 100, 100)
+            sheet[y : y + qr.height, x : x + qr.width] = np.where(
+                np.array(qr.data, dtype=bool), 0, 255
+            )
+        detections = rxing.detect(sheet)
+        self.assertEqual([d.format for d in detections], ["qrcode"] * 4)
+        # each 100 px render has an 18 px quiet zone around the symbol
+        tops = sorted(d.corners[0] for d in detections)
+        self.assertEqual(
+            [(round(x), round(y)) for x, y in tops],
+            [(18, 18), (18, 168), (168, 18), (168, 168)],
+        )
+        for detection in detections:
+            (left, top), _, (right, bottom), _ = detection.corners
+            self.assertAlmostEqual(right - left, 63, delta=1)
+            self.assertAlmostEqual(bottom - top, 63, delta=1)
+
+        aztec = rxing.encode("Pallet", "AZTEC", 200, 200).to_pil_image()
+        found = rxing.detect(aztec, formats=["AZTEC", "QR_CODE"])
+        self.assertEqual([d.format for d in found], ["aztec"])
+
+        blank = np.full((100, 100), 255, dtype=np.uint8)
+        self.assertEqual(rxing.detect(blank), [])
+        for formats in [["EAN_13"], []]:
+            with self.assertRaises(ValueError):
+                rxing.detect(blank, formats=formats)
+
     def test_decode_no_barcode(self):
         if not os.path.exists(
             self.__class__.no_barcode_image_path
@@ -165,6 +1715,332 @@ class TestDecode(unittest.TestCase):
         ):  # Or the specific error rxing_lib raises
             rxing.decode("invalid/path/does_not_exist.png")
 
+    def test_decode_applies_exif_orientation(self):
+        img = rxing.encode("EXIF-123", "CODE_128", 300, 80).to_pil_image().convert("L")
+        # Stored sideways; orientation 6 tells viewers to rotate it 90 degrees clockwise
+        stored = img.rotate(90, expand=True)
+        exif = Image.Exif()
+        exif[0x0112] = 6
+        buffer = io.BytesIO()
+        stored.save(buffer, format="JPEG", exif=exif)
+        jpeg = buffer.getvalue()
+
+        self.assertEqual(rxing.decode(jpeg).text, "EXIF-123")
+        self.assertEqual(rxing.decode_multiple(jpeg)[0].text, "EXIF-123")
+        with tempfile.TemporaryDirectory() as temp_dir:
+            path = pathlib.Path(temp_dir) / "photo.jpg"
+            path.write_bytes(jpeg)
+            self.assertEqual(rxing.decode(path).text, "EXIF-123")
+        with self.assertRaises(ValueError):
+            rxing.decode(jpeg, apply_exif_orientation=False)
+
+    def test_result_to_dict_and_json(self):
+        result = rxing.decode(rxing.encode("Serialize", "QR_CODE").to_pil_image())
+        as_dict = result.to_dict()
+        self.assertEqual(as_dict["text"], "Serialize")
+        self.assertEqual(as_dict["barcode_format"], result.barcode_format)
+        self.assertEqual(as_dict["bytes_encoding"], "base64")
+        self.assertEqual(base64.b64decode(as_dict["raw_bytes"]), result.raw_bytes)
+        self.assertEqual(base64.b64decode(as_dict["text_bytes"]), b"Serialize")
+        self.assertEqual(
+            as_dict["result_points"][0],
+            {"x": result.result_points[0].x, "y": result.result_points[0].y},
+        )
+        self.assertEqual(as_dict["symbol_size"], list(result.symbol_size))
+        self.assertEqual(json.loads(result.to_json()), as_dict)
+
+        as_hex = result.to_dict(bytes_encoding="hex")
+        self.assertEqual(bytes.fromhex(as_hex["raw_bytes"]), result.raw_bytes)
+        self.assertEqual(json.loads(result.to_json("hex", indent=2)), as_hex)
+        with self.assertRaises(ValueError):
+            result.to_dict(bytes_encoding="base32")
+
+    def test_fixed_timestamp(self):
+        image = rxing.encode("Snapshot", "QR_CODE").to_pil_image()
+        self.assertIsNone(rxing.timestamp_override())
+        with rxing.fixed_timestamp(0):
+            first = rxing.decode(image)
+            self.assertEqual(rxing.timestamp_override(), 0)
+            with rxing.fixed_timestamp(1234):
+                self.assertEqual(rxing.decode(image).timestamp, 1234)
+            second = rxing.decode(image)
+        self.assertEqual(first.timestamp, 0)
+        self.assertEqual(first.to_json(), second.to_json())
+        self.assertIsNone(rxing.timestamp_override())
+        self.assertGreater(rxing.decode(image).timestamp, 0)
+
+    def test_was_inverted(self):
+        matrix = rxing.encode("LIGHT-ON-DARK", "QR_CODE", 120, 120)
+        light_on_dark = matrix.invert().to_pil_image().convert("L")
+        hints = {"ALSO_INVERTED": True}
+        result = rxing.decode(light_on_dark, hints)
+        self.assertEqual(result.text, "LIGHT-ON-DARK")
+        self.assertIs(result.was_inverted, True)
+        self.assertIs(rxing.decode(matrix.to_pil_image(), hints).was_inverted, False)
+        with self.assertRaises(ValueError):
+            rxing.decode(light_on_dark)
+
+    def test_orientation_degrees(self):
+        matrix = rxing.encode("ORIENTATION", "QR_CODE", 240, 240)
+        qr = matrix.to_pil_image().convert("L")
+        self.assertAlmostEqual(rxing.decode(qr).orientation_degrees, 0, delta=2)
+        for angle in [30, 90, 180]:
+            # PIL.Image.rotate turns counterclockwise
+            rotated = qr.rotate(-angle, expand=True, fillcolor=255)
+            result = rxing.decode(rotated)
+            self.assertAlmostEqual(result.orientation_degrees, angle, delta=2)
+            self.assertEqual(result.orientation_right_angle, {30: 0}.get(angle, angle))
+
+        code_128 = rxing.encode("ORIENT-128", "CODE_128", 300, 80).to_pil_image()
+        rotated = code_128.convert("L").rotate(-90, expand=True, fillcolor=255)
+        result = rxing.decode(rotated, {"TRY_HARDER": True})
+        self.assertAlmostEqual(result.orientation_degrees, 90, delta=1)
+        self.assertEqual(result.orientation_right_angle, 90)
+        self.assertNotIn("orientation_right_angle", result.to_dict())
+
+    def test_threshold_sweep(self):
+        matrix = rxing.encode("FAINT", "QR_CODE", 150, 150)
+        for dark, light in [(120, 135), (235, 250)]:
+            faint = matrix.to_pil_image().convert("L")
+            faint = faint.point(lambda v: dark if v < 128 else light)
+            with self.assertRaises(ValueError):
+                rxing.decode(faint)
+            with self.assertRaises(ValueError):
+                rxing.decode(faint, binarizer="global")
+            result = rxing.decode(faint, {"THRESHOLD_SWEEP": True})
+            self.assertEqual(result.text, "FAINT")
+            hints = rxing.DecodeHints(threshold_sweep=True)
+            self.assertEqual(rxing.decode(faint, hints).text, "FAINT")
+
+    def test_typed_metadata(self):
+        image = rxing.encode("METADATA", "QR_CODE", 120, 120).to_pil_image()
+        result = rxing.decode(image, rotations=[0])
+        self.assertIn("ERROR_CORRECTION_LEVEL", result.metadata_keys())
+        self.assertEqual(result.metadata_keys(), sorted(result.result_metadata))
+        self.assertTrue(result.has_metadata("SYMBOLOGY_IDENTIFIER"))
+        self.assertEqual(result.get_metadata("SYMBOLOGY_IDENTIFIER"), "]Q1")
+        self.assertEqual(result.get_metadata("ERROR_CORRECTION_LEVEL"), "L")
+        self.assertIsInstance(result.get_metadata("STRUCTURED_APPEND_PARITY"), int)
+        self.assertFalse(result.has_metadata("ORIENTATION"))
+        self.assertFalse(result.has_metadata("NOT_A_KEY"))
+        with self.assertRaises(KeyError):
+            result.get_metadata("ORIENTATION")
+
+        pdf = rxing.encode("PDF417 META", "PDF_417", 300, 100).to_pil_image()
+        result = rxing.decode(pdf)
+        self.assertEqual(result.get_metadata("ORIENTATION"), 0)
+        self.assertIsNone(result.get_metadata("PDF417_EXTRA_METADATA")["file_name"])
+
+    def test_byte_segments(self):
+        payload = "\x30\x82\x00\xff binary"
+        hints = {"CHARACTER_SET": "ISO-8859-1"}
+        matrix = rxing.encode(payload, "QR_CODE", 200, 200, hints)
+        result = rxing.decode(matrix.to_pil_image())
+        self.assertEqual(result.byte_segments, [payload.encode("latin-1")])
+        self.assertEqual(result.get_metadata("BYTE_SEGMENTS"), result.byte_segments)
+        self.assertTrue(result.has_metadata("BYTE_SEGMENTS"))
+        self.assertIn("BYTE_SEGMENTS", result.metadata_keys())
+        self.assertEqual(result.metadata["BYTE_SEGMENTS"], result.byte_segments)
+        self.assertNotIn("BYTE_SEGMENTS", result.result_metadata)
+        self.assertNotIn("byte_segments", result.to_dict())
+
+        alphanumeric = rxing.encode("HELLO", "QR_CODE", 200, 200).to_pil_image()
+        self.assertIsNone(rxing.decode(alphanumeric).byte_segments)
+        code_128 = rxing.encode("HELLO", "CODE_128", 300, 80).to_pil_image()
+        self.assertIsNone(rxing.decode(code_128).byte_segments)
+
+    def test_metadata_dict_and_keys(self):
+        image = rxing.encode("METADATA", "QR_CODE", 120, 120).to_pil_image()
+        result = rxing.decode(image, rotations=[0])
+        metadata = result.metadata
+        self.assertEqual(list(metadata), result.metadata_keys())
+        self.assertEqual(metadata[rxing.MetadataKey.ERROR_CORRECTION_LEVEL], "L")
+        self.assertEqual(metadata[rxing.MetadataKey.STRUCTURED_APPEND_PARITY], -1)
+        # result_metadata keeps the string form
+        self.assertIsInstance(result.result_metadata["STRUCTURED_APPEND_PARITY"], str)
+        self.assertNotIn("metadata", result.to_dict())
+
+        key = rxing.MetadataKey.SYMBOLOGY_IDENTIFIER
+        self.assertEqual(key, "SYMBOLOGY_IDENTIFIER")
+        self.assertEqual(result.get_metadata(key), "]Q1")
+        self.assertTrue(result.has_metadata(key))
+        self.assertIs(rxing.MetadataKey("orientation"), rxing.MetadataKey.ORIENTATION)
+        self.assertNotIn("ROTATION", list(rxing.MetadataKey))
+        self.assertEqual(result.rotation, 0.0)
+        self.assertIsNone(rxing.decode(image).rotation)
+
+    def test_parsed_result_types(self):
+        def parsed(text, fmt="QR_CODE", width=200, height=200):
+            image = rxing.encode(text, fmt, width, height).to_pil_image()
+            return rxing.decode(image).parsed
+
+        wifi = parsed("WIFI:S:home;T:WPA;P:secret;;")
+        self.assertEqual(wifi.type, "WIFI")
+        self.assertEqual((wifi.ssid, wifi.encryption), ("home", "WPA"))
+        self.assertEqual(wifi.password, "secret")
+        self.assertFalse(wifi.hidden)
+        self.assertEqual(parsed("https://example.com/a").uri, "https://example.com/a")
+
+        contact = parsed("MECARD:N:Doe,John;TEL:123;EMAIL:j@x.org;;")
+        self.assertEqual(contact.type, "ADDRESSBOOK")
+        self.assertEqual(contact.names, ["John Doe"])
+        self.assertEqual(contact.emails, ["j@x.org"])
+        self.assertIsNone(contact.note)
+
+        geo = parsed("geo:48.85,2.29,30")
+        self.assertEqual((geo.latitude, geo.longitude), (48.85, 2.29))
+        self.assertEqual(geo.altitude, 30.0)
+        self.assertEqual(parsed("smsto:+1555:hello").body, "hello")
+        self.assertEqual(parsed("mailto:a@b.org?subject=hi").tos, ["a@b.org"])
+
+        event = parsed(
+            "BEGIN:VEVENT\nSUMMARY:Meet\nDTSTART:20240101T100000Z\nEND:VEVENT"
+        )
+        self.assertEqual(event.type, "CALENDAR")
+        start = datetime.datetime(2024, 1, 1, 10, tzinfo=datetime.timezone.utc)
+        self.assertEqual(event.start, start)
+        self.assertIsNone(event.end)
+
+        book = parsed("9780201379624", "EAN_13", 300, 100)
+        self.assertEqual(book.isbn, "9780201379624")
+        product = parsed("4006381333931", "EAN_13", 300, 100)
+        self.assertEqual(product.type, "PRODUCT")
+        self.assertEqual(product.fields["product_id"], "4006381333931")
+        text = parsed("hello world")
+        self.assertEqual(text.type, "TEXT")
+        self.assertEqual(text.display_result, "hello world")
+        with self.assertRaises(AttributeError):
+            text.ssid
+        result = rxing.decode(rxing.encode_to_png("X", "QR_CODE"))
+        self.assertNotIn("parsed", result.to_dict())
+
+    def test_decode_without_result_points(self):
+        png = rxing.encode_to_png("POINTS", "QR_CODE")
+        self.assertIsNotNone(rxing.decode(png).result_points)
+        result = rxing.decode(png, collect_points=False)
+        self.assertEqual(result.text, "POINTS")
+        self.assertIsNone(result.result_points)
+        self.assertIsNone(result.bounding_box)
+        image = Image.open(io.BytesIO(png)).convert("L")
+        self.assertIsNone(rxing.decode(image, collect_points=False).result_points)
+        results = rxing.decode_multiple(png, sort="reading_order", collect_points=False)
+        self.assertEqual([r.text for r in results], ["POINTS"])
+        self.assertIsNone(results[0].result_points)
+        matrix = rxing.encode("POINTS", "QR_CODE", 100, 100)
+        result = rxing.decode_from_bit_matrix(matrix, collect_points=False)
+        self.assertIsNone(result.result_points)
+
+    def test_decode_timed(self):
+        png = rxing.encode_to_png("TIMED", "QR_CODE")
+        timing = rxing.decode_timed(png, {"TRY_HARDER": True}, binarizer="global")
+        self.assertEqual(timing.result.text, "TIMED")
+        self.assertGreater(timing.load_us, 0)
+        self.assertGreater(timing.decode_us, 0)
+        with tempfile.TemporaryDirectory() as temp_dir:
+            path = pathlib.Path(temp_dir) / "code.png"
+            path.write_bytes(png)
+            self.assertEqual(rxing.decode_timed(path).result.text, "TIMED")
+
+        blank = io.BytesIO()
+        Image.new("L", (64, 64), 255).save(blank, format="PNG")
+        timing = rxing.decode_timed(blank.getvalue())
+        self.assertIsNone(timing.result)
+        self.assertIn("result=None", repr(timing))
+
+    def test_decode_batch(self):
+        png = rxing.encode_to_png("BATCH", "QR_CODE")
+        with tempfile.TemporaryDirectory() as temp_dir:
+            path = pathlib.Path(temp_dir) / "code.png"
+            path.write_bytes(png)
+            missing = pathlib.Path(temp_dir) / "missing.png"
+            blank = io.BytesIO()
+            Image.new("L", (64, 64), 255).save(blank, format="PNG")
+            items = [png, path, str(path), missing, blank.getvalue()]
+            results = rxing.decode_batch(items, {"TRY_HARDER": True}, threads=2)
+
+        self.assertEqual([r.text for r in results[:3]], ["BATCH"] * 3)
+        failures = [r for r in results if not r]
+        self.assertEqual([f.index for f in failures], [3, 4])
+        self.assertEqual(failures[0].path, missing)
+        self.assertIsInstance(failures[0].error, FileNotFoundError)
+        self.assertIsNone(failures[1].path)
+        self.assertIsInstance(failures[1].error, rxing.NotFoundException)
+        self.assertEqual(failures[1].message, str(failures[1].error))
+
+        self.assertEqual(rxing.decode_batch([]), [])
+        with self.assertRaises(TypeError):
+            rxing.decode_batch([png, 42])
+        with self.assertRaises(ValueError):
+            rxing.decode_batch([png], threads=0)
+
+    def test_scan_directory(self):
+        png = rxing.encode_to_png("SCAN", "QR_CODE")
+        with tempfile.TemporaryDirectory() as temp_dir:
+            root = pathlib.Path(temp_dir)
+            (root / "nested").mkdir()
+            (root / "b.png").write_bytes(png)
+            (root / "a.png").write_bytes(png)
+            (root / "notes.txt").write_text("not an image")
+            (root / "nested" / "c.png").write_bytes(png)
+            (root / "nested" / "broken.png").write_bytes(b"not a png")
+
+            scanner = rxing.scan_directory(root)
+            self.assertIs(iter(scanner), scanner)
+            found = list(scanner)
+            self.assertEqual([path.name for path, _ in found], ["a.png", "b.png"])
+            self.assertEqual([result.text for _, result in found], ["SCAN"] * 2)
+
+            found = dict(rxing.scan_directory(str(root), recursive=True))
+            self.assertEqual(
+                sorted(path.relative_to(root).as_posix() for path in found),
+                ["a.png", "b.png", "nested/broken.png", "nested/c.png"],
+            )
+            self.assertEqual(found[root / "nested" / "c.png"].text, "SCAN")
+            self.assertIsInstance(found[root / "nested" / "broken.png"], OSError)
+
+            names = [path.name for path, _ in rxing.scan_directory(root, "*.txt")]
+            self.assertEqual(names, ["notes.txt"])
+
+            with self.assertRaises(FileNotFoundError):
+                rxing.scan_directory(root / "missing")
+            with self.assertRaises(NotADirectoryError):
+                rxing.scan_directory(root / "a.png")
+            with self.assertRaises(ValueError):
+                rxing.scan_directory(root, "nested/*.png")
+
+    def test_decode_path_like_and_bytes_paths(self):
+        png = rxing.encode_to_png("PATH", "QR_CODE")
+        with tempfile.TemporaryDirectory() as temp_dir:
+            path = pathlib.Path(temp_dir) / "code.png"
+            path.write_bytes(png)
+            self.assertEqual(rxing.decode(path).text, "PATH")
+            self.assertEqual(rxing.decode_multiple(path)[0].text, "PATH")
+            raw = rxing.rxing_lib.decode_from_file_path
+            self.assertEqual(raw(os.fsencode(path)).text, "PATH")
+            if sys.platform.startswith("linux"):
+                # Not valid UTF-8, so only representable as bytes
+                odd = os.path.join(os.fsencode(temp_dir), b"\xff\xfe.png")
+                with open(odd, "wb") as f:
+                    f.write(png)
+                self.assertEqual(raw(odd).text, "PATH")
+            with self.assertRaises(FileNotFoundError):
+                rxing.decode(pathlib.Path(temp_dir) / "missing.png")
+            with self.assertRaises(TypeError):
+                raw(12345)
+
+    @unittest.skipIf(
+        not hasattr(os, "geteuid") or os.geteuid() == 0,
+        "needs a non-root POSIX user for file permissions",
+    )
+    def test_decode_unreadable_file_raises_permission_error(self):
+        with tempfile.TemporaryDirectory() as temp_dir:
+            path = os.path.join(temp_dir, "locked.png")
+            with open(path, "wb") as f:
+                f.write(rxing.encode_to_png("LOCKED", "QR_CODE"))
+            os.chmod(path, 0)
+            with self.assertRaises(PermissionError):
+                rxing.decode(path)
+
     def test_decode_unsupported_type(self):
         with self.assertRaises(TypeError):
             rxing.decode(12345)
@@ -224,11 +2100,583 @@ class TestEncode(unittest.TestCase):
             "Code128 BitMatrix height should typically be 1 module row.",
         )
 
+    def test_encode_rejects_invalid_dimensions(self):
+        for width, height in ((0, 100), (100, -5), (100, 100000)):
+            with self.assertRaisesRegex(ValueError, "width|height"):
+                rxing.encode("Hello", "QR_CODE", width, height)
+
+    def test_encode_data_too_large(self):
+        with self.assertRaisesRegex(ValueError, "Data too large to encode as QR_CODE"):
+            rxing.encode("x" * 8000, "QR_CODE")
+        with self.assertRaisesRegex(ValueError, "Data too large"):
+            rxing.encode("x" * 5000, "DATA_MATRIX")
+        with self.assertRaisesRegex(ValueError, "too large to encode as DATA_MATRIX"):
+            rxing.encode("1" * 3200, "DATA_MATRIX")
+        rectangle = {"DATA_MATRIX_SHAPE": "RECTANGLE"}
+        with self.assertRaisesRegex(ValueError, "encoder panicked"):
+            rxing.encode("x" * 300, "DATA_MATRIX", hints_dict=rectangle)
+        with self.assertRaisesRegex(ValueError, "RXing encoding failed"):
+            rxing.encode("123", "EAN_13")
+
+    def test_validate_encodable(self):
+        self.assertIsNone(rxing.validate_encodable("590123412345", "EAN_13"))
+        self.assertIsNone(rxing.validate_encodable("5901234123457", "EAN_13"))
+        self.assertIsNone(rxing.validate_encodable("CODE-39 $5", "CODE_39"))
+        self.assertIsNone(rxing.validate_encodable("lower case", "CODE_39"))
+        cases = [
+            ("59012341234", "EAN_13", "12 digits.*got 11"),
+            ("5901234123458", "EAN_13", "check digit is '8' but should be '7'"),
+            ("59012341234X", "EAN_13", "'X' at index 11"),
+            ("12345", "ITF", "even number of digits"),
+            ("21234565", "UPC_E", "number system"),
+            ("ABC\u00e9", "CODE_39", "'\u00e9' at index 3"),
+            ("A12T", "CODABAR", "guards"),
+            ("x" * 5000, "QR_CODE", "too large"),
+        ]
+        for data, fmt, message in cases:
+            with self.assertRaisesRegex(ValueError, message):
+                rxing.validate_encodable(data, fmt)
+        with self.assertRaisesRegex(ValueError, "too large"):
+            rxing.validate_encodable("x" * 2000, "QR_CODE", {"ERROR_CORRECTION": "H"})
+        self.assertIsNone(rxing.validate_encodable("x" * 2000, "QR_CODE"))
+        with self.assertRaises(ValueError):
+            rxing.validate_encodable("x", "MAXICODE")
+
+    def test_encode_qr_mask(self):
+        matrices = [rxing.encode("Mask", "QR_CODE", qr_mask=mask) for mask in (2, 5)]
+        self.assertNotEqual(matrices[0].data, matrices[1].data)
+        again = rxing.encode("Mask", "QR_CODE", qr_mask=2)
+        self.assertEqual(matrices[0].data, again.data)
+        for matrix in matrices:
+            self.assertEqual(rxing.decode(matrix.to_pil_image()).text, "Mask")
+
+        with self.assertRaisesRegex(ValueError, "between 0 and 7"):
+            rxing.encode("Mask", "QR_CODE", qr_mask=8)
+        with self.assertRaisesRegex(ValueError, "only applies to QR_CODE"):
+            rxing.encode("Mask", "DATA_MATRIX", qr_mask=0)
+
+    def test_code_39_extended_round_trip(self):
+        hints = {"CODE_39_EXTENDED_MODE": True}
+        for data in ["part-no/a1b2", "A$B+C%", "lower case!?~"]:
+            matrix = rxing.encode(data, "CODE_39", 400, 80, extended=True)
+            image = matrix.to_pil_image().convert("L")
+            result = rxing.decode(image, hints)
+            self.assertEqual(result.text, data)
+            self.assertIs(result.code_39_extended, True)
+            self.assertNotEqual(rxing.decode(image).text, data)
+            self.assertIsNone(rxing.decode(image).code_39_extended)
+        basic = rxing.encode("ABC-123", "CODE_39", 400, 80, extended=False)
+        self.assertEqual(rxing.decode(basic.to_pil_image(), hints).text, "ABC-123")
+        with self.assertRaisesRegex(ValueError, "'a' at index 0"):
+            rxing.encode("abc", "CODE_39", 400, 80, extended=False)
+        with self.assertRaisesRegex(ValueError, "'\u00e9' at index 3"):
+            rxing.encode("caf\u00e9", "CODE_39", 400, 80, extended=True)
+        with self.assertRaises(ValueError):
+            rxing.encode("x", "QR_CODE", extended=True)
+
+    def test_capacity(self):
+        self.assertEqual(rxing.capacity("QR_CODE", version=1, mode="numeric"), 41)
+        self.assertEqual(rxing.capacity("QR_CODE", version=1, mode="alphanumeric"), 25)
+        self.assertEqual(rxing.capacity("QR_CODE", version=1), 17)
+        self.assertEqual(rxing.capacity("QR_CODE", version=1, mode="kanji"), 10)
+        self.assertEqual(rxing.capacity("QR_CODE", version=40, ecc="H"), 1273)
+        self.assertEqual(rxing.capacity("QR_CODE", ecc="L", mode="numeric"), 7089)
+        self.assertEqual(rxing.capacity("DATA_MATRIX", version=1, mode="numeric"), 6)
+        self.assertEqual(rxing.capacity("DATA_MATRIX"), 1556)
+        data = "x" * rxing.capacity("QR_CODE", ecc="H")
+        rxing.validate_encodable(data, "QR_CODE", {"ERROR_CORRECTION": "H"})
+        with self.assertRaisesRegex(ValueError, "too large"):
+            rxing.validate_encodable(data + "x", "QR_CODE", {"ERROR_CORRECTION": "H"})
+        for fmt, kwargs in [
+            ("QR_CODE", {"version": 41}),
+            ("QR_CODE", {"ecc": "X"}),
+            ("DATA_MATRIX", {"mode": "kanji"}),
+            ("DATA_MATRIX", {"ecc": "H"}),
+            ("CODE_128", {}),
+        ]:
+            with self.assertRaises(ValueError):
+                rxing.capacity(fmt, **kwargs)
+
+    def test_upce_to_upca(self):
+        self.assertEqual(rxing.upce_to_upca("01234565"), "012345000065")
+        self.assertEqual(rxing.upce_to_upca("0123456"), "012345000065")
+        self.assertEqual(rxing.upce_to_upca("04252614"), "042100005264")
+        for bad in ["01234564", "21234565", "0123"]:
+            with self.assertRaises(ValueError):
+                rxing.upce_to_upca(bad)
+
+        image = rxing.encode("01234565", "UPC_E", 300, 100).to_pil_image()
+        self.assertEqual(rxing.decode(image).text, "01234565")
+        result = rxing.decode(image, {"NORMALIZE_UPC_E_TO_UPC_A": True})
+        self.assertEqual(result.text, "012345000065")
+        self.assertEqual(result.barcode_format, rxing.decode(image).barcode_format)
+        self.assertEqual(result.upc_e_text, "01234565")
+        self.assertNotIn("UPC_E", result.result_metadata)
+
+    def test_encode_gs1(self):
+        ai_map = {"01": "09506000134352", "10": "ABC123", "17": "251231"}
+        matrix = rxing.encode_gs1(ai_map, "CODE_128", 400, 100)
+        result = rxing.decode(matrix.to_pil_image().convert("L"))
+        self.assertEqual(result.symbology_identifier, "]C1")
+        self.assertEqual(result.text, "010950600013435210ABC12317251231")
+
+        # the variable-length batch number is followed by an FNC1 separator
+        for barcode_format, identifier in (("DATA_MATRIX", "]d2"), ("QR_CODE", "]Q3")):
+            matrix = rxing.encode_gs1(ai_map, barcode_format, 200, 200, {"MARGIN": "2"})
+            result = rxing.decode(matrix.to_pil_image().convert("L"))
+            self.assertEqual(result.symbology_identifier, identifier)
+            self.assertEqual(result.text, "010950600013435210ABC123\x1d17251231")
+
+    def test_encode_gs1_validation(self):
+        with self.assertRaisesRegex(ValueError, "check digit"):
+            rxing.encode_gs1({"01": "09506000134353"})
+        with self.assertRaisesRegex(ValueError, "14 characters"):
+            rxing.encode_gs1({"01": "0950600013435"})
+        with self.assertRaisesRegex(ValueError, "numeric"):
+            rxing.encode_gs1({"17": "25123A"})
+        with self.assertRaisesRegex(ValueError, "GS1 character set"):
+            rxing.encode_gs1({"10": "ABC 123"})
+        with self.assertRaisesRegex(ValueError, "Unknown GS1 application identifier"):
+            rxing.encode_gs1({"999": "1"})
+        with self.assertRaises(ValueError):
+            rxing.encode_gs1({})
+        with self.assertRaises(ValueError):
+            rxing.encode_gs1({"01": "09506000134352"}, "EAN_13")
+
+    def test_encode_code_128_forced_code_set(self):
+        digits = "12345678901234567890"
+        set_b = rxing.encode(digits, "CODE_128", 1, 1, {"FORCE_CODE_SET": "B"})
+        set_c = rxing.encode(digits, "CODE_128", 1, 1, {"FORCE_CODE_SET": "C"})
+        # code set C packs two digits into each 11-module symbol character
+        self.assertEqual(set_b.width - set_c.width, 10 * 11)
+        result = rxing.decode(set_c.to_pil_image().convert("L"))
+        self.assertEqual(result.text, digits)
+
+        with self.assertRaisesRegex(ValueError, "odd number of digits"):
+            rxing.encode("123", "CODE_128", 1, 1, {"FORCE_CODE_SET": "C"})
+        with self.assertRaisesRegex(ValueError, "cannot encode 'a' at index 2"):
+            rxing.encode("AAa", "CODE_128", 1, 1, {"FORCE_CODE_SET": "A"})
+        with self.assertRaisesRegex(ValueError, "FORCE_CODE_SET"):
+            rxing.EncodeHints(force_code_set="D")
+
+    def test_encode_code_128_gs1_format(self):
+        hints = rxing.EncodeHints(gs1_format=True, force_code_set="c")
+        self.assertEqual(
+            repr(hints), "EncodeHints(force_code_set='C', gs1_format=True)"
+        )
+        matrix = rxing.encode("0109506000134352", "CODE_128", 1, 1, hints)
+        result = rxing.decode(matrix.to_pil_image().convert("L"))
+        self.assertEqual(result.symbology_identifier, "]C1")
+        self.assertEqual(result.text, "0109506000134352")
+
+    def test_encode_hints_object(self):
+        hints = rxing.EncodeHints(error_correction="H", margin=0)
+        self.assertEqual(repr(hints), "EncodeHints(error_correction='H', margin=0)")
+        matrix = rxing.encode("Hello", "QR_CODE", hints_dict=hints)
+        expected = rxing.encode(
+            "Hello", "QR_CODE", hints_dict={"ERROR_CORRECTION": "H", "MARGIN": "0"}
+        )
+        self.assertEqual(
+            (matrix.width, matrix.height), (expected.width, expected.height)
+        )
+        with self.assertRaises(TypeError):
+            rxing.encode("Hello", "QR_CODE", hints_dict=rxing.DecodeHints())
+
+    def test_encode_and_decode_from_threads(self):
+        # Both directions release the GIL; results must match a serial run.
+        from concurrent.futures import ThreadPoolExecutor
+
+        texts = [f"Thread {i}" for i in range(16)]
+
+        def round_trip(text):
+            png = rxing.encode_to_png(text, "QR_CODE")
+            return rxing.decode(png).text
+
+        with ThreadPoolExecutor(max_workers=4) as pool:
+            self.assertEqual(list(pool.map(round_trip, texts)), texts)
+
+    def test_encode_hints_validation(self):
+        hints = rxing.EncodeHints(error_correction="q", margin=2, qr_version=5)
+        self.assertEqual(hints.error_correction, "Q")
+        self.assertEqual(hints.margin, 2)
+        self.assertEqual(hints.qr_version, 5)
+        self.assertIsNone(hints.character_set)
+        for kwargs in (
+            {"error_correction": "Z"},
+            {"margin": -1},
+            {"qr_version": 41},
+            {"character_set": "klingon"},
+        ):
+            with self.assertRaises(ValueError, msg=kwargs):
+                rxing.EncodeHints(**kwargs)
+        with self.assertRaises(TypeError):
+            rxing.EncodeHints(margin="wide")
+
+        # The level must suit the format being encoded, for dicts too.
+        with self.assertRaisesRegex(ValueError, "L, M, Q or H"):
+            rxing.encode("Hello", "QR_CODE", hints_dict={"ERROR_CORRECTION": "5"})
+        pdf_hints = rxing.EncodeHints(error_correction="H")
+        with self.assertRaisesRegex(ValueError, "between 0 and 8"):
+            rxing.encode("Hello", "PDF_417", hints_dict=pdf_hints)
+        matrix = rxing.encode("Hello", "PDF_417", hints_dict={"ERROR_CORRECTION": 5})
+        self.assertGreater(matrix.width, 0)
+        with self.assertRaisesRegex(ValueError, "non-negative"):
+            rxing.encode("Hello", "QR_CODE", hints_dict={"MARGIN": "-1"})
+
+    def test_encode_hints_2d_formats(self):
+        def decode_text(data, barcode_format, hints, size=(200, 200)):
+            matrix = rxing.encode(data, barcode_format, *size, hints_dict=hints)
+            return rxing.decode(matrix.to_pil_image().convert("L")).text
+
+        text = "HELLO WORLD 12345"
+        full = rxing.encode(text, "PDF_417", 1, 1)
+        compact = rxing.encode(text, "PDF_417", 1, 1, {"PDF417_COMPACT": True})
+        self.assertLess(compact.width, full.width)
+        hints = {"PDF417_DIMENSIONS": (2, 2, 10, 30), "PDF417_COMPACTION": "text"}
+        self.assertEqual(decode_text(text, "PDF_417", hints, (400, 200)), text)
+        with self.assertRaises(ValueError):
+            rxing.encode("abc", "PDF_417", hints_dict={"PDF417_COMPACTION": "NUMERIC"})
+
+        compact_aztec = rxing.encode("AZ", "AZTEC", 1, 1, {"AZTEC_LAYERS": -1})
+        full_aztec = rxing.encode("AZ", "AZTEC", 1, 1, {"AZTEC_LAYERS": 3})
+        self.assertLess(compact_aztec.width, full_aztec.width)
+        self.assertEqual(decode_text("AZ", "AZTEC", {"AZTEC_LAYERS": -4}), "AZ")
+        with self.assertRaises(ValueError):
+            rxing.encode("A" * 200, "AZTEC", hints_dict={"AZTEC_LAYERS": -1})
+
+        shape = rxing.EncodeHints(data_matrix_shape="rectangle")
+        matrix = rxing.encode("ABCDEF", "DATA_MATRIX", 1, 1, shape)
+        self.assertEqual((matrix.width, matrix.height), (18, 8))
+        text = decode_text("ABCDEF", "DATA_MATRIX", shape, (240, 120))
+        self.assertEqual(text, "ABCDEF")
+        matrix = rxing.encode("AB", "DATA_MATRIX", 1, 1, {"MIN_SIZE": (16, 16)})
+        self.assertEqual((matrix.width, matrix.height), (16, 16))
+        with self.assertRaises(ValueError):
+            rxing.encode("A" * 40, "DATA_MATRIX", hints_dict={"MAX_SIZE": (12, 12)})
+        hints = {"FORCE_C40": True}
+        self.assertEqual(decode_text("ABCDEFGHIJ", "DATA_MATRIX", hints), "ABCDEFGHIJ")
+
+        data = "\x01a\x02b\x03c"
+        default = rxing.encode(data, "CODE_128", 1, 1)
+        compact = rxing.encode(data, "CODE_128", 1, 1, {"CODE128_COMPACT": True})
+        self.assertLess(compact.width, default.width)
+        hints = {"CODE128_COMPACT": True}
+        self.assertEqual(decode_text(data, "CODE_128", hints, (400, 80)), data)
+
+        image = rxing.encode(
+            "1234", "TELEPEN", 300, 80, {"TELEPEN_AS_NUMERIC": True}
+        ).to_pil_image()
+        result = rxing.decode(image, hints={"TELEPEN_AS_NUMERIC": True})
+        self.assertEqual(result.text, "1234")
+
+    def test_encode_hints_2d_validation(self):
+        hints = rxing.EncodeHints(
+            pdf417_compaction=2,
+            pdf417_dimensions=[1, 5, 3, 20],
+            aztec_layers=-2,
+            data_matrix_shape="square",
+            min_size=(10, 10),
+            code128_compact=True,
+        )
+        self.assertEqual(hints.pdf417_compaction, "BYTE")
+        self.assertEqual(hints.pdf417_dimensions, (1, 5, 3, 20))
+        self.assertEqual(hints.aztec_layers, -2)
+        self.assertEqual(hints.data_matrix_shape, "SQUARE")
+        self.assertEqual(hints.min_size, (10, 10))
+        self.assertIsNone(hints.max_size)
+        self.assertTrue(hints.code128_compact)
+        for bad in (
+            {"CODE128_COMPACT": True, "FORCE_CODE_SET": "B"},
+            {"FORCE_C40": True, "DATA_MATRIX_COMPACT": True},
+            {"MIN_SIZE": (30, 30), "MAX_SIZE": (10, 10)},
+            {"AZTEC_LAYERS": 33},
+            {"PDF417_DIMENSIONS": (3, 2, 3, 4)},
+            {"DATA_MATRIX_SHAPE": "round"},
+            {"PDF417_COMPACTION": "zip"},
+        ):
+            with self.assertRaises(ValueError, msg=bad):
+                rxing.encode("1", "QR_CODE", hints_dict=bad)
+            with self.assertRaises(ValueError, msg=bad):
+                rxing.EncodeHints(**{key.lower(): value for key, value in bad.items()})
+        with self.assertRaises(TypeError):
+            rxing.EncodeHints(pdf417_dimensions="1234")
+
+    def test_encode_to_png_colors_and_size(self):
+        png = rxing.encode_to_png(
+            "Hello PNG",
+            "QR_CODE",
+            module_size=5,
+            border=2,
+            fg=(20, 40, 160, 255),
+            bg=(255, 255, 255, 0),
+        )
+        self.assertTrue(png.startswith(b"\x89PNG"))
+        matrix = rxing.encode("Hello PNG", "QR_CODE", hints_dict={"MARGIN": "0"})
+        img = Image.open(io.BytesIO(png))
+        self.assertEqual(img.mode, "RGBA")
+        self.assertEqual(img.size, ((matrix.width + 4) * 5, (matrix.height + 4) * 5))
+        self.assertEqual(img.getpixel((0, 0)), (255, 255, 255, 0))
+        # Top-left finder pattern corner starts right after the border.
+        self.assertEqual(img.getpixel((10, 10)), (20, 40, 160, 255))
+        self.assertEqual(rxing.decode(png).text, "Hello PNG")
+
+    def test_encode_to_png_1d_bar_height(self):
+        png = rxing.encode_to_png("ABC-128", "CODE_128", module_size=2, bar_height=30)
+        img = Image.open(io.BytesIO(png))
+        self.assertEqual(img.size[1], (30 + 2 * 4) * 2)
+        self.assertEqual(rxing.decode(png).text, "ABC-128")
+
+    def test_encode_to_png_minimum_size(self):
+        png = rxing.encode_to_png("Hello PNG", "QR_CODE", width=300, height=300)
+        img = Image.open(io.BytesIO(png))
+        self.assertGreaterEqual(min(img.size), 300)
+        self.assertLess(max(img.size), 300 * 1.5)
+        self.assertEqual(rxing.decode(png).text, "Hello PNG")
+
+    def test_encode_to_png_pixel_format(self):
+        # IHDR: bit depth at byte 24, color type at byte 25 (0 gray, 2 RGB, 6 RGBA)
+        for pixel_format, depth, color_type, mode in [
+            ("1bit", 1, 0, "1"),
+            ("gray", 8, 0, "L"),
+            ("rgb", 8, 2, "RGB"),
+            ("rgba", 8, 6, "RGBA"),
+        ]:
+            png = rxing.encode_to_png("PIXELS", "QR_CODE", pixel_format=pixel_format)
+            self.assertEqual((png[24], png[25]), (depth, color_type))
+            self.assertEqual(Image.open(io.BytesIO(png)).mode, mode)
+            self.assertEqual(rxing.decode(png).text, "PIXELS")
+        white_on_black = rxing.encode_to_png(
+            "PIXELS",
+            "QR_CODE",
+            fg=(255, 255, 255, 255),
+            bg=(0, 0, 0, 255),
+            pixel_format="1bit",
+        )
+        self.assertEqual(white_on_black[24], 1)
+        sheet = rxing.encode_sheet([("A", "QR_CODE")], pixel_format="gray")
+        self.assertEqual((sheet[24], sheet[25]), (8, 0))
+
+        for kwargs in [
+            {"pixel_format": "rgb", "bg": (255, 255, 255, 0)},
+            {"pixel_format": "gray", "fg": (20, 40, 160, 255)},
+            {"pixel_format": "1bit", "fg": (64, 64, 64, 255)},
+            {"pixel_format": "1bit", "fg": (255, 255, 255, 255)},
+            {"pixel_format": "cmyk"},
+        ]:
+            with self.assertRaises(ValueError):
+                rxing.encode_to_png("PIXELS", "QR_CODE", **kwargs)
+        with tempfile.TemporaryDirectory() as directory:
+            path = os.path.join(directory, "code.png")
+            rxing.encode_to_file("PIXELS", "QR_CODE", path, pixel_format="1bit")
+            self.assertEqual(Image.open(path).mode, "1")
+            with self.assertRaisesRegex(ValueError, "only be written as PNG"):
+                bmp = os.path.join(directory, "code.bmp")
+                rxing.encode_to_file("PIXELS", "QR_CODE", bmp, pixel_format="1bit")
+
+    def test_encode_to_png_validation(self):
+        with self.assertRaises(ValueError):
+            rxing.encode_to_png("data", "QR_CODE", module_size=0)
+        with self.assertRaises(ValueError):
+            rxing.encode_to_png("data", "QR_CODE", border=2**31)
+        with self.assertRaises(ValueError):
+            rxing.encode_to_png("data", "QR_CODE", hints={"MARGIN": "1"})
+
+    def test_encode_many(self):
+        items = [
+            {"data": "First", "format": "QR_CODE"},
+            {"data": "abc", "format": "EAN_13"},
+            {"data": "12345", "format": "CODE_128", "width": 200, "height": 50},
+            {"data": "Rendered", "format": "DATA_MATRIX", "png": True},
+        ]
+        for workers in (None, 1, 3):
+            results = rxing.encode_many(items, workers=workers)
+            self.assertEqual(len(results), 4)
+            self.assertEqual(results[0].data, rxing.encode("First", "QR_CODE").data)
+            self.assertIsInstance(results[1], rxing.EncodeFailure)
+            self.assertFalse(results[1])
+            self.assertEqual((results[1].index, results[1].format), (1, "EAN_13"))
+            self.assertIn("12 or 13 digits", results[1].message)
+            self.assertEqual(results[2].width, 200)
+            self.assertEqual(results[3], rxing.encode_to_png("Rendered", "DATA_MATRIX"))
+        self.assertEqual(len([r for r in results if r]), 3)
+
+        self.assertEqual(rxing.encode_many([]), [])
+        with self.assertRaisesRegex(ValueError, "item 1"):
+            rxing.encode_many([items[0], {"data": "x"}])
+        with self.assertRaisesRegex(ValueError, "unknown key"):
+            rxing.encode_many([{"data": "x", "format": "QR_CODE", "size": 3}])
+        with self.assertRaises(ValueError):
+            rxing.encode_many(items, workers=0)
+
+    def test_encode_sheet_grid(self):
+        items = [("A-1", "QR_CODE"), ("B-2", "DATA_MATRIX"), ("C-3", "CODE_128")]
+        png = rxing.encode_sheet(items, columns=2, spacing=10)
+        cells = [Image.open(io.BytesIO(rxing.encode_to_png(*i))) for i in items]
+        pitch_w = max(cell.width for cell in cells)
+        pitch_h = max(cell.height for cell in cells)
+        img = Image.open(io.BytesIO(png))
+        self.assertEqual(img.size, (2 * pitch_w + 10, 2 * pitch_h + 10))
+        texts = sorted(result.text for result in rxing.decode_multiple(png))
+        self.assertEqual(texts, ["A-1", "B-2", "C-3"])
+
+        with self.assertRaisesRegex(ValueError, "item 1") as ctx:
+            rxing.encode_sheet([("A", "QR_CODE"), ("abc", "EAN_13")])
+        self.assertIsInstance(ctx.exception.__cause__, ValueError)
+        with self.assertRaises(ValueError):
+            rxing.encode_sheet([])
+        with self.assertRaises(ValueError):
+            rxing.encode_sheet(items, columns=0)
+
+    def test_encode_to_svg(self):
+        import xml.etree.ElementTree as ET
+
+        ns = "{http://www.w3.org/2000/svg}"
+        matrix = rxing.encode("Hello SVG", "QR_CODE", 1, 1, {"MARGIN": "0"})
+        svg = rxing.encode_to_svg("Hello SVG", "QR_CODE", module_size=3, border=2)
+        root = ET.fromstring(svg)
+        self.assertEqual(root.get("width"), str((matrix.width + 4) * 3))
+        view_box = f"0 0 {matrix.width + 4} {matrix.height + 4}"
+        self.assertEqual(root.get("viewBox"), view_box)
+        self.assertEqual(len(root.findall(f"{ns}path")), 1)
+
+        svg = rxing.encode_to_svg(
+            "Hello SVG", "QR_CODE", border=2, bg=(255, 255, 255, 0), shape="rects"
+        )
+        root = ET.fromstring(svg)
+        self.assertIsNone(root.find(f"{ns}rect"))
+        dark = set()
+        for rect in root.iter(f"{ns}rect"):
+            x, y, width = (int(rect.get(key)) for key in ("x", "y", "width"))
+            dark.update((x - 2 + i, y - 2) for i in range(width))
+        expected = {
+            (x, y)
+            for y, row in enumerate(matrix.data)
+            for x, bit in enumerate(row)
+            if bit
+        }
+        self.assertEqual(dark, expected)
+
+        svg = rxing.encode_to_svg("ABC-128", "CODE_128", bar_height=20, border=1)
+        self.assertIn("v20h", svg)
+        with self.assertRaises(ValueError):
+            rxing.encode_to_svg("x", "QR_CODE", shape="circles")
+        with self.assertRaises(ValueError):
+            rxing.encode_to_svg("x", "QR_CODE", hints={"MARGIN": 1})
+
+    def test_barcode_writer(self):
+        hints = {"ERROR_CORRECTION": "H"}
+        writer = rxing.BarcodeWriter("QR_CODE", hints)
+        self.assertEqual(writer.format, "qrcode")
+        for data in ("Label 1", "Label 2"):
+            matrix = writer.encode(data, 200, 200)
+            expected = rxing.encode(data, "QR_CODE", 200, 200, hints)
+            self.assertEqual(matrix.data, expected.data)
+            self.assertEqual(
+                writer.encode_to_png(data, module_size=2),
+                rxing.encode_to_png(data, "QR_CODE", module_size=2, hints=hints),
+            )
+            self.assertEqual(
+                writer.encode_to_svg(data, border=2),
+                rxing.encode_to_svg(data, "QR_CODE", border=2, hints=hints),
+            )
+        image = Image.open(io.BytesIO(writer.encode_to_png("Label 1", width=300)))
+        self.assertGreaterEqual(image.width, 300)
+
+        with self.assertRaises(ValueError):
+            rxing.BarcodeWriter("MAXICODE")
+        with self.assertRaises(ValueError):
+            writer.encode("x", 0, 100)
+        margin_writer = rxing.BarcodeWriter("QR_CODE", {"MARGIN": "1"})
+        self.assertEqual(margin_writer.encode("x", 1, 1).width, 23)
+        with self.assertRaises(ValueError):
+            margin_writer.encode_to_svg("x")
+
+        masked = writer.encode("Mask", 200, 200, qr_mask=5)
+        expected = rxing.encode("Mask", "QR_CODE", 200, 200, hints, qr_mask=5)
+        self.assertEqual(masked.data, expected.data)
+        with self.assertRaisesRegex(ValueError, "between 0 and 7"):
+            writer.encode("Mask", 200, 200, qr_mask=8)
+        code_39 = rxing.BarcodeWriter("CODE_39")
+        matrix = code_39.encode("part-no", 400, 80, extended=True)
+        expected = rxing.encode("part-no", "CODE_39", 400, 80, extended=True)
+        self.assertEqual(matrix.data, expected.data)
+        with self.assertRaisesRegex(ValueError, "'p' at index 0"):
+            code_39.encode("part-no", 400, 80, extended=False)
+
+    def test_encode_to_file_infers_format(self):
+        formats = {"qr.png": "PNG", "qr.jpg": "JPEG", "qr.bmp": "BMP"}
+        for name, pil_format in formats.items():
+            path = os.path.join(self.temp_dir, name)
+            rxing.encode_to_file("File out", "QR_CODE", path, width=200, height=200)
+            with Image.open(path) as img:
+                self.assertEqual(img.format, pil_format)
+                self.assertGreaterEqual(img.size[0], 200)
+                self.assertGreaterEqual(img.size[1], 200)
+            self.assertEqual(rxing.decode(path).text, "File out")
+
+    def test_encode_to_file_create_dirs(self):
+        path = os.path.join(self.temp_dir, "nested", "code128.png")
+        with self.assertRaises(FileNotFoundError):
+            rxing.encode_to_file("ABC-128", "CODE_128", path)
+        rxing.encode_to_file("ABC-128", "CODE_128", path, create_dirs=True)
+        self.assertEqual(rxing.decode(path).text, "ABC-128")
+        os.remove(path)
+        os.rmdir(os.path.dirname(path))
+
+    def test_encode_to_file_rejects_unknown_extension(self):
+        path = os.path.join(self.temp_dir, "qr.unknown")
+        with self.assertRaisesRegex(ValueError, "extension"):
+            rxing.encode_to_file("data", "QR_CODE", path)
+
     def test_encode_invalid_format(self):
         with self.assertRaisesRegex(ValueError, "RXing encoding failed"):
             rxing.encode("data", format="INVALID_FORMAT_XYZ")
 
 
+class TestSupportedFormats(unittest.TestCase):
+    def test_supported_formats_match_constants(self):
+        format_module = vars(rxing.BarcodeFormat)
+        constants = {v for k, v in format_module.items() if k.isupper()}
+        read_formats = rxing.supported_read_formats()
+        write_formats = rxing.supported_write_formats()
+        self.assertTrue(set(read_formats) <= constants)
+        self.assertTrue(set(write_formats) <= constants)
+        self.assertIn(rxing.BarcodeFormat.QR_CODE, read_formats)
+        self.assertIn(rxing.BarcodeFormat.QR_CODE, write_formats)
+
+    def test_barcode_format_enum(self):
+        formats = list(rxing.BarcodeFormat)
+        self.assertIn(rxing.BarcodeFormat.QR_CODE, formats)
+        self.assertTrue(set(rxing.supported_read_formats()) <= set(formats))
+        qr = rxing.BarcodeFormat.QR_CODE
+        self.assertIsInstance(qr, str)
+        self.assertEqual(qr, "qrcode")
+        self.assertEqual(str(qr), "qrcode")
+        self.assertIs(rxing.BarcodeFormat("QR_CODE"), qr)
+        self.assertIs(rxing.BarcodeFormat("qrcode"), qr)
+        with self.assertRaises(ValueError):
+            rxing.BarcodeFormat("NOT_A_FORMAT")
+
+        matrix = rxing.encode("Enum", qr, 100, 100)
+        result = rxing.decode(
+            matrix.to_pil_image().convert("L"), {"POSSIBLE_FORMATS": [qr]}
+        )
+        self.assertEqual(result.barcode_format, qr)
+        self.assertIs(rxing.BarcodeFormat(result.barcode_format), qr)
+
+    def test_maxicode_is_decode_only(self):
+        self.assertIn(rxing.BarcodeFormat.MAXICODE, rxing.supported_read_formats())
+        self.assertNotIn(rxing.BarcodeFormat.MAXICODE, rxing.supported_write_formats())
+
+
+    def test_micro_qr_is_decode_only(self):
+        for fmt in (
+            rxing.BarcodeFormat.MICRO_QR_CODE,
+            rxing.BarcodeFormat.RECTANGULAR_MICRO_QR_CODE,
+        ):
+            self.assertIn(fmt, rxing.supported_read_formats())
+            self.assertNotIn(fmt, rxing.supported_write_formats())
+
 class TestBitMatrixMethods(unittest.TestCase):
     def setUp(self):
         self.test_data = "Test Matrix"
@@ -283,6 +2731,21 @@ class TestBitMatrixMethods(unittest.TestCase):
             np_array.shape, (self.matrix.height, self.matrix.width)
         )  # Numpy shape is (rows, cols)
 
+    def test_to_numpy_and_to_bytes(self):
+        matrix = rxing.BitMatrix.from_rows([[True, False, False], [True, False, True]])
+        self.assertEqual(matrix.to_bytes(), bytes([1, 0, 0, 1, 0, 1]))
+        self.assertEqual(matrix.to_bytes(row_major=False), bytes([1, 1, 0, 0, 0, 1]))
+        array = matrix.to_numpy()
+        self.assertEqual(array.dtype, bool)
+        self.assertEqual(array.tolist(), matrix.data)
+        self.assertEqual(matrix.to_numpy(np.uint8).tolist(), [[1, 0, 0], [1, 0, 1]])
+
+        large = rxing.encode("A" * 500, "PDF_417", 1, 1)
+        modules = large.to_bytes()
+        rebuilt = rxing.BitMatrix.from_bytes(modules, large.width, large.height)
+        self.assertEqual(rebuilt, large)
+        self.assertEqual(large.to_numpy().shape, (large.height, large.width))
+
     def test_save_bitmatrix(self):
         if self.matrix is None:
             self.skipTest("Matrix not created in setUp")
@@ -295,6 +2758,23 @@ class TestBitMatrixMethods(unittest.TestCase):
         self.assertIsNotNone(decoded_result, "Decoding saved BitMatrix image failed.")
         self.assertEqual(decoded_result.text, self.test_data)
 
+    def test_save_bitmatrix_scale_and_margin(self):
+        matrix = rxing.encode("Hello", "QR_CODE", 1, 1, {"MARGIN": "0"})
+        for ext in ("png", "bmp", "pgm", "gif"):
+            save_path = os.path.join(self.temp_dir, f"scaled.{ext}")
+            matrix.save(save_path, scale=3, margin=2)
+            with Image.open(save_path) as img:
+                self.assertEqual(img.size, ((matrix.width + 4) * 3,) * 2)
+            self.assertEqual(rxing.decode(save_path).text, "Hello")
+        legacy_path = os.path.join(self.temp_dir, "legacy.img")
+        matrix.save(legacy_path, "PNG")
+        with open(legacy_path, "rb") as f:
+            self.assertTrue(f.read().startswith(b"\x89PNG"))
+        with self.assertRaises(ValueError):
+            matrix.save(os.path.join(self.temp_dir, "matrix.unknown"))
+        with self.assertRaises(ValueError):
+            matrix.save(os.path.join(self.temp_dir, "matrix.png"), scale=0)
+
     def test_str_bitmatrix(self):
         if self.matrix is None:
             self.skipTest("Matrix not created in setUp")
@@ -303,6 +2783,53 @@ class TestBitMatrixMethods(unittest.TestCase):
         self.assertTrue(len(s) > 0)
         self.assertIn("██", s)  # Check for presence of "black" block character
 
+    def test_from_rows_round_trip(self):
+        if self.matrix is None:
+            self.skipTest("Matrix not created in setUp")
+        rebuilt = rxing.BitMatrix.from_rows(self.matrix.data)
+        self.assertEqual(
+            (rebuilt.width, rebuilt.height), (self.matrix.width, self.matrix.height)
+        )
+        self.assertEqual(rebuilt.data, self.matrix.data)
+
+    def test_from_rows_rejects_ragged_rows(self):
+        with self.assertRaisesRegex(ValueError, "rectangular"):
+            rxing.BitMatrix.from_rows([[True, False], [True]])
+        with self.assertRaises(ValueError):
+            rxing.BitMatrix.from_rows([])
+
+    def test_from_bytes(self):
+        matrix = rxing.BitMatrix.from_bytes(bytes([1, 0, 0, 255, 0, 1]), 3, 2)
+        self.assertEqual(matrix.data, [[True, False, False], [True, False, True]])
+        with self.assertRaises(ValueError):
+            rxing.BitMatrix.from_bytes(bytes(5), 3, 2)
+
+
+    def test_invert(self):
+        matrix = rxing.BitMatrix.from_rows([[True, False, False], [False, True, True]])
+        inverted = matrix.invert()
+        self.assertEqual(inverted.data, [[False, True, True], [True, False, False]])
+        self.assertEqual(matrix.data, [[True, False, False], [False, True, True]])
+        if self.matrix is not None:
+            self.assertEqual(self.matrix.invert().invert().data, self.matrix.data)
+
+    def test_bitmatrix_eq_and_diff(self):
+        golden = rxing.encode("Golden", "QR_CODE", 100, 100)
+        same = rxing.encode("Golden", "QR_CODE", 100, 100)
+        self.assertEqual(golden, same)
+        self.assertEqual(golden.diff(same), [])
+        self.assertNotEqual(golden, "Golden")
+
+        rows = golden.data
+        rows[7][3] = not rows[7][3]
+        one_off = rxing.BitMatrix.from_rows(rows)
+        self.assertNotEqual(golden, one_off)
+        self.assertEqual(golden.diff(one_off), [(3, 7)])
+
+        larger = rxing.encode("Golden", "QR_CODE", 200, 200)
+        self.assertNotEqual(golden, larger)
+        with self.assertRaisesRegex(ValueError, "100x100"):
+            golden.diff(larger)
 
 if __name__ == "__main__":
     print("Running rxing Python interface tests...")